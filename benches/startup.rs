@@ -0,0 +1,121 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shell startup cost of the monolithic bash script against the split loader.
+//!
+//! Run with `cargo bench --bench startup`. Completions are generated from
+//! `tests/fixtures/help`, or from a real bd with `BD_COMPLETE_BENCH_BD=bd`.
+//! Each case sources its script `BD_COMPLETE_BENCH_ITERS` times (default
+//! 200) in one bash process; the cost of an empty loop is subtracted.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+/// Run bd-complete with the help source this benchmark uses.
+fn bd_complete(args: &[&str], extra: &[&Path]) {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_bd-complete"));
+    cmd.args(args).args(extra);
+    match std::env::var("BD_COMPLETE_BENCH_BD") {
+        Ok(bd) => cmd.args(["--bd", &bd]),
+        Err(_) => cmd
+            .arg("--help-dir")
+            .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/help")),
+    };
+    let status = cmd.status().expect("failed to run bd-complete");
+    assert!(status.success(), "bd-complete {args:?} failed");
+}
+
+/// Best wall time over `RUNS` of a bash process running `body` `iters` times.
+fn time_bash(body: &str, iters: usize) -> Duration {
+    let script = format!("for ((n = 0; n < {iters}; n++)); do {body}; done");
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            let status = Command::new("bash")
+                .args(["--norc", "--noprofile", "-c", &script])
+                .status()
+                .expect("failed to run bash");
+            assert!(status.success(), "bash failed on: {body}");
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn main() {
+    if Command::new("bash").arg("--version").output().is_err() {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let iters: usize = std::env::var("BD_COMPLETE_BENCH_ITERS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(200);
+
+    let dir = std::env::temp_dir().join(format!("bd-complete-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let monolithic = dir.join("bd.bash");
+    let split = dir.join("split");
+    bd_complete(&["generate", "--shell", "bash", "--output"], &[&monolithic]);
+    bd_complete(&["generate", "--shell", "bash", "--split-dir"], &[&split]);
+    let loader: PathBuf = split.join("bd");
+    let fragments: u64 = std::fs::read_dir(split.join("bd.d"))
+        .unwrap()
+        .map(|e| file_size(&e.unwrap().path()))
+        .sum();
+
+    let baseline = time_bash(":", iters);
+    let per_source = |body: String| {
+        let elapsed = time_bash(&body, iters).saturating_sub(baseline);
+        elapsed.as_secs_f64() * 1e6 / iters as f64
+    };
+    // Sourcing again redefines the functions but leaves __bd_load's check
+    // intact, so unset the fragment to time its first load each round
+    let cases = [
+        ("monolithic", format!("source '{}'", monolithic.display())),
+        ("split loader", format!("source '{}'", loader.display())),
+        (
+            "split loader + 1 fragment",
+            format!(
+                "source '{}'; unset -f _bd_create; __bd_load create",
+                loader.display()
+            ),
+        ),
+    ];
+
+    println!("sourcing, mean of {iters} (best of {RUNS} runs)");
+    println!(
+        "  {:<28}{:>10} bytes",
+        "monolithic script",
+        file_size(&monolithic)
+    );
+    println!(
+        "  {:<28}{:>10} bytes (+{fragments} in fragments)",
+        "split loader",
+        file_size(&loader)
+    );
+    println!();
+    for (name, body) in cases {
+        println!("  {name:<28}{:>10.1} µs", per_source(body));
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
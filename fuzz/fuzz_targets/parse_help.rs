@@ -0,0 +1,33 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feed arbitrary text to the help parser. Run with:
+//!
+//! ```text
+//! cargo +nightly fuzz run parse_help -- -dict=fuzz/help.dict
+//! ```
+
+#![no_main]
+
+use bd_complete::{parse_help_output, parse_usage_args};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = parse_help_output(text);
+        for line in text.lines() {
+            let _ = parse_usage_args(line);
+        }
+    }
+});
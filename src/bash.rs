@@ -12,8 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::command_tree::{Command, CommandTree, Flag};
-use std::io::{self, Write};
+use crate::command_tree::{Command, CommandTree, Flag, ValueCompletion};
+use crate::error::BdCompleteError;
+use crate::options::{GenerateOptions, Order};
+use crate::quote;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 const LICENSE_HEADER: &str = r#"# Copyright 2006 JT Perry
 #
@@ -30,7 +35,23 @@ const LICENSE_HEADER: &str = r#"# Copyright 2006 JT Perry
 # limitations under the License."#;
 
 /// Generate a complete Bash completion script for the given command tree.
-pub fn generate_bash_completion(tree: &CommandTree, out: &mut dyn Write) -> io::Result<()> {
+pub fn generate_bash_completion(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    let tree = opts.ordered(tree);
+    let tree = &*tree;
+    quote::check_tree(tree)?;
+    quote::check_aliases(&opts.aliases)?;
+    write_bash_script(tree, opts, out).map_err(BdCompleteError::Write)
+}
+
+fn write_bash_script(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
     writeln!(out, "#!/usr/bin/env bash")?;
     writeln!(out, "{LICENSE_HEADER}")?;
     writeln!(out)?;
@@ -47,7 +68,7 @@ pub fn generate_bash_completion(tree: &CommandTree, out: &mut dyn Write) -> io::
     writeln!(out)?;
 
     // Build a case statement that walks the command tree
-    generate_command_dispatch(tree, out)?;
+    generate_command_dispatch(tree, Scope::Whole, opts, out)?;
 
     writeln!(out, "}}")?;
     writeln!(out)?;
@@ -56,29 +77,209 @@ pub fn generate_bash_completion(tree: &CommandTree, out: &mut dyn Write) -> io::
         "complete -F {func_name} {}",
         tree.root.name
     )?;
+    write_alias_completions(&tree.root.name, opts, out)?;
+
+    Ok(())
+}
+
+/// Generate a split Bash completion: a small loader in `dir/<root>` that
+/// knows only the top-level commands, and one fragment per top-level command
+/// in `dir/<root>.d/<command>.bash`, sourced the first time it's needed.
+pub fn generate_bash_split(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, BdCompleteError> {
+    let tree = opts.ordered(tree);
+    let tree = &*tree;
+    quote::check_tree(tree)?;
+    quote::check_aliases(&opts.aliases)?;
+    write_bash_split(tree, opts, dir).map_err(BdCompleteError::Write)
+}
+
+fn write_bash_split(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    dir: &Path,
+) -> io::Result<Vec<PathBuf>> {
+    let root = &tree.root.name;
+    let fragment_dir = dir.join(format!("{root}.d"));
+    std::fs::create_dir_all(&fragment_dir)?;
+
+    let loader = dir.join(root);
+    let mut out = BufWriter::new(File::create(&loader)?);
+    write_bash_loader(tree, opts, &mut out)?;
+    out.flush()?;
+    let mut written = vec![loader];
+
+    for (name, _) in tree.root.subcommands.iter().filter(|(_, c)| opts.includes(c)) {
+        let fragment = fragment_dir.join(format!("{name}.bash"));
+        let mut out = BufWriter::new(File::create(&fragment)?);
+        write_bash_fragment(tree, name, opts, &mut out)?;
+        out.flush()?;
+        written.push(fragment);
+    }
+    Ok(written)
+}
+
+/// Write the loader of a split completion.
+fn write_bash_loader(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let root = &tree.root.name;
+    writeln!(out, "#!/usr/bin/env bash")?;
+    writeln!(out, "{LICENSE_HEADER}")?;
+    writeln!(out)?;
+    writeln!(out, "# Bash completion loader for {root}")?;
+    writeln!(out, "# Generated by bd-complete")?;
+    writeln!(out, "#")?;
+    writeln!(out, "# Completions for each command live in {root}.d/<command>.bash next to")?;
+    writeln!(out, "# this file and are sourced the first time they are needed.")?;
+    writeln!(out)?;
+
+    // Resolve the fragment directory now; relative to $PWD it would break on cd
+    let dir_var = format!("__{}_fragments", identifier(root));
+    writeln!(out, "{dir_var}=${{BASH_SOURCE[0]%/*}}")?;
+    writeln!(out, "[[ ${dir_var} == \"${{BASH_SOURCE[0]}}\" ]] && {dir_var}=.")?;
+    writeln!(out, "[[ ${dir_var} == /* ]] || {dir_var}=$PWD/${dir_var}")?;
+    writeln!(out, "{dir_var}+=/{root}.d")?;
+    writeln!(out)?;
+    writeln!(out, "__{root}_load() {{")?;
+    writeln!(out, "    declare -F \"_{root}_$1\" >/dev/null && return")?;
+    writeln!(out, "    # shellcheck source=/dev/null")?;
+    writeln!(out, "    source \"${dir_var}/$1.bash\" 2>/dev/null")?;
+    writeln!(out, "    declare -F \"_{root}_$1\" >/dev/null")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    let func_name = format!("_{root}");
+    writeln!(out, "{func_name}() {{")?;
+    writeln!(out, "    local cur prev words cword")?;
+    writeln!(out, "    _init_completion || return")?;
+    writeln!(out)?;
+    generate_command_dispatch(tree, Scope::Loader, opts, out)?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "complete -F {func_name} {root}")?;
+    write_alias_completions(root, opts, out)?;
+    Ok(())
+}
+
+/// `name` as a bash variable name: unlike function names, those can only
+/// hold letters, digits and `_`, so `bd-dev` becomes `bd_dev`.
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// Register `_<root>` for each of `opts.aliases` as well. An alias with
+/// prefix arguments gets a wrapper that splices them in after the command
+/// name, as the shell would, so `bdc --pa` is walked as `bd create --pa`.
+fn write_alias_completions(root: &str, opts: &GenerateOptions, out: &mut dyn Write) -> io::Result<()> {
+    for alias in &opts.aliases {
+        let name = &alias.name;
+        if alias.prefix.is_empty() {
+            writeln!(out, "complete -F _{root} {name}")?;
+            continue;
+        }
+        let head = std::iter::once(root)
+            .chain(alias.prefix.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let words: Vec<String> = alias.prefix.iter().map(|w| quote::bash(w)).collect();
+        writeln!(out)?;
+        writeln!(out, "# {name} runs: {head}")?;
+        writeln!(out, "__{root}_alias_{name}() {{")?;
+        // _init_completion lines COMP_WORDS up against COMP_LINE, so both change
+        writeln!(out, "    local head={}", quote::bash(&head))?;
+        writeln!(out, "    local COMP_POINT=$((COMP_POINT + ${{#head}} - ${{#COMP_WORDS[0]}}))")?;
+        writeln!(out, "    local COMP_LINE=$head${{COMP_LINE#\"${{COMP_WORDS[0]}}\"}}")?;
+        writeln!(out, "    local COMP_WORDS=({root} {} \"${{COMP_WORDS[@]:1}}\")", words.join(" "))?;
+        writeln!(out, "    local COMP_CWORD=$((COMP_CWORD + {}))", words.len())?;
+        writeln!(out, "    _{root}")?;
+        writeln!(out, "}}")?;
+        writeln!(out, "complete -F __{root}_alias_{name} {name}")?;
+    }
+    Ok(())
+}
 
+/// Write the fragment of a split completion for the top-level command `name`.
+/// It defines `_<root>_<name>`, which the loader calls with the index of the
+/// command's word and which completes everything under that command.
+fn write_bash_fragment(
+    tree: &CommandTree,
+    name: &str,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let root = &tree.root.name;
+    writeln!(out, "{LICENSE_HEADER}")?;
+    writeln!(out)?;
+    writeln!(out, "# Bash completion for {root} {name}, sourced by the {root} loader")?;
+    writeln!(out, "# Generated by bd-complete")?;
+    writeln!(out)?;
+    writeln!(out, "# cur, prev, words and cword are the caller's locals")?;
+    writeln!(out, "# shellcheck disable=SC2154")?;
+    writeln!(out, "_{root}_{name}() {{")?;
+    generate_command_dispatch(tree, Scope::Fragment(name), opts, out)?;
+    writeln!(out, "}}")?;
     Ok(())
 }
 
-fn generate_command_dispatch(tree: &CommandTree, out: &mut dyn Write) -> io::Result<()> {
+/// The part of the tree one completion function covers.
+#[derive(Clone, Copy, PartialEq)]
+enum Scope<'a> {
+    /// The whole tree
+    Whole,
+    /// The root only; top-level commands are handed to their fragments
+    Loader,
+    /// One top-level command and everything under it
+    Fragment(&'a str),
+}
+
+fn generate_command_dispatch(
+    tree: &CommandTree,
+    scope: Scope,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let (cmd, path, start) = match scope {
+        Scope::Whole | Scope::Loader => (&tree.root, "", "1"),
+        // Fragments pick up the walk after their own command word
+        Scope::Fragment(name) => (&tree.root.subcommands[name], name, "$(($1 + 1))"),
+    };
+    let cmd_path = if path.is_empty() {
+        "\"\"".to_string()
+    } else {
+        case_pattern(path)
+    };
+
     // We need to determine which subcommand the user is typing.
-    // Walk words[1..cword-1] to find the deepest matching command.
+    // Walk words[1..cword-1], descending only into children of the current path.
+    // The first word that is neither a flag nor a child starts the positionals.
     writeln!(out, "    # Walk the command tree to find the active subcommand")?;
-    writeln!(out, "    local cmd_path=\"\"")?;
-    writeln!(out, "    local i=1")?;
+    writeln!(out, "    local cmd_path={cmd_path}")?;
+    writeln!(out, "    local positional=0")?;
+    writeln!(out, "    local i={start}")?;
     writeln!(out, "    while [[ $i -lt $cword ]]; do")?;
     writeln!(out, "        case \"${{words[$i]}}\" in")?;
-
-    // Collect all known subcommand names (and their sub-subcommands)
-    let all_subcmds = collect_all_command_names(&tree.root);
-    if !all_subcmds.is_empty() {
-        let pattern = all_subcmds.join("|");
-        writeln!(out, "            {pattern})")?;
-        writeln!(out, "                cmd_path=\"${{cmd_path:+$cmd_path }}${{words[$i]}}\"")?;
-        writeln!(out, "                ;;")?;
-    }
-
+    writeln!(out, "            --*=*)")?;
+    writeln!(out, "                ;;")?;
+    writeln!(out, "            -*)")?;
+    generate_value_flag_skip(tree, cmd, path, scope, opts, out)?;
+    writeln!(out, "                ;;")?;
     writeln!(out, "            *)")?;
+    writeln!(out, "                if [[ $positional -eq 0 ]]; then")?;
+    writeln!(out, "                    case \"$cmd_path\" in")?;
+    generate_walk_cases(cmd, path, scope == Scope::Loader, opts, out)?;
+    writeln!(out, "                        *)")?;
+    writeln!(out, "                            positional=1")?;
+    writeln!(out, "                            ;;")?;
+    writeln!(out, "                    esac")?;
+    writeln!(out, "                fi")?;
     writeln!(out, "                ;;")?;
     writeln!(out, "        esac")?;
     writeln!(out, "        ((i++))")?;
@@ -88,89 +289,226 @@ fn generate_command_dispatch(tree: &CommandTree, out: &mut dyn Write) -> io::Res
     // Now dispatch based on cmd_path
     writeln!(out, "    case \"$cmd_path\" in")?;
 
-    // Generate cases for each subcommand path
-    generate_cases_recursive(&tree.root, "", &tree.global_flags, out)?;
+    if scope == Scope::Loader {
+        // The walk stopped on a top-level command; its fragment takes over
+        let root = &tree.root.name;
+        for (name, _) in tree.root.subcommands.iter().filter(|(_, c)| opts.includes(c)) {
+            writeln!(out, "        {})", case_pattern(name))?;
+            writeln!(out, "            __{root}_load {name} && _{root}_{name} \"$i\"")?;
+            writeln!(out, "            ;;")?;
+        }
+    } else {
+        // Generate cases for each subcommand path
+        generate_cases_recursive(tree, cmd, path, opts, out)?;
+    }
 
-    // Default: top-level completions
+    // Default: completions for the command this function covers
     writeln!(out, "        *)")?;
-    generate_completions_for_command(
-        &tree.root,
-        &tree.global_flags,
-        "            ",
-        out,
-    )?;
+    generate_completions_for_command(tree, cmd, opts, "            ", out)?;
     writeln!(out, "            ;;")?;
     writeln!(out, "    esac")?;
 
     Ok(())
 }
 
-fn generate_cases_recursive(
+/// Emit the walker's rule for skipping the value word after a flag that takes one.
+/// Patterns are "<cmd_path>:<flag>"; global flags match under any path.
+fn generate_value_flag_skip(
+    tree: &CommandTree,
     cmd: &Command,
     path: &str,
-    global_flags: &[Flag],
+    scope: Scope,
+    opts: &GenerateOptions,
     out: &mut dyn Write,
 ) -> io::Result<()> {
-    for (name, subcmd) in &cmd.subcommands {
-        let sub_path = if path.is_empty() {
-            name.clone()
-        } else {
-            format!("{path} {name}")
-        };
+    let mut patterns: Vec<String> = Vec::new();
+    for flag in tree.global_flags.iter().filter(|f| f.takes_value()) {
+        patterns.extend(flag_spellings(flag).iter().map(|f| format!("*:{f}")));
+    }
+    // The loader only walks up to the first command
+    let recurse = scope != Scope::Loader;
+    collect_value_flag_patterns(cmd, path, recurse, opts, &mut patterns);
+    patterns.dedup();
+
+    if patterns.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "                case \"$cmd_path:${{words[$i]}}\" in")?;
+    writeln!(out, "                    {})", patterns.join("|"))?;
+    writeln!(out, "                        ((i++))")?;
+    writeln!(out, "                        ;;")?;
+    writeln!(out, "                esac")?;
+    Ok(())
+}
 
-        // Also handle aliases
-        let mut patterns = vec![sub_path.clone()];
-        for alias in &subcmd.aliases {
-            let alias_path = if path.is_empty() {
-                alias.clone()
-            } else {
-                format!("{path} {alias}")
-            };
-            patterns.push(alias_path);
+fn collect_value_flag_patterns(
+    cmd: &Command,
+    path: &str,
+    recurse: bool,
+    opts: &GenerateOptions,
+    patterns: &mut Vec<String>,
+) {
+    for flag in cmd.flags.iter().filter(|f| f.takes_value()) {
+        for spelling in flag_spellings(flag) {
+            let pattern = quote::bash(&format!("{path}:{spelling}"));
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
         }
+    }
+    if !recurse {
+        return;
+    }
+    for (name, subcmd) in cmd.subcommands.iter().filter(|(_, c)| opts.includes(c)) {
+        collect_value_flag_patterns(subcmd, &join_path(path, name), true, opts, patterns);
+    }
+}
 
-        let pattern = patterns
-            .iter()
-            .map(|p| if p.contains(' ') { format!("\"{p}\"") } else { p.clone() })
-            .collect::<Vec<_>>()
-            .join("|");
-        writeln!(out, "        {pattern})")?;
-        generate_completions_for_command(subcmd, global_flags, "            ", out)?;
+/// Emit one walker case per command path, mapping child names and aliases
+/// to the canonical child path. With `stop`, the walk ends at the first child
+/// instead of descending into it.
+fn generate_walk_cases(
+    cmd: &Command,
+    path: &str,
+    stop: bool,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let subcommands: Vec<(&String, &Command)> = cmd
+        .subcommands
+        .iter()
+        .filter(|(_, c)| opts.includes(c))
+        .collect();
+    if subcommands.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "                        {})", case_pattern(path))?;
+    writeln!(out, "                            case \"${{words[$i]}}\" in")?;
+    for &(name, subcmd) in &subcommands {
+        let mut names = vec![name.as_str()];
+        names.extend(subcmd.aliases.iter().map(String::as_str));
+        writeln!(out, "                                {})", case_alternatives(&names))?;
+        writeln!(
+            out,
+            "                                    cmd_path={}",
+            case_pattern(&join_path(path, name))
+        )?;
+        if stop {
+            writeln!(out, "                                    break")?;
+        }
+        writeln!(out, "                                    ;;")?;
+    }
+    writeln!(out, "                                *)")?;
+    writeln!(out, "                                    positional=1")?;
+    writeln!(out, "                                    ;;")?;
+    writeln!(out, "                            esac")?;
+    writeln!(out, "                            ;;")?;
+
+    if stop {
+        return Ok(());
+    }
+    for (name, subcmd) in subcommands {
+        generate_walk_cases(subcmd, &join_path(path, name), false, opts, out)?;
+    }
+    Ok(())
+}
+
+fn generate_cases_recursive(
+    tree: &CommandTree,
+    cmd: &Command,
+    path: &str,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    for (name, subcmd) in cmd.subcommands.iter().filter(|(_, c)| opts.includes(c)) {
+        // The walker already resolved aliases to the canonical path
+        let sub_path = join_path(path, name);
+        writeln!(out, "        {})", case_pattern(&sub_path))?;
+        generate_completions_for_command(tree, subcmd, opts, "            ", out)?;
         writeln!(out, "            ;;")?;
 
         // Recurse into sub-subcommands
         if !subcmd.subcommands.is_empty() {
-            generate_cases_recursive(subcmd, &sub_path, global_flags, out)?;
+            generate_cases_recursive(tree, subcmd, &sub_path, opts, out)?;
         }
     }
     Ok(())
 }
 
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path} {name}")
+    }
+}
+
+/// Quote a command path for use as a case pattern or assignment.
+fn case_pattern(path: &str) -> String {
+    quote::bash(path)
+}
+
+/// Quote a name or flag spelling as a single word. Leading dashes need no
+/// quoting, so `--verbose` stays bare like the name after it.
+fn word(name: &str) -> String {
+    if quote::is_safe_word(name.trim_start_matches('-')) {
+        return name.to_string();
+    }
+    quote::bash(name)
+}
+
+/// Join names into `case` alternatives, each quoted on its own so a `|`
+/// or `)` inside a name stays part of that name.
+fn case_alternatives<S: AsRef<str>>(names: &[S]) -> String {
+    let words: Vec<String> = names.iter().map(|n| word(n.as_ref())).collect();
+    words.join("|")
+}
+
+/// Quote words for `compgen -W`, which expands its word list once more:
+/// each word is quoted for that expansion and the whole list for the script.
+fn word_list<S: AsRef<str>>(words: &[S]) -> String {
+    let words: Vec<String> = words.iter().map(|w| word(w.as_ref())).collect();
+    quote::bash(&words.join(" "))
+}
+
+/// All spellings of a flag on the command line, e.g. ["--priority", "-p"].
+fn flag_spellings(flag: &Flag) -> Vec<String> {
+    let mut spellings = vec![format!("--{}", flag.long)];
+    if let Some(s) = flag.short {
+        spellings.push(format!("-{s}"));
+    }
+    spellings
+}
+
 fn generate_completions_for_command(
+    tree: &CommandTree,
     cmd: &Command,
-    global_flags: &[Flag],
+    opts: &GenerateOptions,
     indent: &str,
     out: &mut dyn Write,
 ) -> io::Result<()> {
+    // At the root, persistent flags are also among the command's own
+    let global_flags: Vec<&Flag> = tree
+        .global_flags
+        .iter()
+        .filter(|g| !cmd.flags.iter().any(|f| f.long == g.long))
+        .collect();
     // Check if completing a flag value (prev is a flag that takes a value)
     let flags_with_values: Vec<&Flag> = cmd
         .flags
         .iter()
-        .chain(global_flags.iter())
-        .filter(|f| f.value_type.is_some())
+        .chain(global_flags.iter().copied())
+        .filter(|f| f.takes_value())
         .collect();
 
     if !flags_with_values.is_empty() {
         writeln!(out, "{indent}case \"$prev\" in")?;
         for flag in &flags_with_values {
-            let mut flag_patterns = vec![format!("--{}", flag.long)];
-            if let Some(s) = flag.short {
-                flag_patterns.push(format!("-{s}"));
-            }
-            let pattern = flag_patterns.join("|");
+            let pattern = flag_spellings(flag).join("|");
             writeln!(out, "{indent}    {pattern})")?;
             // Generate value completions based on type
-            generate_flag_value_completion(flag, indent, out)?;
+            generate_flag_value_completion(flag, &tree.root.name, indent, out)?;
             writeln!(out, "{indent}        return")?;
             writeln!(out, "{indent}        ;;")?;
         }
@@ -183,85 +521,138 @@ fn generate_completions_for_command(
     let all_flags: Vec<String> = cmd
         .flags
         .iter()
-        .chain(global_flags.iter())
+        .chain(global_flags.iter().copied())
+        .filter(|f| opts.offers_flag(f))
         .map(|f| format!("--{}", f.long))
         .collect();
 
     if !all_flags.is_empty() {
-        let flag_list = all_flags.join(" ");
         writeln!(
             out,
-            "{indent}    mapfile -t COMPREPLY < <(compgen -W \"{flag_list}\" -- \"$cur\")"
+            "{indent}    mapfile -t COMPREPLY < <(compgen -W {} -- \"$cur\")",
+            word_list(&all_flags)
         )?;
     }
 
+    // Deprecated flags are completed only once fully typed
+    let deprecated_flags: Vec<String> = cmd
+        .flags
+        .iter()
+        .chain(global_flags.iter().copied())
+        .filter(|f| f.deprecated && !f.hidden)
+        .flat_map(flag_spellings)
+        .collect();
+    write_exact_matches(&deprecated_flags, &format!("{indent}    "), out)?;
+
     writeln!(out, "{indent}    return")?;
     writeln!(out, "{indent}fi")?;
 
-    // Otherwise, complete subcommands
-    if !cmd.subcommands.is_empty() {
-        let subcmd_names: Vec<String> = cmd
-            .subcommands
-            .keys()
-            .cloned()
-            .collect();
-        let subcmd_list = subcmd_names.join(" ");
+    // Otherwise, complete subcommands until positional arguments have started.
+    // In help order, top-level commands are offered group by group.
+    let sections = if std::ptr::eq(cmd, &tree.root) {
+        opts.top_level(tree)
+    } else {
+        vec![(None, cmd.subcommands.iter().collect())]
+    };
+    let subcommands: Vec<(&String, &Command)> = sections
+        .into_iter()
+        .flat_map(|(_, commands)| commands)
+        .collect();
+    let subcmd_names: Vec<String> = subcommands
+        .iter()
+        .filter(|(_, c)| opts.offers(c))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    // Deprecated commands and those in hidden groups
+    let deprecated_names: Vec<String> = subcommands
+        .iter()
+        .filter(|(_, c)| opts.includes(c) && !opts.offers(c))
+        .flat_map(|(name, c)| std::iter::once(*name).chain(&c.aliases).cloned())
+        .collect();
+    if !subcmd_names.is_empty() || !deprecated_names.is_empty() {
+        writeln!(out, "{indent}if [[ $positional -eq 0 ]]; then")?;
+        if opts.order == Order::Help {
+            // Keep the help order instead of sorting (bash 4.4+)
+            writeln!(out, "{indent}    compopt -o nosort 2>/dev/null")?;
+        }
         writeln!(
             out,
-            "{indent}mapfile -t COMPREPLY < <(compgen -W \"{subcmd_list}\" -- \"$cur\")"
+            "{indent}    mapfile -t COMPREPLY < <(compgen -W {} -- \"$cur\")",
+            word_list(&subcmd_names)
         )?;
+        write_exact_matches(&deprecated_names, &format!("{indent}    "), out)?;
+        writeln!(out, "{indent}fi")?;
     }
 
     Ok(())
 }
 
-fn generate_flag_value_completion(flag: &Flag, indent: &str, out: &mut dyn Write) -> io::Result<()> {
-    match flag.value_type.as_deref() {
-        Some("string") | Some("strings") => {
-            // For file-like flags, use file completion; otherwise just return empty
-            if flag.long.contains("file") || flag.long.contains("path") || flag.long == "db" {
-                writeln!(out, "{indent}        mapfile -t COMPREPLY < <(compgen -f -- \"$cur\")")?;
-            } else {
-                writeln!(out, "{indent}        COMPREPLY=()")?;
-            }
-        }
-        Some("int") | Some("int32") | Some("int64") | Some("uint") | Some("float") | Some("float64") | Some("count") => {
-            writeln!(out, "{indent}        COMPREPLY=()")?;
-        }
-        Some("duration") => {
-            writeln!(out, "{indent}        COMPREPLY=()")?;
-        }
-        _ => {
-            writeln!(out, "{indent}        COMPREPLY=()")?;
-        }
+/// Offer `names` only when `$cur` already spells one of them out in full.
+fn write_exact_matches(names: &[String], indent: &str, out: &mut dyn Write) -> io::Result<()> {
+    if names.is_empty() {
+        return Ok(());
     }
+    writeln!(out, "{indent}case \"$cur\" in")?;
+    writeln!(out, "{indent}    {})", case_alternatives(names))?;
+    writeln!(out, "{indent}        COMPREPLY+=(\"$cur\")")?;
+    writeln!(out, "{indent}        ;;")?;
+    writeln!(out, "{indent}esac")?;
     Ok(())
 }
 
-/// Collect all command and subcommand names from the tree (for the word-walking case statement).
-fn collect_all_command_names(cmd: &Command) -> Vec<String> {
-    let mut names: Vec<String> = Vec::new();
-    for (name, subcmd) in &cmd.subcommands {
-        names.push(name.clone());
-        for alias in &subcmd.aliases {
-            names.push(alias.clone());
+fn generate_flag_value_completion(
+    flag: &Flag,
+    root: &str,
+    indent: &str,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let body = format!("{indent}        ");
+    let completion = flag.value_completion();
+    if let Some(command) = completion.command(root) {
+        writeln!(out, "{body}mapfile -t COMPREPLY < <(compgen -W \"$({command})\" -- \"$cur\")")?;
+        return Ok(());
+    }
+    match completion {
+        ValueCompletion::File => {
+            writeln!(out, "{body}mapfile -t COMPREPLY < <(compgen -f -- \"$cur\")")?;
         }
-        // Also add sub-subcommand names
-        for sub_name in subcmd.subcommands.keys() {
-            if !names.contains(sub_name) {
-                names.push(sub_name.clone());
-            }
+        ValueCompletion::Directory => {
+            writeln!(out, "{body}mapfile -t COMPREPLY < <(compgen -d -- \"$cur\")")?;
+        }
+        ValueCompletion::Bool => {
+            writeln!(out, "{body}mapfile -t COMPREPLY < <(compgen -W \"true false\" -- \"$cur\")")?;
+        }
+        ValueCompletion::Duration => {
+            // Offer unit suffixes once a number has been typed
+            writeln!(out, "{body}COMPREPLY=()")?;
+            writeln!(out, "{body}if [[ \"$cur\" =~ ^[0-9]+$ ]]; then")?;
+            writeln!(out, "{body}    COMPREPLY=(\"${{cur}}ms\" \"${{cur}}s\" \"${{cur}}m\" \"${{cur}}h\")")?;
+            writeln!(out, "{body}fi")?;
+        }
+        ValueCompletion::KeyValue => {
+            // Close the key with '=' and leave the cursor on the value
+            writeln!(out, "{body}COMPREPLY=()")?;
+            writeln!(out, "{body}if [[ -n \"$cur\" && \"$cur\" != *=* ]]; then")?;
+            writeln!(out, "{body}    COMPREPLY=(\"$cur=\")")?;
+            writeln!(out, "{body}    compopt -o nospace")?;
+            writeln!(out, "{body}fi")?;
+        }
+        ValueCompletion::Values(values) => {
+            let list = word_list(&values);
+            writeln!(out, "{body}mapfile -t COMPREPLY < <(compgen -W {list} -- \"$cur\")")?;
+        }
+        ValueCompletion::Nothing | ValueCompletion::Command(_) | ValueCompletion::IssueId => {
+            writeln!(out, "{body}COMPREPLY=()")?;
         }
     }
-    names.sort();
-    names.dedup();
-    names
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::command_tree::CommandGroup;
+    use crate::command_tree::{CommandGroup, ValueKind};
+    use crate::options::{Order, ShellAlias};
 
     fn sample_tree() -> CommandTree {
         let mut root = Command::new("bd", "A lightweight issue tracker");
@@ -269,20 +660,16 @@ mod tests {
         // Add some subcommands
         let mut show_cmd = Command::new("show", "Show issue details");
         show_cmd.flags.push(Flag {
-            long: "format".to_string(),
             short: Some('f'),
-            description: "Output format".to_string(),
-            value_type: Some("string".to_string()),
-            default: None,
+            value_type: Some(ValueKind::String),
+            ..Flag::new("format", "Output format")
         });
 
         let mut search_cmd = Command::new("search", "Search issues");
         search_cmd.flags.push(Flag {
-            long: "query".to_string(),
             short: Some('q'),
-            description: "Search query".to_string(),
-            value_type: Some("string".to_string()),
-            default: None,
+            value_type: Some(ValueKind::String),
+            ..Flag::new("query", "Search query")
         });
 
         let set_state_cmd = Command::new("set-state", "Set issue state");
@@ -314,25 +701,16 @@ mod tests {
 
         let global_flags = vec![
             Flag {
-                long: "db".to_string(),
-                short: None,
-                description: "Database path".to_string(),
-                value_type: Some("string".to_string()),
-                default: None,
+                value_type: Some(ValueKind::String),
+                ..Flag::new("db", "Database path")
             },
             Flag {
-                long: "verbose".to_string(),
                 short: Some('v'),
-                description: "Enable verbose output".to_string(),
-                value_type: None,
-                default: None,
+                ..Flag::new("verbose", "Enable verbose output")
             },
             Flag {
-                long: "help".to_string(),
                 short: Some('h'),
-                description: "help for bd".to_string(),
-                value_type: None,
-                default: None,
+                ..Flag::new("help", "help for bd")
             },
         ];
 
@@ -349,7 +727,7 @@ mod tests {
     fn test_generates_license_header() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         assert!(output.contains("Copyright 2006 JT Perry"));
@@ -360,7 +738,7 @@ mod tests {
     fn test_generates_shebang() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         assert!(output.starts_with("#!/usr/bin/env bash"));
@@ -370,7 +748,7 @@ mod tests {
     fn test_generates_complete_command() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         assert!(output.contains("complete -F _bd bd"));
@@ -380,7 +758,7 @@ mod tests {
     fn test_completes_top_level_commands() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // Should contain all subcommand names for top-level completion
@@ -395,19 +773,30 @@ mod tests {
     fn test_completes_subcommands() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
-        // dep should have add, remove, list subcommands
+        // dep should have add, remove, list subcommands, in the order given
         assert!(output.contains("dep)"));
-        assert!(output.contains("\"add list remove\""));
+        assert!(output.contains("'add remove list'"));
+
+        let opts = GenerateOptions {
+            order: Order::Alphabetical,
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &opts, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.contains("'add list remove'"));
+        assert!(output.contains("compgen -W 'create dep search set-state show'"));
+        assert!(!output.contains("nosort"));
     }
 
     #[test]
     fn test_completes_flags() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // Global flags should appear in flag completions
@@ -420,7 +809,7 @@ mod tests {
     fn test_completes_flag_values() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // --db takes a file path, should use compgen -f
@@ -432,7 +821,7 @@ mod tests {
     fn test_handles_aliases() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // "create" has alias "new", both should be handled
@@ -443,7 +832,7 @@ mod tests {
     fn test_passes_shellcheck() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_bash_completion(&tree, &mut buf).unwrap();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let script = String::from_utf8(buf).unwrap();
 
         // Write to temp file and run shellcheck
@@ -473,4 +862,278 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_alias_completions() {
+        let tree = sample_tree();
+        let opts = GenerateOptions {
+            aliases: vec![
+                ShellAlias::parse("b").unwrap(),
+                ShellAlias::parse("bdw=--db x.db").unwrap(),
+            ],
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &opts, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        assert!(output.contains("complete -F _bd b\n"));
+        assert!(output.contains("    local COMP_WORDS=(bd '--db' x.db \"${COMP_WORDS[@]:1}\")\n"));
+        assert!(output.contains("    local COMP_CWORD=$((COMP_CWORD + 2))\n"));
+        assert!(output.trim_end().ends_with("complete -F __bd_alias_bdw bdw"));
+    }
+
+    #[test]
+    fn test_walker_stops_at_positionals() {
+        let tree = sample_tree();
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        // Children resolve to canonical paths; anything else starts positionals
+        assert!(output.contains("cmd_path='dep add'"));
+        assert!(output.contains("positional=1"));
+        assert!(output.contains("if [[ $positional -eq 0 ]]; then"));
+    }
+
+    #[test]
+    fn test_walker_skips_flag_values() {
+        let tree = sample_tree();
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        // Global value flags apply under any path, local ones only under theirs
+        assert!(output.contains("*:--db|"));
+        assert!(output.contains("'show:-f'"));
+        assert!(!output.contains(":--verbose"));
+    }
+
+    #[test]
+    fn test_value_kind_completions() {
+        let mut tree = sample_tree();
+        let show = tree.root.subcommands.get_mut("show").unwrap();
+        for (long, kind) in [
+            ("limit", ValueKind::Int),
+            ("since", ValueKind::Duration),
+            ("meta", ValueKind::StringToString),
+            ("archived", ValueKind::Bool),
+        ] {
+            show.flags.push(Flag {
+                value_type: Some(kind),
+                ..Flag::new(long, "")
+            });
+        }
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        assert!(output.contains("COMPREPLY=(\"${cur}ms\" \"${cur}s\" \"${cur}m\" \"${cur}h\")"));
+        assert!(output.contains("COMPREPLY=(\"$cur=\")"));
+        assert!(output.contains("compopt -o nospace"));
+        assert!(output.contains("compgen -W \"true false\""));
+    }
+
+    #[test]
+    fn test_deprecated_completed_only_when_typed() {
+        let mut tree = sample_tree();
+        let mut migrate = Command::new("migrate", "DEPRECATED: use upgrade");
+        migrate.deprecated = true;
+        tree.root.subcommands.insert("migrate".to_string(), migrate);
+        tree.global_flags[1].deprecated = true;
+
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        // Still walked, but offered only as an exact match
+        assert!(output.contains("                                migrate)"));
+        assert!(output.contains("compgen -W 'show search create set-state dep'"));
+        assert!(output.contains("    migrate)\n"));
+        assert!(output.contains("--verbose|-v)"));
+        assert!(output.contains("COMPREPLY+=(\"$cur\")"));
+    }
+
+    #[test]
+    fn test_helper_commands_optional() {
+        let mut tree = sample_tree();
+        let mut help = Command::new("help", "Help about any command");
+        help.hidden = true;
+        tree.root.subcommands.insert("help".to_string(), help);
+
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(!output.contains("help)"));
+
+        let opts = GenerateOptions {
+            include_helpers: true,
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &opts, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.contains("help)"));
+        assert!(output.contains("'show search create set-state dep help'"));
+    }
+
+    #[test]
+    fn test_group_order_and_hidden_groups() {
+        let mut tree = sample_tree();
+        for name in ["show", "search", "create"] {
+            tree.root.subcommands.get_mut(name).unwrap().group =
+                Some("Working With Issues".to_string());
+        }
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        // The group's commands first, as listed, then the rest
+        assert!(output.contains("compgen -W 'show search create set-state dep'"));
+        // Nested commands keep their help order as well
+        let lines: Vec<&str> = output.lines().map(str::trim).collect();
+        let dep = lines.iter().position(|l| l.contains("'add remove list'")).unwrap();
+        assert_eq!(lines[dep - 1], "compopt -o nosort 2>/dev/null");
+
+        let opts = GenerateOptions {
+            hidden_groups: vec!["Working With Issues".to_string()],
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &opts, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.contains("compgen -W 'set-state dep'"));
+        // Still completed once typed in full
+        assert!(output.contains("    show|search|create|new)\n"));
+    }
+
+    #[test]
+    fn test_split_loader_and_fragments() {
+        let tree = sample_tree();
+        let dir = std::env::temp_dir().join(format!("bd-complete-split-{}", std::process::id()));
+        let written = generate_bash_split(&tree, &GenerateOptions::default(), &dir).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|p| p.strip_prefix(&dir).unwrap().display().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "bd",
+                "bd.d/show.bash",
+                "bd.d/search.bash",
+                "bd.d/set-state.bash",
+                "bd.d/dep.bash",
+                "bd.d/create.bash"
+            ]
+        );
+
+        // The loader stops at top-level commands and knows nothing below them
+        let loader = std::fs::read_to_string(dir.join("bd")).unwrap();
+        assert!(loader.contains("cmd_path=create\n                                    break"));
+        assert!(loader.contains("__bd_load dep && _bd_dep \"$i\""));
+        assert!(!loader.contains("'dep add'"));
+        assert!(!loader.contains("show:-f"));
+        assert!(loader.ends_with("complete -F _bd bd\n"));
+
+        let dep = std::fs::read_to_string(dir.join("bd.d/dep.bash")).unwrap();
+        assert!(dep.contains("_bd_dep() {"));
+        assert!(dep.contains("local i=$(($1 + 1))"));
+        assert!(dep.contains("cmd_path='dep add'"));
+        assert!(dep.contains("'add remove list'"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_split_loader_with_hyphenated_root() {
+        let mut tree = sample_tree();
+        tree.root.name = "bd-dev".to_string();
+        let dir = std::env::temp_dir().join(format!("bd-complete-split-dev-{}", std::process::id()));
+        generate_bash_split(&tree, &GenerateOptions::default(), &dir).unwrap();
+
+        let loader = std::fs::read_to_string(dir.join("bd-dev")).unwrap();
+        assert!(loader.contains("__bd_dev_fragments=${BASH_SOURCE[0]%/*}\n"));
+        assert!(loader.contains("__bd_dev_fragments+=/bd-dev.d\n"));
+        assert!(loader.contains("source \"$__bd_dev_fragments/$1.bash\""));
+        assert!(loader.ends_with("complete -F _bd-dev bd-dev\n"));
+
+        // Sourcing it must define the loader and find the fragments
+        let check = "source \"$1/bd-dev\" && __bd-dev_load dep && declare -F _bd-dev_dep";
+        let output = std::process::Command::new("bash")
+            .args(["-c", check, "bash"])
+            .arg(&dir)
+            .output();
+        let _ = std::fs::remove_dir_all(&dir);
+        match output {
+            Ok(result) => {
+                let stderr = String::from_utf8_lossy(&result.stderr);
+                assert!(result.status.success(), "{stderr}");
+                assert!(stderr.is_empty(), "{stderr}");
+            }
+            Err(e) => eprintln!("bash not available, skipping: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_rejects_hostile_names() {
+        let mut tree = sample_tree();
+        tree.root
+            .subcommands
+            .insert("foo;rm".to_string(), Command::new("foo;rm", "A plugin"));
+
+        let mut buf = Vec::new();
+        let err = generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf)
+            .unwrap_err();
+        assert!(matches!(err, BdCompleteError::UnsafeIdentifier { .. }), "{err}");
+        assert!(buf.is_empty(), "nothing is written for a rejected tree");
+    }
+
+    #[test]
+    fn test_rejects_hostile_alias_names() {
+        let opts = GenerateOptions {
+            aliases: vec![ShellAlias {
+                name: "b;id".to_string(),
+                prefix: Vec::new(),
+            }],
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        let err = generate_bash_completion(&sample_tree(), &opts, &mut buf).unwrap_err();
+        assert!(err.to_string().starts_with("alias name \"b;id\""), "{err}");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_names_are_quoted_without_check_tree() {
+        let names = ["a|b", "x)", "$(touch pwned)", "it's", "dep add"];
+        assert_eq!(case_alternatives(&["show", "view"]), "show|view");
+        assert_eq!(word_list(&["show", "view"]), "'show view'");
+        assert_eq!(case_alternatives(&["--verbose", "-v"]), "--verbose|-v");
+
+        // Each name matches only itself and compgen offers each one whole
+        let mut script = String::new();
+        for name in names {
+            script.push_str(&format!(
+                "case {} in {}) ;; *) exit 1 ;; esac\n",
+                word(name),
+                case_alternatives(&[name])
+            ));
+        }
+        script.push_str("case b in ");
+        script.push_str(&case_alternatives(&names));
+        script.push_str(") exit 1 ;; esac\n");
+        script.push_str(&format!("compgen -W {} --\n", word_list(&names)));
+
+        let output = std::process::Command::new("bash")
+            .args(["-c", &script])
+            .output();
+        match output {
+            Ok(result) => {
+                let stderr = String::from_utf8_lossy(&result.stderr);
+                assert!(result.status.success(), "{stderr}");
+                let stdout = String::from_utf8_lossy(&result.stdout);
+                assert_eq!(stdout.lines().collect::<Vec<_>>(), names);
+            }
+            Err(e) => eprintln!("bash not available, skipping: {e}"),
+        }
+    }
 }
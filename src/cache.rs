@@ -0,0 +1,378 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! On-disk cache of the command tree built from a binary's help output.
+//!
+//! Building a tree runs `bd ... --help` once per command, which takes
+//! seconds. Each cache entry holds the parsed tree and the diagnostics its
+//! build reported (`tree`), plus a `key` file describing the binary they
+//! came from: its resolved path, size, mtime and `--version` output, and
+//! the bd-complete version that parsed it. When the key no longer matches,
+//! or the tree file can't be read back, the entry is rebuilt.
+
+use crate::command_tree::CommandTree;
+use crate::error::BdCompleteError;
+use crate::parser::{
+    build_command_tree_with_diagnostics, plain_command, wait_with_timeout, Diagnostic, HelpSource,
+    ScratchHome, HELP_TIMEOUT,
+};
+use crate::plugins::add_plugins_built_by;
+use crate::tree_file::{read_tree, write_tree};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// A cache directory, normally `$XDG_CACHE_HOME/bd-complete`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Cache {
+    pub root: PathBuf,
+}
+
+impl Cache {
+    pub fn new(root: impl Into<PathBuf>) -> Cache {
+        Cache { root: root.into() }
+    }
+
+    /// The user's cache: `$XDG_CACHE_HOME/bd-complete`, falling back to
+    /// `~/.cache/bd-complete`. None if neither variable is set.
+    pub fn from_env() -> Option<Cache> {
+        let base = match std::env::var_os("XDG_CACHE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+        };
+        Some(Cache::new(base.join("bd-complete")))
+    }
+
+    /// Build the tree for `binary`, from the cache when its key still matches.
+    /// With `refresh`, the entry is rebuilt regardless. Caching is best
+    /// effort: if the entry can't be written, the tree is built directly.
+    pub fn build_tree(&self, binary: &str, refresh: bool) -> Result<CommandTree, BdCompleteError> {
+        self.build_tree_with_diagnostics(binary, refresh, &mut Vec::new())
+    }
+
+    /// Like [`Cache::build_tree`], recording the help lines the parser
+    /// skipped or wasn't sure about in `diagnostics`.
+    pub fn build_tree_with_diagnostics(
+        &self,
+        binary: &str,
+        refresh: bool,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<CommandTree, BdCompleteError> {
+        let live = HelpSource::Binary(binary.to_string());
+        let Some(resolved) = resolve_binary(binary) else {
+            // Not found; let the live build report it
+            return build_command_tree_with_diagnostics(&live, diagnostics);
+        };
+        let root = live.root_name();
+        let dir = self.entry_dir(&resolved, &root);
+        let key = cache_key(binary, &resolved, &root).map_err(BdCompleteError::Io)?;
+
+        let key_file = dir.join("key");
+        let tree_file = dir.join("tree");
+        if !refresh && std::fs::read_to_string(&key_file).is_ok_and(|k| k == key) {
+            let cached = std::fs::read_to_string(&tree_file).ok();
+            if let Some((tree, mut cached_diagnostics)) = cached.as_deref().and_then(read_tree) {
+                diagnostics.append(&mut cached_diagnostics);
+                return Ok(tree);
+            }
+        }
+
+        let mut built_diagnostics = Vec::new();
+        let tree = build_command_tree_with_diagnostics(&live, &mut built_diagnostics)?;
+        // A stale entry that can't be replaced is no reason to fail
+        let stale_kept = dir.exists() && std::fs::remove_dir_all(&dir).is_err();
+        if !stale_kept && std::fs::create_dir_all(&dir).is_ok() {
+            // The key goes in last, so an interrupted write is never used
+            let saved = std::fs::write(&tree_file, write_tree(&tree, &built_diagnostics));
+            let tmp = dir.join("key.tmp");
+            if saved.is_ok() && std::fs::write(&tmp, &key).is_ok() {
+                let _ = std::fs::rename(&tmp, &key_file);
+            }
+        }
+        diagnostics.append(&mut built_diagnostics);
+        Ok(tree)
+    }
+
+    /// Like [`add_plugins_with_diagnostics`](crate::add_plugins_with_diagnostics),
+    /// with each plugin's tree built through the cache as bd's is.
+    pub fn add_plugins(
+        &self,
+        tree: &mut CommandTree,
+        plugins: &BTreeMap<String, PathBuf>,
+        refresh: bool,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Vec<(String, BdCompleteError)> {
+        add_plugins_built_by(tree, plugins, diagnostics, |binary, diagnostics| {
+            self.build_tree_with_diagnostics(binary, refresh, diagnostics)
+        })
+    }
+
+    /// Remove every entry.
+    pub fn clear(&self) -> io::Result<()> {
+        match std::fs::remove_dir_all(&self.root) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// One entry per binary and root name, so `bd` and a `beads` symlink to
+    /// the same file don't share a tree named after the other.
+    fn entry_dir(&self, resolved: &Path, root: &str) -> PathBuf {
+        let mut id = resolved.as_os_str().as_encoded_bytes().to_vec();
+        id.push(0);
+        id.extend_from_slice(root.as_bytes());
+        self.root.join(format!("{:016x}", fnv1a(&id)))
+    }
+}
+
+/// Find `binary` the way the shell would and resolve symlinks.
+fn resolve_binary(binary: &str) -> Option<PathBuf> {
+    if binary.contains('/') {
+        return std::fs::canonicalize(binary).ok();
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(binary))
+        .find(|path| path.is_file())
+        .and_then(|path| std::fs::canonicalize(path).ok())
+}
+
+/// Everything that, when changed, means the cached tree may be stale.
+fn cache_key(binary: &str, resolved: &Path, root: &str) -> io::Result<String> {
+    let meta = std::fs::metadata(resolved)?;
+    let mtime = meta
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    Ok(format!(
+        "bd-complete {}\npath {}\nroot {root}\nsize {}\nmtime {mtime}\nversion\n{}",
+        env!("CARGO_PKG_VERSION"),
+        resolved.display(),
+        meta.len(),
+        version_output(binary)
+    ))
+}
+
+/// `<binary> --version`, stdout and stderr alike; binaries without the flag
+/// still print the same error each time. It runs in the same environment as
+/// the help, so the terminal or user config can't change the key.
+fn version_output(binary: &str) -> String {
+    let Ok(home) = ScratchHome::create() else {
+        return String::new();
+    };
+    let child = plain_command(binary, home.path()).arg("--version").spawn();
+    match child.and_then(|child| wait_with_timeout(child, HELP_TIMEOUT)) {
+        Ok(Some(output)) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            text
+        }
+        // A hung bd fails the build when its help is run
+        Ok(None) | Err(_) => String::new(),
+    }
+}
+
+/// 64-bit FNV-1a; stable across Rust releases, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A stub bd whose top-level commands are `commands`, and which counts
+    /// its `--help` runs in `<dir>/runs`.
+    fn write_stub(dir: &Path, commands: &[&str]) -> PathBuf {
+        let listing: String = commands
+            .iter()
+            .map(|c| format!("  {c:<8} Does {c}\n"))
+            .collect();
+        let script = format!(
+            "#!/bin/sh\n\
+             case \"$*\" in\n\
+             --version) echo 'bd version 1.0'; exit 0 ;;\n\
+             --help) ;;\n\
+             *) printf 'Usage:\\n  bd x [flags]\\n'; exit 0 ;;\n\
+             esac\n\
+             echo x >> '{runs}'\n\
+             printf 'A tracker\\n\\nUsage:\\n  bd [command]\\n\\nAvailable Commands:\\n{listing}'\n",
+            runs = dir.join("runs").display(),
+            listing = listing.replace('\n', "\\n"),
+        );
+        let path = dir.join("bd");
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn runs(dir: &Path) -> usize {
+        std::fs::read_to_string(dir.join("runs"))
+            .map(|s| s.lines().count())
+            .unwrap_or(0)
+    }
+
+    fn names(tree: &CommandTree) -> Vec<&str> {
+        tree.root.subcommands.keys().map(String::as_str).collect()
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("bd-complete-cache-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_second_build_reads_the_cache() {
+        let dir = scratch("hit");
+        let stub = write_stub(&dir, &["create", "list"]);
+        let cache = Cache::new(dir.join("cache"));
+        let binary = stub.to_str().unwrap();
+
+        let first = cache.build_tree(binary, false).unwrap();
+        assert_eq!(runs(&dir), 1);
+        let second = cache.build_tree(binary, false).unwrap();
+        assert_eq!(runs(&dir), 1, "the root help wasn't run again");
+        assert_eq!(first, second);
+
+        // --refresh runs the binary even though nothing changed
+        cache.build_tree(binary, true).unwrap();
+        assert_eq!(runs(&dir), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_changed_binary_invalidates_the_entry() {
+        let dir = scratch("stale");
+        let stub = write_stub(&dir, &["create"]);
+        let cache = Cache::new(dir.join("cache"));
+        let binary = stub.to_str().unwrap();
+
+        assert_eq!(names(&cache.build_tree(binary, false).unwrap()), ["create"]);
+        write_stub(&dir, &["create", "delete"]);
+        assert_eq!(
+            names(&cache.build_tree(binary, false).unwrap()),
+            ["create", "delete"]
+        );
+        assert_eq!(runs(&dir), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unremovable_stale_entry_falls_back_to_a_live_build() {
+        let dir = scratch("stuck");
+        let stub = write_stub(&dir, &["create"]);
+        let cache = Cache::new(dir.join("cache"));
+        let binary = stub.to_str().unwrap();
+
+        // A file where the entry goes can't be removed as a directory
+        let entry = cache.entry_dir(&resolve_binary(binary).unwrap(), "bd");
+        std::fs::create_dir_all(&cache.root).unwrap();
+        std::fs::write(&entry, "not an entry").unwrap();
+
+        assert_eq!(names(&cache.build_tree(binary, false).unwrap()), ["create"]);
+        assert_eq!(std::fs::read_to_string(&entry).unwrap(), "not an entry");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_version_runs_in_the_help_environment() {
+        let dir = scratch("version-env");
+        let stub = dir.join("bd");
+        std::fs::write(&stub, "#!/bin/sh\necho \"$TERM $CLICOLOR $HOME\"\n").unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let output = version_output(stub.to_str().unwrap());
+        let fields: Vec<&str> = output.split_whitespace().collect();
+        assert_eq!(fields[..2], ["dumb", "0"]);
+        assert_ne!(Some(fields[2]), std::env::var("HOME").ok().as_deref());
+        assert!(!Path::new(fields[2]).exists(), "the scratch home was removed");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cached_tree_keeps_its_diagnostics() {
+        let dir = scratch("diagnostics");
+        let stub = write_stub(&dir, &["create", "create"]);
+        let cache = Cache::new(dir.join("cache"));
+        let binary = stub.to_str().unwrap();
+
+        let mut first = Vec::new();
+        cache
+            .build_tree_with_diagnostics(binary, false, &mut first)
+            .unwrap();
+        let mut second = Vec::new();
+        cache
+            .build_tree_with_diagnostics(binary, false, &mut second)
+            .unwrap();
+        assert_eq!(runs(&dir), 1);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first, second);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unreadable_tree_is_rebuilt() {
+        let dir = scratch("corrupt");
+        let stub = write_stub(&dir, &["create"]);
+        let cache = Cache::new(dir.join("cache"));
+        let binary = stub.to_str().unwrap();
+
+        let first = cache.build_tree(binary, false).unwrap();
+        let entry = cache.entry_dir(&resolve_binary(binary).unwrap(), "bd");
+        std::fs::write(entry.join("tree"), "not a tree").unwrap();
+        assert_eq!(cache.build_tree(binary, false).unwrap(), first);
+        assert_eq!(runs(&dir), 2);
+        // The rebuilt entry is used again
+        cache.build_tree(binary, false).unwrap();
+        assert_eq!(runs(&dir), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_entries_hold_the_tree_and_key() {
+        let dir = scratch("layout");
+        let stub = write_stub(&dir, &["create"]);
+        let cache = Cache::new(dir.join("cache"));
+        cache.build_tree(stub.to_str().unwrap(), false).unwrap();
+
+        let entries: Vec<PathBuf> = std::fs::read_dir(&cache.root)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        let mut files: Vec<String> = std::fs::read_dir(&entries[0])
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["key", "tree"]);
+        let tree = std::fs::read_to_string(entries[0].join("tree")).unwrap();
+        assert!(tree.contains("\ncommand\t1\tcreate\n"), "{tree}");
+        let key = std::fs::read_to_string(entries[0].join("key")).unwrap();
+        assert!(key.ends_with("version\nbd version 1.0\n"));
+
+        cache.clear().unwrap();
+        assert!(!cache.root.exists());
+        cache.clear().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [carapace-spec](https://carapace-sh.github.io/carapace-spec/) YAML, which
+//! carapace turns into completions for every shell it supports.
+
+use crate::command_tree::{Command, CommandTree, Flag, ValueCompletion};
+use crate::error::BdCompleteError;
+use crate::options::GenerateOptions;
+use crate::yaml::{write_yaml, Yaml};
+use std::io::{self, Write};
+
+/// Write the command tree as a carapace spec.
+pub fn generate_carapace_spec(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    let tree = opts.ordered(tree);
+    write_carapace_spec(&tree, opts, out).map_err(BdCompleteError::Write)
+}
+
+fn write_carapace_spec(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "# yaml-language-server: $schema=https://carapace.sh/schemas/command.json"
+    )?;
+    writeln!(out, "# Carapace spec for {}", tree.root.name)?;
+    writeln!(out, "# Generated by bd-complete")?;
+
+    let root = &tree.root;
+    let mut entries = vec![("name".to_string(), Yaml::str(&root.name))];
+    if !root.description.is_empty() {
+        entries.push(("description".to_string(), Yaml::str(&root.description)));
+    }
+    if !tree.global_flags.is_empty() {
+        entries.push(("persistentflags".to_string(), flag_map(&tree.global_flags)));
+    }
+    // Root flags that aren't persistent; at the root cobra lists both together
+    let local: Vec<Flag> = root
+        .flags
+        .iter()
+        .filter(|f| !tree.global_flags.iter().any(|g| g.long == f.long))
+        .cloned()
+        .collect();
+    push_flags_and_completion(&root.name, &local, &tree.global_flags, &mut entries);
+    push_commands(&root.name, root, true, opts, &mut entries);
+
+    write_yaml(&Yaml::Map(entries), out)
+}
+
+fn command(
+    root: &str,
+    name: &str,
+    cmd: &Command,
+    top_level: bool,
+    opts: &GenerateOptions,
+) -> Yaml {
+    let mut entries = vec![("name".to_string(), Yaml::str(name))];
+    if !cmd.aliases.is_empty() {
+        let aliases = cmd.aliases.iter().map(|a| Yaml::str(a)).collect();
+        entries.push(("aliases".to_string(), Yaml::List(aliases)));
+    }
+    if !cmd.description.is_empty() {
+        entries.push(("description".to_string(), Yaml::str(&cmd.description)));
+    }
+    // Shells with tags (zsh) show each help group under its own heading
+    if let Some(group) = cmd.group.as_ref().filter(|_| top_level) {
+        entries.push(("group".to_string(), Yaml::str(group)));
+    }
+    // Hidden commands still run and complete their own arguments
+    if !opts.offers(cmd) {
+        entries.push(("hidden".to_string(), Yaml::Bool(true)));
+    }
+    push_flags_and_completion(root, &cmd.flags, &[], &mut entries);
+    push_commands(root, cmd, false, opts, &mut entries);
+    Yaml::Map(entries)
+}
+
+/// Add the subcommands of `cmd`; `top_level` when `cmd` is the root.
+fn push_commands(
+    root: &str,
+    cmd: &Command,
+    top_level: bool,
+    opts: &GenerateOptions,
+    entries: &mut Vec<(String, Yaml)>,
+) {
+    let commands: Vec<Yaml> = cmd
+        .subcommands
+        .iter()
+        .filter(|(_, c)| opts.includes(c))
+        .map(|(name, c)| command(root, name, c, top_level, opts))
+        .collect();
+    if !commands.is_empty() {
+        entries.push(("commands".to_string(), Yaml::List(commands)));
+    }
+}
+
+/// Add `flags` and a `completion.flag` section covering them and `persistent`.
+fn push_flags_and_completion(
+    root: &str,
+    flags: &[Flag],
+    persistent: &[Flag],
+    entries: &mut Vec<(String, Yaml)>,
+) {
+    if !flags.is_empty() {
+        entries.push(("flags".to_string(), flag_map(flags)));
+    }
+    let actions: Vec<(String, Yaml)> = persistent
+        .iter()
+        .chain(flags)
+        .filter_map(|f| value_actions(f, root).map(|a| (f.long.clone(), a)))
+        .collect();
+    if !actions.is_empty() {
+        let flag = ("flag".to_string(), Yaml::Map(actions));
+        entries.push(("completion".to_string(), Yaml::Map(vec![flag])));
+    }
+}
+
+fn flag_map(flags: &[Flag]) -> Yaml {
+    Yaml::Map(
+        flags
+            .iter()
+            .map(|f| (flag_key(f), Yaml::str(&f.description)))
+            .collect(),
+    )
+}
+
+/// The spec's flag syntax: `-p, --priority=` where the suffixes mark a flag
+/// that is repeatable (`*`), hidden (`&`) or takes a value (`=`).
+fn flag_key(flag: &Flag) -> String {
+    let mut key = match flag.short {
+        Some(s) => format!("-{s}, --{}", flag.long),
+        None => format!("--{}", flag.long),
+    };
+    if flag.value_type.as_ref().is_some_and(|k| k.is_repeatable()) {
+        key.push('*');
+    }
+    // Deprecated flags stay accepted but aren't suggested
+    if flag.hidden || flag.deprecated {
+        key.push('&');
+    }
+    if flag.takes_value() {
+        key.push('=');
+    }
+    key
+}
+
+/// Carapace actions for a flag's value, if there is anything to offer.
+/// Commands run through the spec's `$(...)` macro, one value per line.
+fn value_actions(flag: &Flag, root: &str) -> Option<Yaml> {
+    let completion = flag.value_completion();
+    if let Some(command) = completion.command(root) {
+        return Some(Yaml::List(vec![Yaml::str(&format!("$({command})"))]));
+    }
+    let actions = match &completion {
+        ValueCompletion::File => vec!["$files"],
+        ValueCompletion::Directory => vec!["$directories"],
+        ValueCompletion::Bool => vec!["true", "false"],
+        ValueCompletion::Values(values) => values.iter().map(String::as_str).collect(),
+        ValueCompletion::Duration
+        | ValueCompletion::KeyValue
+        | ValueCompletion::Nothing
+        | ValueCompletion::Command(_)
+        | ValueCompletion::IssueId => return None,
+    };
+    Some(Yaml::List(actions.into_iter().map(Yaml::str).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_tree::ValueKind;
+
+    fn flag(long: &str, short: Option<char>, kind: Option<ValueKind>, description: &str) -> Flag {
+        Flag {
+            short,
+            value_type: kind,
+            ..Flag::new(long, description)
+        }
+    }
+
+    fn sample_tree() -> CommandTree {
+        let mut root = Command::new("bd", "A lightweight issue tracker");
+
+        let mut create = Command::new("create", "Create a new issue");
+        create.aliases = vec!["new".to_string()];
+        create.flags.push(flag(
+            "priority",
+            Some('p'),
+            Some(ValueKind::String),
+            "Priority (0-4)",
+        ));
+        create.flags.push(flag(
+            "labels",
+            Some('l'),
+            Some(ValueKind::Strings),
+            "Labels",
+        ));
+        create.flags.push(flag(
+            "file",
+            Some('f'),
+            Some(ValueKind::String),
+            "Markdown file",
+        ));
+        let mut legacy = flag("legacy", None, None, "Deprecated: old behavior");
+        legacy.deprecated = true;
+        create.flags.push(legacy);
+
+        let mut dep = Command::new("dep", "Manage dependencies");
+        dep.subcommands
+            .insert("add".to_string(), Command::new("add", "Add a dependency"));
+
+        let mut migrate = Command::new("migrate", "DEPRECATED: use upgrade");
+        migrate.deprecated = true;
+        let mut help = Command::new("help", "Help about any command");
+        help.hidden = true;
+
+        root.subcommands.insert("create".to_string(), create);
+        root.subcommands.insert("dep".to_string(), dep);
+        root.subcommands.insert("migrate".to_string(), migrate);
+        root.subcommands.insert("help".to_string(), help);
+
+        let mut tree = CommandTree::new(root);
+        tree.global_flags = vec![
+            flag("db", None, Some(ValueKind::String), "Database path"),
+            flag("json", None, None, "Output in JSON: machine-readable"),
+        ];
+        tree.root.flags = tree.global_flags.clone();
+        tree.root
+            .flags
+            .push(flag("version", None, None, "version for bd"));
+        tree
+    }
+
+    fn render(tree: &CommandTree) -> String {
+        let mut buf = Vec::new();
+        generate_carapace_spec(tree, &GenerateOptions::default(), &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_root_flags() {
+        let output = render(&sample_tree());
+
+        assert!(output.contains("\nname: bd\ndescription: A lightweight issue tracker\n"));
+        assert!(output.contains(
+            "persistentflags:\n  --db=: Database path\n  --json: \"Output in JSON: machine-readable\"\nflags:\n  --version: version for bd\n"
+        ));
+        assert!(output.contains("completion:\n  flag:\n    db:\n      - $files\n"));
+    }
+
+    #[test]
+    fn test_commands_flags_and_aliases() {
+        let output = render(&sample_tree());
+
+        assert!(output.contains(
+            "  - name: create\n    aliases:\n      - new\n    description: Create a new issue\n    flags:\n      -p, --priority=: Priority (0-4)\n      -l, --labels*=: Labels\n      -f, --file=: Markdown file\n      --legacy&: \"Deprecated: old behavior\"\n"
+        ));
+        assert!(
+            output.contains("    completion:\n      flag:\n        file:\n          - $files\n")
+        );
+        assert!(output
+            .contains("    commands:\n      - name: add\n        description: Add a dependency\n"));
+    }
+
+    #[test]
+    fn test_hidden_and_deprecated_commands() {
+        let output = render(&sample_tree());
+
+        assert!(output.contains(
+            "  - name: migrate\n    description: \"DEPRECATED: use upgrade\"\n    hidden: true\n"
+        ));
+        assert!(!output.contains("name: help"));
+    }
+
+    #[test]
+    fn test_only_top_level_commands_have_a_group() {
+        let mut tree = sample_tree();
+        let dep = tree.root.subcommands.get_mut("dep").unwrap();
+        dep.group = Some("Dependencies".to_string());
+        // Named like the root, as after an overrides rename
+        let mut nested = Command::new("bd", "Nested command");
+        nested.group = Some("Dependencies".to_string());
+        let mut inner = Command::new("inner", "Under the nested bd");
+        inner.group = Some("Dependencies".to_string());
+        nested.subcommands.insert("inner".to_string(), inner);
+        dep.subcommands.insert("bd".to_string(), nested);
+        let output = render(&tree);
+
+        assert!(output.contains(
+            "  - name: dep\n    description: Manage dependencies\n    group: Dependencies\n"
+        ));
+        assert!(output.contains(
+            "      - name: bd\n        description: Nested command\n        commands:\n"
+        ));
+        assert!(output
+            .contains("          - name: inner\n            description: Under the nested bd\n"));
+        assert_eq!(output.matches("group:").count(), 1);
+    }
+
+    #[test]
+    fn test_override_completions() {
+        let mut tree = sample_tree();
+        let create = tree.root.subcommands.get_mut("create").unwrap();
+        create.flags[0].completion = Some(ValueCompletion::Values(vec![
+            "0".to_string(),
+            "1".to_string(),
+        ]));
+        create.flags[1].completion = Some(ValueCompletion::Command("bd label list-all".to_string()));
+        let output = render(&tree);
+
+        assert!(output.contains(
+            "        priority:\n          - \"0\"\n          - \"1\"\n        labels:\n          - $(bd label list-all)\n"
+        ));
+    }
+}
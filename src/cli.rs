@@ -0,0 +1,528 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command-line parsing: commands (and their subcommands) each with their
+//! own options, given as `--opt value`, `--opt=value` or combined short
+//! flags (`-ab`, `-ofile`). `--` ends the options, and `--help`/`-h` and
+//! `--version`/`-V` work on every command.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// An option a command accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opt {
+    pub long: &'static str,
+    pub short: Option<char>,
+    /// Placeholder for its value, e.g. "FILE"; None for a switch
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+}
+
+impl Opt {
+    /// An option without a value, like `--no-cache`.
+    pub const fn switch(long: &'static str, help: &'static str) -> Opt {
+        Opt {
+            long,
+            short: None,
+            value: None,
+            help,
+        }
+    }
+
+    /// An option that takes a value, like `--shell <SHELL>`.
+    pub const fn value(long: &'static str, value: &'static str, help: &'static str) -> Opt {
+        Opt {
+            long,
+            short: None,
+            value: Some(value),
+            help,
+        }
+    }
+
+    /// The same option with a short name as well.
+    pub const fn short(self, short: char) -> Opt {
+        Opt {
+            short: Some(short),
+            ..self
+        }
+    }
+}
+
+/// A command, what it accepts and how it's described in help.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub options: Vec<Opt>,
+    /// Commands below this one; one of them has to be given
+    pub subcommands: Vec<CommandSpec>,
+    /// Placeholder for positional arguments, e.g. "FILE..."; None if it
+    /// takes none
+    pub operands: Option<&'static str>,
+}
+
+impl CommandSpec {
+    fn subcommand(&self, name: &str) -> Option<&CommandSpec> {
+        self.subcommands.iter().find(|c| c.name == name)
+    }
+}
+
+/// What the command line asked for.
+#[derive(Debug, PartialEq)]
+pub enum Parsed {
+    /// Run a command.
+    Run(Matches),
+    /// Show the help of the command at this path (empty for the program).
+    Help(Vec<&'static str>),
+    Version,
+}
+
+/// The command given and its options and arguments.
+#[derive(Debug, Default, PartialEq)]
+pub struct Matches {
+    /// The command and any subcommand, e.g. `["cache", "clear"]`
+    pub command: Vec<&'static str>,
+    /// Positional arguments, including everything after `--`
+    pub operands: Vec<String>,
+    switches: BTreeSet<&'static str>,
+    values: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl Matches {
+    /// Whether the switch `--long` was given.
+    pub fn flag(&self, long: &str) -> bool {
+        self.switches.contains(long)
+    }
+
+    /// The value of `--long`; the last one if it was given more than once.
+    pub fn value(&self, long: &str) -> Option<&str> {
+        self.values(long).last().map(String::as_str)
+    }
+
+    /// Every value given for `--long`, in order.
+    pub fn values(&self, long: &str) -> &[String] {
+        self.values.get(long).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// A command line that doesn't fit the commands' specs.
+#[derive(Debug, PartialEq)]
+pub struct CliError {
+    /// The command it was given to, for pointing at its help
+    pub command: Vec<&'static str>,
+    pub message: String,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Parse `args` (without the program name) against `app`, the program's
+/// own spec whose subcommands are its commands.
+pub fn parse(app: &CommandSpec, args: &[String]) -> Result<Parsed, CliError> {
+    let mut spec = app;
+    let mut matches = Matches::default();
+    let error = |matches: &Matches, message: String| CliError {
+        command: matches.command.clone(),
+        message,
+    };
+    let mut options_done = false;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if !options_done && arg == "--" {
+            options_done = true;
+        } else if let (false, Some(long)) = (options_done, arg.strip_prefix("--")) {
+            let (name, inline) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            match name {
+                "help" => return Ok(Parsed::Help(matches.command)),
+                "version" => return Ok(Parsed::Version),
+                _ => {}
+            }
+            let Some(opt) = spec.options.iter().find(|o| o.long == name) else {
+                return Err(error(
+                    &matches,
+                    unknown_option(&format!("--{name}"), &matches),
+                ));
+            };
+            match (opt.value, inline) {
+                (Some(_), Some(value)) => push_value(&mut matches, opt, value),
+                (Some(_), None) => {
+                    let Some(value) = args.next() else {
+                        return Err(error(&matches, format!("--{name} requires a value")));
+                    };
+                    push_value(&mut matches, opt, value.clone());
+                }
+                (None, Some(_)) => {
+                    return Err(error(&matches, format!("--{name} doesn't take a value")));
+                }
+                (None, None) => {
+                    matches.switches.insert(opt.long);
+                }
+            }
+        } else if let (false, Some(shorts)) = (options_done, arg.strip_prefix('-')) {
+            if shorts.is_empty() {
+                return Err(error(&matches, format!("unexpected argument '{arg}'")));
+            }
+            // Each letter is a switch, until one that takes a value: the rest
+            // of the word (or else the next argument) is its value
+            for (i, c) in shorts.char_indices() {
+                match c {
+                    'h' => return Ok(Parsed::Help(matches.command)),
+                    'V' => return Ok(Parsed::Version),
+                    _ => {}
+                }
+                let Some(opt) = spec.options.iter().find(|o| o.short == Some(c)) else {
+                    return Err(error(&matches, unknown_option(&format!("-{c}"), &matches)));
+                };
+                if opt.value.is_none() {
+                    matches.switches.insert(opt.long);
+                    continue;
+                }
+                let rest = &shorts[i + c.len_utf8()..];
+                let value = if !rest.is_empty() {
+                    rest.to_string()
+                } else if let Some(value) = args.next() {
+                    value.clone()
+                } else {
+                    return Err(error(&matches, format!("-{c} requires a value")));
+                };
+                push_value(&mut matches, opt, value);
+                break;
+            }
+        } else if !options_done && !spec.subcommands.is_empty() {
+            if matches.command.is_empty() && arg == "help" {
+                return help_for(app, args.as_slice()).map(Parsed::Help);
+            }
+            let Some(sub) = spec.subcommand(arg) else {
+                return Err(error(&matches, unknown_command(arg, spec, &matches)));
+            };
+            spec = sub;
+            matches.command.push(sub.name);
+        } else if spec.operands.is_some() {
+            matches.operands.push(arg.clone());
+        } else {
+            return Err(error(&matches, format!("unexpected argument '{arg}'")));
+        }
+    }
+
+    if !spec.subcommands.is_empty() {
+        let names: Vec<&str> = spec.subcommands.iter().map(|c| c.name).collect();
+        let message = match matches.command.last() {
+            Some(name) => format!("'{name}' needs a command: {}", names.join(", ")),
+            None => format!("expected a command: {}", names.join(", ")),
+        };
+        return Err(error(&matches, message));
+    }
+    Ok(Parsed::Run(matches))
+}
+
+fn push_value(matches: &mut Matches, opt: &Opt, value: String) {
+    matches.values.entry(opt.long).or_default().push(value);
+}
+
+fn unknown_option(option: &str, matches: &Matches) -> String {
+    match matches.command.last() {
+        Some(command) => format!("unknown option '{option}' for '{command}'"),
+        None => format!("unknown option '{option}'"),
+    }
+}
+
+fn unknown_command(name: &str, spec: &CommandSpec, matches: &Matches) -> String {
+    let names: Vec<&str> = spec.subcommands.iter().map(|c| c.name).collect();
+    let command = match matches.command.last() {
+        Some(parent) => format!("'{parent} {name}'"),
+        None => format!("'{name}'"),
+    };
+    format!(
+        "unknown command {command}. Expected one of: {}",
+        names.join(", ")
+    )
+}
+
+/// `help <command>...`: the path of the command whose help to show.
+fn help_for(app: &CommandSpec, names: &[String]) -> Result<Vec<&'static str>, CliError> {
+    let mut spec = app;
+    let mut path = Vec::new();
+    for name in names {
+        let Some(sub) = spec.subcommand(name) else {
+            return Err(CliError {
+                message: unknown_command(name, spec, &Matches::default()),
+                command: path,
+            });
+        };
+        spec = sub;
+        path.push(sub.name);
+    }
+    Ok(path)
+}
+
+/// The help of the command at `path` below `app` (empty for `app` itself).
+pub fn help(app: &CommandSpec, path: &[&str]) -> String {
+    let mut spec = app;
+    for name in path {
+        spec = spec.subcommand(name).expect("help for a known command");
+    }
+    let invocation = std::iter::once(app.name)
+        .chain(path.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut out = format!("{}\n\nUsage: {invocation}", spec.about);
+    if !spec.subcommands.is_empty() {
+        out.push_str(" <COMMAND>");
+    }
+    if !spec.options.is_empty() {
+        out.push_str(" [OPTIONS]");
+    }
+    if let Some(operands) = spec.operands {
+        out.push_str(&format!(" [--] {operands}"));
+    }
+    out.push('\n');
+
+    if !spec.subcommands.is_empty() {
+        let mut rows: Vec<(String, &str)> = spec
+            .subcommands
+            .iter()
+            .map(|c| (c.name.to_string(), c.about))
+            .collect();
+        if path.is_empty() {
+            rows.push(("help".to_string(), "Show the help of a command"));
+        }
+        out.push_str("\nCommands:\n");
+        write_rows(&rows, &mut out);
+    }
+
+    let mut rows: Vec<(String, &str)> = spec
+        .options
+        .iter()
+        .map(|o| {
+            let short = match o.short {
+                Some(c) => format!("-{c}, "),
+                None => "    ".to_string(),
+            };
+            let value = o.value.map(|v| format!(" <{v}>")).unwrap_or_default();
+            (format!("{short}--{}{value}", o.long), o.help)
+        })
+        .collect();
+    rows.push(("-h, --help".to_string(), "Show this help"));
+    rows.push(("-V, --version".to_string(), "Show the version"));
+    out.push_str("\nOptions:\n");
+    write_rows(&rows, &mut out);
+
+    if !spec.subcommands.is_empty() {
+        out.push_str(&format!(
+            "\nRun '{invocation} <COMMAND> --help' for the options of a command.\n"
+        ));
+    }
+    out
+}
+
+/// Two aligned columns; a left entry too long for the column gets its
+/// description on the next line.
+fn write_rows(rows: &[(String, &str)], out: &mut String) {
+    const MAX_WIDTH: usize = 24;
+    let width = rows
+        .iter()
+        .map(|(left, _)| left.len())
+        .filter(|&len| len <= MAX_WIDTH)
+        .max()
+        .unwrap_or(0);
+    for (left, right) in rows {
+        if left.len() > width {
+            out.push_str(&format!("  {left}\n  {:width$}  {right}\n", ""));
+        } else {
+            out.push_str(&format!("  {left:width$}  {right}\n"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> CommandSpec {
+        CommandSpec {
+            name: "tool",
+            about: "A tool",
+            subcommands: vec![
+                CommandSpec {
+                    name: "build",
+                    about: "Build something",
+                    options: vec![
+                        Opt::value("output", "FILE", "Where to write").short('o'),
+                        Opt::value("tag", "TAG", "A tag (repeatable)"),
+                        Opt::switch("quiet", "Say less").short('q'),
+                        Opt::switch("force", "Overwrite").short('f'),
+                    ],
+                    operands: Some("PATH..."),
+                    ..CommandSpec::default()
+                },
+                CommandSpec {
+                    name: "cache",
+                    about: "Manage the cache",
+                    subcommands: vec![CommandSpec {
+                        name: "clear",
+                        about: "Empty the cache",
+                        ..CommandSpec::default()
+                    }],
+                    ..CommandSpec::default()
+                },
+            ],
+            ..CommandSpec::default()
+        }
+    }
+
+    fn run(args: &[&str]) -> Result<Parsed, CliError> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        parse(&app(), &args)
+    }
+
+    fn matches(args: &[&str]) -> Matches {
+        match run(args) {
+            Ok(Parsed::Run(matches)) => matches,
+            other => panic!("{args:?}: {other:?}"),
+        }
+    }
+
+    fn message(args: &[&str]) -> String {
+        run(args).unwrap_err().message
+    }
+
+    #[test]
+    fn test_long_options() {
+        let m = matches(&[
+            "build", "--output", "a.out", "--tag=x", "--tag", "y", "--quiet",
+        ]);
+        assert_eq!(m.command, ["build"]);
+        assert_eq!(m.value("output"), Some("a.out"));
+        assert_eq!(m.values("tag"), ["x", "y"]);
+        assert!(m.flag("quiet"));
+        assert!(!m.flag("force"));
+        assert_eq!(m.value("missing"), None);
+
+        // A value is taken as given, even when it looks like an option
+        let m = matches(&["build", "--output", "--help", "--tag="]);
+        assert_eq!(m.value("output"), Some("--help"));
+        assert_eq!(m.value("tag"), Some(""));
+    }
+
+    #[test]
+    fn test_short_options() {
+        let m = matches(&["build", "-qf", "-ofile"]);
+        assert!(m.flag("quiet") && m.flag("force"));
+        assert_eq!(m.value("output"), Some("file"));
+
+        let m = matches(&["build", "-qo", "file", "src"]);
+        assert!(m.flag("quiet"));
+        assert_eq!(m.value("output"), Some("file"));
+        assert_eq!(m.operands, ["src"]);
+    }
+
+    #[test]
+    fn test_double_dash_ends_options() {
+        let m = matches(&["build", "-q", "--", "--force", "-o"]);
+        assert!(m.flag("quiet") && !m.flag("force"));
+        assert_eq!(m.operands, ["--force", "-o"]);
+    }
+
+    #[test]
+    fn test_subcommands() {
+        assert_eq!(matches(&["cache", "clear"]).command, ["cache", "clear"]);
+        assert_eq!(message(&["cache"]), "'cache' needs a command: clear");
+        assert_eq!(
+            message(&["cache", "purge"]),
+            "unknown command 'cache purge'. Expected one of: clear"
+        );
+        assert_eq!(
+            message(&["deploy"]),
+            "unknown command 'deploy'. Expected one of: build, cache"
+        );
+        assert_eq!(message(&[]), "expected a command: build, cache");
+    }
+
+    #[test]
+    fn test_help_and_version() {
+        assert_eq!(run(&["--help"]), Ok(Parsed::Help(vec![])));
+        assert_eq!(run(&["build", "-h"]), Ok(Parsed::Help(vec!["build"])));
+        assert_eq!(run(&["build", "-qh"]), Ok(Parsed::Help(vec!["build"])));
+        assert_eq!(
+            run(&["help", "cache", "clear"]),
+            Ok(Parsed::Help(vec!["cache", "clear"]))
+        );
+        assert_eq!(run(&["build", "--version"]), Ok(Parsed::Version));
+        assert_eq!(run(&["-V"]), Ok(Parsed::Version));
+        assert!(run(&["help", "deploy"]).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            message(&["build", "--bogus"]),
+            "unknown option '--bogus' for 'build'"
+        );
+        assert_eq!(message(&["build", "-x"]), "unknown option '-x' for 'build'");
+        assert_eq!(message(&["build", "--output"]), "--output requires a value");
+        assert_eq!(message(&["build", "-o"]), "-o requires a value");
+        assert_eq!(
+            message(&["build", "--quiet=yes"]),
+            "--quiet doesn't take a value"
+        );
+        assert_eq!(
+            message(&["cache", "clear", "now"]),
+            "unexpected argument 'now'"
+        );
+        assert_eq!(run(&["build", "--bogus"]).unwrap_err().command, ["build"]);
+    }
+
+    #[test]
+    fn test_help_text() {
+        let app = app();
+        let top = help(&app, &[]);
+        assert!(
+            top.starts_with("A tool\n\nUsage: tool <COMMAND>\n"),
+            "{top}"
+        );
+        assert!(top.contains("\n  build  Build something\n"), "{top}");
+        assert!(
+            top.contains("\n  help   Show the help of a command\n"),
+            "{top}"
+        );
+        assert!(top.ends_with("Run 'tool <COMMAND> --help' for the options of a command.\n"));
+
+        let build = help(&app, &["build"]);
+        assert!(
+            build.contains("Usage: tool build [OPTIONS] [--] PATH...\n"),
+            "{build}"
+        );
+        assert!(
+            build.contains("\n  -o, --output <FILE>  Where to write\n"),
+            "{build}"
+        );
+        assert!(
+            build.contains("\n      --tag <TAG>      A tag (repeatable)\n"),
+            "{build}"
+        );
+        assert!(
+            build.contains("\n  -h, --help           Show this help\n"),
+            "{build}"
+        );
+    }
+}
@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Index;
 
 /// A flag for a CLI command (e.g., --verbose, -v).
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct Flag {
     /// Long form, e.g. "verbose"
     pub long: String,
@@ -23,37 +25,390 @@ pub struct Flag {
     pub short: Option<char>,
     /// Human-readable description
     pub description: String,
-    /// The value type if the flag takes an argument (e.g. "string", "int", "strings").
-    /// None for boolean flags.
-    pub value_type: Option<String>,
+    /// The pflag type shown after the flag name (e.g. `ValueKind::String` for
+    /// "string", `ValueKind::Ints` for "ints"). None for boolean flags.
+    pub value_type: Option<ValueKind>,
     /// Default value, if any
     pub default: Option<String>,
+    /// Marked deprecated in its description; completed only once fully typed
+    pub deprecated: bool,
+    /// Never offered in completions
+    pub hidden: bool,
+    /// Hand-set value completion (from an override), used instead of the
+    /// one derived from the value type
+    pub completion: Option<ValueCompletion>,
+}
+
+impl Flag {
+    /// A boolean flag with no short form, default or special completion.
+    pub fn new(long: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            long: long.into(),
+            short: None,
+            description: description.into(),
+            value_type: None,
+            default: None,
+            deprecated: false,
+            hidden: false,
+            completion: None,
+        }
+    }
+
+    /// Whether the flag consumes the following word as its value.
+    /// Count flags (`-v, --verbose count`) are repeated, not given a value.
+    pub fn takes_value(&self) -> bool {
+        matches!(&self.value_type, Some(kind) if *kind != ValueKind::Count)
+    }
+
+    /// How a generator should complete this flag's value.
+    pub fn value_completion(&self) -> ValueCompletion {
+        let kind = match &self.value_type {
+            Some(kind) if self.takes_value() => kind,
+            _ => return ValueCompletion::Nothing,
+        };
+        if let Some(completion) = &self.completion {
+            return completion.clone();
+        }
+        match kind {
+            ValueKind::Bool | ValueKind::Bools => ValueCompletion::Bool,
+            ValueKind::Duration | ValueKind::DurationSlice => ValueCompletion::Duration,
+            k if k.is_key_value() => ValueCompletion::KeyValue,
+            k if k.is_numeric() || k.is_opaque() => ValueCompletion::Nothing,
+            _ => {
+                let name = match kind {
+                    ValueKind::Custom(name) => name.as_str(),
+                    _ => self.long.as_str(),
+                };
+                if name.ends_with("dir") || name == "directory" {
+                    ValueCompletion::Directory
+                } else if name.contains("file") || name.contains("path") || self.long == "db" {
+                    ValueCompletion::File
+                } else {
+                    ValueCompletion::Nothing
+                }
+            }
+        }
+    }
+}
+
+/// The completion a generator should offer for a flag value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValueCompletion {
+    /// Free-form, numeric or opaque values: no candidates and no files
+    Nothing,
+    /// `true` / `false`
+    Bool,
+    File,
+    Directory,
+    /// Unit suffixes (`ms`, `s`, `m`, `h`) after a typed number
+    Duration,
+    /// `key=` with no trailing space
+    KeyValue,
+    /// A fixed list of values
+    Values(Vec<String>),
+    /// The lines a shell command prints
+    Command(String),
+    /// Issue IDs, as listed by `bd list --json`
+    IssueId,
+}
+
+impl ValueCompletion {
+    /// The shell command whose output lines are the candidates, for the
+    /// completions that run one. `root` is the name bd is invoked as.
+    pub fn command(&self, root: &str) -> Option<String> {
+        match self {
+            ValueCompletion::Command(command) => Some(command.clone()),
+            // Nested dependencies repeat the key; their IDs are issues too
+            ValueCompletion::IssueId => Some(format!(
+                "{root} list --json 2>/dev/null | grep -o '\"id\": *\"[^\"]*\"' | cut -d'\"' -f4"
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// The pflag value type shown after a flag name in help output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValueKind {
+    Bool,
+    Bools,
+    String,
+    /// `stringSlice`, shown as "strings"
+    Strings,
+    StringArray,
+    Int,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    /// `intSlice`, shown as "ints"
+    Ints,
+    Int32Slice,
+    Int64Slice,
+    Uint,
+    Uint8,
+    Uint16,
+    Uint32,
+    Uint64,
+    /// `uintSlice`, shown as "uints"
+    Uints,
+    Float32,
+    /// `float64`, shown as "float"
+    Float64,
+    Float32Slice,
+    Float64Slice,
+    Count,
+    Duration,
+    DurationSlice,
+    Ip,
+    IpSlice,
+    IpMask,
+    IpNet,
+    BytesHex,
+    BytesBase64,
+    StringToString,
+    StringToInt,
+    StringToInt64,
+    /// A custom `Value` type or a back-quoted name from the usage text (e.g. "path")
+    Custom(String),
+}
+
+impl ValueKind {
+    /// Map a type token from help output to its kind. Unrecognized names become `Custom`.
+    pub fn from_type_name(name: &str) -> Self {
+        Self::known(name).unwrap_or_else(|| ValueKind::Custom(name.to_string()))
+    }
+
+    /// Map a type token to a built-in pflag kind, if it is one.
+    pub fn known(name: &str) -> Option<Self> {
+        Some(match name {
+            "bool" => ValueKind::Bool,
+            "bools" | "boolSlice" => ValueKind::Bools,
+            "string" => ValueKind::String,
+            "strings" | "stringSlice" => ValueKind::Strings,
+            "stringArray" => ValueKind::StringArray,
+            "int" => ValueKind::Int,
+            "int8" => ValueKind::Int8,
+            "int16" => ValueKind::Int16,
+            "int32" => ValueKind::Int32,
+            "int64" => ValueKind::Int64,
+            "ints" | "intSlice" => ValueKind::Ints,
+            "int32Slice" => ValueKind::Int32Slice,
+            "int64Slice" => ValueKind::Int64Slice,
+            "uint" => ValueKind::Uint,
+            "uint8" => ValueKind::Uint8,
+            "uint16" => ValueKind::Uint16,
+            "uint32" => ValueKind::Uint32,
+            "uint64" => ValueKind::Uint64,
+            "uints" | "uintSlice" => ValueKind::Uints,
+            "float32" => ValueKind::Float32,
+            "float" | "float64" => ValueKind::Float64,
+            "float32Slice" => ValueKind::Float32Slice,
+            "float64Slice" => ValueKind::Float64Slice,
+            "count" => ValueKind::Count,
+            "duration" => ValueKind::Duration,
+            "durationSlice" => ValueKind::DurationSlice,
+            "ip" => ValueKind::Ip,
+            "ipSlice" => ValueKind::IpSlice,
+            "ipMask" => ValueKind::IpMask,
+            "ipNet" => ValueKind::IpNet,
+            "bytesHex" => ValueKind::BytesHex,
+            "bytesBase64" => ValueKind::BytesBase64,
+            "stringToString" => ValueKind::StringToString,
+            "stringToInt" => ValueKind::StringToInt,
+            "stringToInt64" => ValueKind::StringToInt64,
+            _ => return None,
+        })
+    }
+
+    /// The type token as pflag prints it in help output.
+    pub fn type_name(&self) -> &str {
+        match self {
+            ValueKind::Bool => "bool",
+            ValueKind::Bools => "bools",
+            ValueKind::String => "string",
+            ValueKind::Strings => "strings",
+            ValueKind::StringArray => "stringArray",
+            ValueKind::Int => "int",
+            ValueKind::Int8 => "int8",
+            ValueKind::Int16 => "int16",
+            ValueKind::Int32 => "int32",
+            ValueKind::Int64 => "int64",
+            ValueKind::Ints => "ints",
+            ValueKind::Int32Slice => "int32Slice",
+            ValueKind::Int64Slice => "int64Slice",
+            ValueKind::Uint => "uint",
+            ValueKind::Uint8 => "uint8",
+            ValueKind::Uint16 => "uint16",
+            ValueKind::Uint32 => "uint32",
+            ValueKind::Uint64 => "uint64",
+            ValueKind::Uints => "uints",
+            ValueKind::Float32 => "float32",
+            ValueKind::Float64 => "float",
+            ValueKind::Float32Slice => "float32Slice",
+            ValueKind::Float64Slice => "float64Slice",
+            ValueKind::Count => "count",
+            ValueKind::Duration => "duration",
+            ValueKind::DurationSlice => "durationSlice",
+            ValueKind::Ip => "ip",
+            ValueKind::IpSlice => "ipSlice",
+            ValueKind::IpMask => "ipMask",
+            ValueKind::IpNet => "ipNet",
+            ValueKind::BytesHex => "bytesHex",
+            ValueKind::BytesBase64 => "bytesBase64",
+            ValueKind::StringToString => "stringToString",
+            ValueKind::StringToInt => "stringToInt",
+            ValueKind::StringToInt64 => "stringToInt64",
+            ValueKind::Custom(name) => name,
+        }
+    }
+
+    /// Integer, unsigned, float and count kinds (and their slices).
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            ValueKind::Int
+                | ValueKind::Int8
+                | ValueKind::Int16
+                | ValueKind::Int32
+                | ValueKind::Int64
+                | ValueKind::Ints
+                | ValueKind::Int32Slice
+                | ValueKind::Int64Slice
+                | ValueKind::Uint
+                | ValueKind::Uint8
+                | ValueKind::Uint16
+                | ValueKind::Uint32
+                | ValueKind::Uint64
+                | ValueKind::Uints
+                | ValueKind::Float32
+                | ValueKind::Float64
+                | ValueKind::Float32Slice
+                | ValueKind::Float64Slice
+                | ValueKind::Count
+        )
+    }
+
+    /// Kinds that may be given more than once, accumulating values:
+    /// slices, arrays, maps and counts.
+    pub fn is_repeatable(&self) -> bool {
+        matches!(
+            self,
+            ValueKind::Bools
+                | ValueKind::Strings
+                | ValueKind::StringArray
+                | ValueKind::Ints
+                | ValueKind::Int32Slice
+                | ValueKind::Int64Slice
+                | ValueKind::Uints
+                | ValueKind::Float32Slice
+                | ValueKind::Float64Slice
+                | ValueKind::Count
+                | ValueKind::DurationSlice
+                | ValueKind::IpSlice
+        ) || self.is_key_value()
+    }
+
+    /// `key=value` map kinds.
+    pub fn is_key_value(&self) -> bool {
+        matches!(
+            self,
+            ValueKind::StringToString | ValueKind::StringToInt | ValueKind::StringToInt64
+        )
+    }
+
+    /// Network address kinds and raw byte encodings: typed by hand, never files.
+    pub fn is_opaque(&self) -> bool {
+        matches!(
+            self,
+            ValueKind::Ip
+                | ValueKind::IpSlice
+                | ValueKind::IpMask
+                | ValueKind::IpNet
+                | ValueKind::BytesHex
+                | ValueKind::BytesBase64
+        )
+    }
+}
+
+/// A positional argument taken from a command's usage line
+/// (e.g. `<issue>`, `[title]`, `[id...]`).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct PositionalArg {
+    /// Argument name with brackets and ellipsis removed, e.g. "issue"
+    pub name: String,
+    /// `<issue>` is required, `[title]` is optional
+    pub required: bool,
+    /// Whether the argument accepts multiple values (`[id...]`)
+    pub variadic: bool,
+}
+
+impl PositionalArg {
+    /// Whether the argument names an issue: `id`, `issue`, `issue-id`,
+    /// `depends-on-id`, ...
+    pub fn is_issue_id(&self) -> bool {
+        self.name == "id" || self.name == "issue" || self.name.ends_with("-id")
+    }
+}
+
+/// Whether a flag or command description marks it as deprecated, e.g.
+/// "DEPRECATED: use --type", "Old name (deprecated: use --x)".
+pub fn is_deprecated_description(description: &str) -> bool {
+    let lower = description.to_ascii_lowercase();
+    description.contains("DEPRECATED")
+        || lower.starts_with("deprecated")
+        || lower.contains("(deprecated")
+        || lower.contains("deprecated:")
 }
 
 /// A command group/category (e.g., "Working With Issues", "Views & Reports").
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct CommandGroup {
     pub name: String,
     pub commands: Vec<String>,
 }
 
+impl CommandGroup {
+    pub fn new(name: impl Into<String>, commands: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            commands,
+        }
+    }
+}
+
 /// A single command node in the tree.
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct Command {
     /// The command name (e.g. "create", "epic")
     pub name: String,
-    /// Human-readable description
+    /// One-line description (from the parent's command listing)
     pub description: String,
+    /// Full description from the command's own help preamble, line breaks preserved
+    pub long_description: Option<String>,
     /// Aliases for this command (e.g. ["new"] for "create")
     pub aliases: Vec<String>,
     /// Usage string from help output
     pub usage: Option<String>,
+    /// Positional arguments decomposed from the usage line
+    pub args: Vec<PositionalArg>,
+    /// The `Examples:` block, dedented, blank lines preserved
+    pub examples: Option<String>,
     /// Flags local to this command
     pub flags: Vec<Flag>,
-    /// Subcommands keyed by name
-    pub subcommands: BTreeMap<String, Command>,
+    /// Subcommands by name, in the order help lists them
+    pub subcommands: CommandMap,
     /// Which group/category this command belongs to (from parent's help)
     pub group: Option<String>,
+    /// Marked deprecated in its description; completed only once fully typed
+    pub deprecated: bool,
+    /// Never offered in completions (cobra's `help`/`completion` helpers by default)
+    pub hidden: bool,
 }
 
 impl Command {
@@ -61,17 +416,176 @@ impl Command {
         Self {
             name: name.into(),
             description: description.into(),
+            long_description: None,
             aliases: Vec::new(),
             usage: None,
+            args: Vec::new(),
+            examples: None,
             flags: Vec::new(),
-            subcommands: BTreeMap::new(),
+            subcommands: CommandMap::new(),
             group: None,
+            deprecated: false,
+            hidden: false,
         }
     }
+
+    /// Sort subcommands and flags by name, all the way down.
+    pub fn sort_by_name(&mut self) {
+        self.flags.sort_by(|a, b| a.long.cmp(&b.long));
+        self.subcommands.sort_by_name();
+        for sub in self.subcommands.values_mut() {
+            sub.sort_by_name();
+        }
+    }
+
+    /// Whether this is one of the helper commands cobra adds to every root command.
+    pub fn is_helper(&self) -> bool {
+        self.name == "help" || self.name == "completion"
+    }
+}
+
+/// Subcommands by name, kept in insertion order (the order help lists
+/// them) with lookup by name.
+#[derive(Debug, Clone, Default)]
+pub struct CommandMap {
+    entries: Vec<(String, Command)>,
+    index: HashMap<String, usize>,
 }
 
+impl CommandMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add a command at the end, or replace one of the same name in place.
+    /// Returns the replaced command.
+    pub fn insert(&mut self, name: String, cmd: Command) -> Option<Command> {
+        if let Some(&i) = self.index.get(&name) {
+            return Some(std::mem::replace(&mut self.entries[i].1, cmd));
+        }
+        self.index.insert(name.clone(), self.entries.len());
+        self.entries.push((name, cmd));
+        None
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.index.get(name).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Command> {
+        self.index.get(name).map(|&i| &mut self.entries[i].1)
+    }
+
+    pub fn get_key_value(&self, name: &str) -> Option<(&String, &Command)> {
+        self.index.get(name).map(|&i| {
+            let (name, cmd) = &self.entries[i];
+            (name, cmd)
+        })
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Remove a command, keeping the others in order.
+    pub fn remove(&mut self, name: &str) -> Option<Command> {
+        let i = self.index.remove(name)?;
+        let (_, cmd) = self.entries.remove(i);
+        for (name, _) in &self.entries[i..] {
+            *self.index.get_mut(name).unwrap() -= 1;
+        }
+        Some(cmd)
+    }
+
+    /// Give the command `old` the key `new` in its current place. The
+    /// command's own name is left alone. Returns it, or None if `old` is
+    /// missing or `new` is taken.
+    pub fn rename(&mut self, old: &str, new: &str) -> Option<&mut Command> {
+        if self.index.contains_key(new) {
+            return None;
+        }
+        let i = self.index.remove(old)?;
+        self.index.insert(new.to_string(), i);
+        let entry = &mut self.entries[i];
+        entry.0 = new.to_string();
+        Some(&mut entry.1)
+    }
+
+    pub fn sort_by_name(&mut self) {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (i, (name, _)) in self.entries.iter().enumerate() {
+            *self.index.get_mut(name).unwrap() = i;
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&String, &Command)> {
+        self.entries.iter().map(|(name, cmd)| (name, cmd))
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &String> {
+        self.entries.iter().map(|(name, _)| name)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Command> {
+        self.entries.iter().map(|(_, cmd)| cmd)
+    }
+
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Command> {
+        self.entries.iter_mut().map(|(_, cmd)| cmd)
+    }
+}
+
+/// Equal when they hold the same commands in the same order.
+impl PartialEq for CommandMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl Index<&str> for CommandMap {
+    type Output = Command;
+
+    fn index(&self, name: &str) -> &Command {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no subcommand named {name:?}"))
+    }
+}
+
+impl<'a> IntoIterator for &'a CommandMap {
+    type Item = (&'a String, &'a Command);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (String, Command)>,
+        fn(&'a (String, Command)) -> (&'a String, &'a Command),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(name, cmd)| (name, cmd))
+    }
+}
+
+impl IntoIterator for CommandMap {
+    type Item = (String, Command);
+    type IntoIter = std::vec::IntoIter<(String, Command)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// Commands of one help group, or of no group (`None`), with their names.
+pub type GroupSection<'a> = (Option<&'a str>, Vec<(&'a String, &'a Command)>);
+
 /// The root of the parsed command tree.
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct CommandTree {
     /// The root command (e.g., "bd")
     pub root: Command,
@@ -89,4 +603,78 @@ impl CommandTree {
             groups: Vec::new(),
         }
     }
+
+    /// Sort every command's subcommands and flags, the global flags, and the
+    /// commands within each group by name. Groups keep their help order.
+    pub fn sort_by_name(&mut self) {
+        self.root.sort_by_name();
+        self.global_flags.sort_by(|a, b| a.long.cmp(&b.long));
+        for group in &mut self.groups {
+            group.commands.sort();
+        }
+    }
+
+    /// Top-level commands in `bd --help` order: each group's commands as
+    /// listed, then those in no group (`None`).
+    pub fn grouped_subcommands(&self) -> Vec<GroupSection<'_>> {
+        let mut sections = Vec::new();
+        let mut listed: BTreeSet<&str> = BTreeSet::new();
+        for group in &self.groups {
+            let commands: Vec<(&String, &Command)> = group
+                .commands
+                .iter()
+                .filter_map(|name| self.root.subcommands.get_key_value(name))
+                .filter(|(name, _)| listed.insert(name.as_str()))
+                .collect();
+            if !commands.is_empty() {
+                sections.push((Some(group.name.as_str()), commands));
+            }
+        }
+        let rest: Vec<(&String, &Command)> = self
+            .root
+            .subcommands
+            .iter()
+            .filter(|(name, _)| !listed.contains(name.as_str()))
+            .collect();
+        if !rest.is_empty() {
+            sections.push((None, rest));
+        }
+        sections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(map: &CommandMap) -> Vec<&str> {
+        map.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_command_map_keeps_insertion_order() {
+        let mut map = CommandMap::new();
+        for name in ["show", "create", "list", "dep"] {
+            map.insert(name.to_string(), Command::new(name, ""));
+        }
+        // Replacing keeps the place
+        let old = map.insert("create".to_string(), Command::new("create", "New"));
+        assert_eq!(old.map(|c| c.description), Some(String::new()));
+        assert_eq!(names(&map), ["show", "create", "list", "dep"]);
+        assert_eq!(map["create"].description, "New");
+
+        assert!(map.remove("create").is_some());
+        assert_eq!(names(&map), ["show", "list", "dep"]);
+        assert_eq!(map["dep"].name, "dep");
+
+        assert!(map.rename("list", "dep").is_none());
+        map.rename("list", "ls").unwrap();
+        assert_eq!(names(&map), ["show", "ls", "dep"]);
+        assert!(!map.contains_key("list"));
+        assert_eq!(map["ls"].name, "list");
+
+        map.sort_by_name();
+        assert_eq!(names(&map), ["dep", "ls", "show"]);
+        assert_eq!(map.get("show").unwrap().name, "show");
+    }
 }
@@ -0,0 +1,572 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command reference documentation in Markdown or HTML.
+
+use crate::command_tree::{Command, CommandTree, Flag};
+use crate::error::BdCompleteError;
+use crate::options::GenerateOptions;
+use std::io::{self, Write};
+
+/// Heading for top-level commands that aren't listed under any group.
+const UNGROUPED: &str = "Other Commands";
+
+/// A command in document order, with its full path (e.g. "bd dep add").
+struct Entry<'a> {
+    path: String,
+    cmd: &'a Command,
+    depth: usize,
+}
+
+/// Top-level commands grouped as in `bd --help`, each followed by its
+/// subcommands depth-first.
+fn grouped_entries<'a>(
+    tree: &'a CommandTree,
+    opts: &GenerateOptions,
+) -> Vec<(String, Vec<Entry<'a>>)> {
+    let root = &tree.root;
+    let mut sections: Vec<(String, Vec<Entry>)> = Vec::new();
+
+    let push = |group: &str, cmd: &'a Command, sections: &mut Vec<(String, Vec<Entry<'a>>)>| {
+        let mut entries = Vec::new();
+        collect_entries(cmd, &format!("{} {}", root.name, cmd.name), 0, opts, &mut entries);
+        match sections.iter_mut().find(|(name, _)| name == group) {
+            Some((_, existing)) => existing.extend(entries),
+            None => sections.push((group.to_string(), entries)),
+        }
+    };
+
+    for (group, commands) in tree.grouped_subcommands() {
+        for (_, cmd) in commands.into_iter().filter(|(_, c)| opts.includes(c)) {
+            push(group.unwrap_or(UNGROUPED), cmd, &mut sections);
+        }
+    }
+    sections
+}
+
+fn collect_entries<'a>(
+    cmd: &'a Command,
+    path: &str,
+    depth: usize,
+    opts: &GenerateOptions,
+    entries: &mut Vec<Entry<'a>>,
+) {
+    entries.push(Entry {
+        path: path.to_string(),
+        cmd,
+        depth,
+    });
+    for (name, sub) in cmd.subcommands.iter().filter(|(_, c)| opts.includes(c)) {
+        collect_entries(sub, &format!("{path} {name}"), depth + 1, opts, entries);
+    }
+}
+
+/// Anchor for a command path, matching GitHub's heading slugs ("bd dep add" → "bd-dep-add").
+fn anchor(path: &str) -> String {
+    path.to_lowercase().replace(' ', "-")
+}
+
+fn visible_flags(flags: &[Flag]) -> Vec<&Flag> {
+    flags.iter().filter(|f| !f.hidden).collect()
+}
+
+/// Visible root flags that subcommands don't inherit, like --version.
+fn root_only_flags(tree: &CommandTree) -> Vec<&Flag> {
+    tree.root
+        .flags
+        .iter()
+        .filter(|f| !f.hidden && !tree.global_flags.iter().any(|g| g.long == f.long))
+        .collect()
+}
+
+/// The long description, or the short one when help had nothing more.
+fn full_description(cmd: &Command) -> &str {
+    cmd.long_description.as_deref().unwrap_or(&cmd.description)
+}
+
+/// Render the command reference as Markdown.
+pub fn generate_markdown_docs(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    let tree = opts.ordered(tree);
+    write_markdown_docs(&tree, opts, out).map_err(BdCompleteError::Write)
+}
+
+fn write_markdown_docs(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let root = &tree.root;
+    let sections = grouped_entries(tree, opts);
+
+    writeln!(out, "# {} command reference", root.name)?;
+    writeln!(out)?;
+    writeln!(out, "<!-- Generated by bd-complete; edit the command help instead. -->")?;
+    writeln!(out)?;
+    write_markdown_paragraphs(full_description(root), out)?;
+    if let Some(usage) = &root.usage {
+        writeln!(out, "```text\n{usage}\n```")?;
+        writeln!(out)?;
+    }
+    let root_flags = root_only_flags(tree);
+    if !root_flags.is_empty() {
+        writeln!(out, "**Flags:**")?;
+        writeln!(out)?;
+        write_markdown_flags(&root_flags, out)?;
+    }
+
+    writeln!(out, "## Contents")?;
+    writeln!(out)?;
+    for (group, entries) in &sections {
+        writeln!(out, "**{}**", md_escape(group))?;
+        writeln!(out)?;
+        for entry in entries {
+            writeln!(
+                out,
+                "{}- [{}](#{}) — {}",
+                "  ".repeat(entry.depth),
+                entry.path,
+                anchor(&entry.path),
+                md_escape(&entry.cmd.description)
+            )?;
+        }
+        writeln!(out)?;
+    }
+    let globals = visible_flags(&tree.global_flags);
+    if !globals.is_empty() {
+        writeln!(out, "- [Global flags](#global-flags)")?;
+        writeln!(out)?;
+    }
+
+    for (group, entries) in &sections {
+        writeln!(out, "## {}", md_escape(group))?;
+        writeln!(out)?;
+        for entry in entries {
+            write_markdown_command(entry, out)?;
+        }
+    }
+
+    if !globals.is_empty() {
+        writeln!(out, "## Global flags")?;
+        writeln!(out)?;
+        writeln!(out, "Accepted by every command.")?;
+        writeln!(out)?;
+        write_markdown_flags(&globals, out)?;
+    }
+    Ok(())
+}
+
+fn write_markdown_command(entry: &Entry, out: &mut dyn Write) -> io::Result<()> {
+    let cmd = entry.cmd;
+    // Top-level commands sit under a group heading, so start one level down
+    let level = "#".repeat((entry.depth + 3).min(6));
+    writeln!(out, "{level} {}", entry.path)?;
+    writeln!(out)?;
+    if cmd.deprecated {
+        writeln!(out, "> **Deprecated.**")?;
+        writeln!(out)?;
+    }
+    write_markdown_paragraphs(full_description(cmd), out)?;
+
+    if let Some(usage) = &cmd.usage {
+        writeln!(out, "```text\n{usage}\n```")?;
+        writeln!(out)?;
+    }
+    if !cmd.aliases.is_empty() {
+        let aliases: Vec<String> = cmd.aliases.iter().map(|a| format!("`{a}`")).collect();
+        writeln!(out, "**Aliases:** {}", aliases.join(", "))?;
+        writeln!(out)?;
+    }
+    let flags = visible_flags(&cmd.flags);
+    if !flags.is_empty() {
+        writeln!(out, "**Flags:**")?;
+        writeln!(out)?;
+        write_markdown_flags(&flags, out)?;
+    }
+    if let Some(examples) = &cmd.examples {
+        writeln!(out, "**Examples:**")?;
+        writeln!(out)?;
+        writeln!(out, "```sh\n{examples}\n```")?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn write_markdown_paragraphs(text: &str, out: &mut dyn Write) -> io::Result<()> {
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+    for paragraph in text.split("\n\n") {
+        // Keep help's own line breaks; trailing backslashes force them in Markdown
+        let lines: Vec<String> = paragraph.lines().map(|l| md_escape(l.trim())).collect();
+        writeln!(out, "{}", lines.join("\\\n"))?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn write_markdown_flags(flags: &[&Flag], out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "| Flag | Type | Default | Description |")?;
+    writeln!(out, "| --- | --- | --- | --- |")?;
+    for flag in flags {
+        let mut names = format!("`--{}`", flag.long);
+        if let Some(s) = flag.short {
+            names = format!("`-{s}`, {names}");
+        }
+        let kind = flag.value_type.as_ref().map(|k| k.type_name()).unwrap_or("");
+        let default = flag
+            .default
+            .as_deref()
+            .map(|d| format!("`{}`", d.replace('|', "\\|")))
+            .unwrap_or_default();
+        let mut description = md_escape(&flag.description);
+        if flag.deprecated {
+            description = format!("*Deprecated.* {description}");
+        }
+        writeln!(out, "| {names} | {kind} | {default} | {description} |")?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Escape text so Markdown shows it literally; `<id>` would otherwise be
+/// taken for an HTML tag and `|` would end a table cell.
+fn md_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Render the command reference as a standalone HTML page.
+pub fn generate_html_docs(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    let tree = opts.ordered(tree);
+    write_html_docs(&tree, opts, out).map_err(BdCompleteError::Write)
+}
+
+fn write_html_docs(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let root = &tree.root;
+    let sections = grouped_entries(tree, opts);
+    let title = format!("{} command reference", html_escape(&root.name));
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<!-- Generated by bd-complete; edit the command help instead. -->")?;
+    writeln!(out, "<html lang=\"en\">")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>{title}</title>")?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    writeln!(out, "<h1>{title}</h1>")?;
+    write_html_paragraphs(full_description(root), out)?;
+    if let Some(usage) = &root.usage {
+        writeln!(out, "<pre><code>{}</code></pre>", html_escape(usage))?;
+    }
+    let root_flags = root_only_flags(tree);
+    if !root_flags.is_empty() {
+        writeln!(out, "<p><strong>Flags:</strong></p>")?;
+        write_html_flags(&root_flags, out)?;
+    }
+
+    writeln!(out, "<nav>")?;
+    writeln!(out, "<h2>Contents</h2>")?;
+    for (group, entries) in &sections {
+        writeln!(out, "<h3>{}</h3>", html_escape(group))?;
+        write_html_toc(entries, out)?;
+    }
+    let globals = visible_flags(&tree.global_flags);
+    if !globals.is_empty() {
+        writeln!(out, "<p><a href=\"#global-flags\">Global flags</a></p>")?;
+    }
+    writeln!(out, "</nav>")?;
+
+    for (group, entries) in &sections {
+        writeln!(out, "<h2>{}</h2>", html_escape(group))?;
+        for entry in entries {
+            write_html_command(entry, out)?;
+        }
+    }
+
+    if !globals.is_empty() {
+        writeln!(out, "<section id=\"global-flags\">")?;
+        writeln!(out, "<h2>Global flags</h2>")?;
+        writeln!(out, "<p>Accepted by every command.</p>")?;
+        write_html_flags(&globals, out)?;
+        writeln!(out, "</section>")?;
+    }
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")?;
+    Ok(())
+}
+
+/// Nested lists following each entry's depth.
+fn write_html_toc(entries: &[Entry], out: &mut dyn Write) -> io::Result<()> {
+    let mut depth = 0;
+    writeln!(out, "<ul>")?;
+    for (i, entry) in entries.iter().enumerate() {
+        while depth < entry.depth {
+            writeln!(out, "<ul>")?;
+            depth += 1;
+        }
+        while depth > entry.depth {
+            writeln!(out, "</ul></li>")?;
+            depth -= 1;
+        }
+        write!(
+            out,
+            "<li><a href=\"#{}\">{}</a> — {}",
+            anchor(&entry.path),
+            html_escape(&entry.path),
+            html_escape(&entry.cmd.description)
+        )?;
+        // Leave the item open when its children follow
+        if entries.get(i + 1).is_some_and(|next| next.depth > entry.depth) {
+            writeln!(out)?;
+        } else {
+            writeln!(out, "</li>")?;
+        }
+    }
+    while depth > 0 {
+        writeln!(out, "</ul></li>")?;
+        depth -= 1;
+    }
+    writeln!(out, "</ul>")?;
+    Ok(())
+}
+
+fn write_html_command(entry: &Entry, out: &mut dyn Write) -> io::Result<()> {
+    let cmd = entry.cmd;
+    let level = (entry.depth + 3).min(6);
+    writeln!(out, "<section id=\"{}\">", anchor(&entry.path))?;
+    writeln!(out, "<h{level}>{}</h{level}>", html_escape(&entry.path))?;
+    if cmd.deprecated {
+        writeln!(out, "<p><strong>Deprecated.</strong></p>")?;
+    }
+    write_html_paragraphs(full_description(cmd), out)?;
+
+    if let Some(usage) = &cmd.usage {
+        writeln!(out, "<pre><code>{}</code></pre>", html_escape(usage))?;
+    }
+    if !cmd.aliases.is_empty() {
+        let aliases: Vec<String> = cmd
+            .aliases
+            .iter()
+            .map(|a| format!("<code>{}</code>", html_escape(a)))
+            .collect();
+        writeln!(out, "<p><strong>Aliases:</strong> {}</p>", aliases.join(", "))?;
+    }
+    let flags = visible_flags(&cmd.flags);
+    if !flags.is_empty() {
+        writeln!(out, "<p><strong>Flags:</strong></p>")?;
+        write_html_flags(&flags, out)?;
+    }
+    if let Some(examples) = &cmd.examples {
+        writeln!(out, "<p><strong>Examples:</strong></p>")?;
+        writeln!(out, "<pre><code>{}</code></pre>", html_escape(examples))?;
+    }
+    writeln!(out, "</section>")?;
+    Ok(())
+}
+
+fn write_html_paragraphs(text: &str, out: &mut dyn Write) -> io::Result<()> {
+    for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        let lines: Vec<String> = paragraph.lines().map(|l| html_escape(l.trim())).collect();
+        writeln!(out, "<p>{}</p>", lines.join("<br>\n"))?;
+    }
+    Ok(())
+}
+
+fn write_html_flags(flags: &[&Flag], out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "<table>")?;
+    writeln!(
+        out,
+        "<thead><tr><th>Flag</th><th>Type</th><th>Default</th><th>Description</th></tr></thead>"
+    )?;
+    writeln!(out, "<tbody>")?;
+    for flag in flags {
+        let mut names = format!("<code>--{}</code>", html_escape(&flag.long));
+        if let Some(s) = flag.short {
+            names = format!("<code>-{}</code>, {names}", html_escape(&s.to_string()));
+        }
+        let kind = flag.value_type.as_ref().map(|k| k.type_name()).unwrap_or("");
+        let default = flag
+            .default
+            .as_deref()
+            .map(|d| format!("<code>{}</code>", html_escape(d)))
+            .unwrap_or_default();
+        let mut description = html_escape(&flag.description);
+        if flag.deprecated {
+            description = format!("<em>Deprecated.</em> {description}");
+        }
+        writeln!(
+            out,
+            "<tr><td>{names}</td><td>{}</td><td>{default}</td><td>{description}</td></tr>",
+            html_escape(kind)
+        )?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")?;
+    Ok(())
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_tree::{CommandGroup, ValueKind};
+
+    fn sample_tree() -> CommandTree {
+        let mut root = Command::new("bd", "A lightweight issue tracker");
+        root.usage = Some("bd [command]".to_string());
+
+        let mut create = Command::new("create", "Create a new issue");
+        create.usage = Some("bd create [title] [flags]".to_string());
+        create.aliases = vec!["new".to_string()];
+        create.examples = Some("bd create \"Fix <div> | layout\" -p 1".to_string());
+        create.flags.push(Flag {
+            short: Some('p'),
+            value_type: Some(ValueKind::String),
+            default: Some("2".to_string()),
+            ..Flag::new("priority", "Priority (0-4 or P0-P4)")
+        });
+        create.flags.push(Flag {
+            deprecated: true,
+            ..Flag::new("legacy", "Old behavior")
+        });
+
+        let mut dep = Command::new("dep", "Manage dependencies");
+        dep.subcommands.insert(
+            "add".to_string(),
+            Command::new("add", "Add a dependency <from> <to>"),
+        );
+
+        let mut help = Command::new("help", "Help about any command");
+        help.hidden = true;
+
+        root.subcommands.insert("create".to_string(), create);
+        root.subcommands.insert("dep".to_string(), dep);
+        root.subcommands.insert("version".to_string(), Command::new("version", "Print version"));
+        root.subcommands.insert("help".to_string(), help);
+
+        let mut tree = CommandTree::new(root);
+        tree.groups = vec![CommandGroup {
+            name: "Working With Issues".to_string(),
+            commands: vec!["dep".to_string(), "create".to_string()],
+        }];
+        tree.global_flags.push(Flag {
+            value_type: Some(ValueKind::String),
+            ..Flag::new("db", "Database path")
+        });
+        tree
+    }
+
+    fn markdown(tree: &CommandTree) -> String {
+        let mut buf = Vec::new();
+        generate_markdown_docs(tree, &GenerateOptions::default(), &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn html(tree: &CommandTree) -> String {
+        let mut buf = Vec::new();
+        generate_html_docs(tree, &GenerateOptions::default(), &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_markdown_toc_follows_groups() {
+        let output = markdown(&sample_tree());
+
+        let dep = output.find("- [bd dep](#bd-dep)").unwrap();
+        let add = output.find("  - [bd dep add](#bd-dep-add)").unwrap();
+        let create = output.find("- [bd create](#bd-create)").unwrap();
+        let other = output.find("**Other Commands**").unwrap();
+        let version = output.find("- [bd version](#bd-version)").unwrap();
+        assert!(dep < add && add < create && create < other && other < version);
+        // Hidden helpers are left out
+        assert!(!output.contains("bd help"));
+    }
+
+    #[test]
+    fn test_markdown_command_sections() {
+        let output = markdown(&sample_tree());
+
+        assert!(output.contains("### bd create\n"));
+        assert!(output.contains("#### bd dep add\n"));
+        assert!(output.contains("```text\nbd create [title] [flags]\n```"));
+        assert!(output.contains("**Aliases:** `new`"));
+        assert!(output.contains("| `-p`, `--priority` | string | `2` | Priority (0-4 or P0-P4) |"));
+        assert!(output.contains("| `--legacy` |  |  | *Deprecated.* Old behavior |"));
+        assert!(output.contains("```sh\nbd create \"Fix <div> | layout\" -p 1\n```"));
+        assert!(output.contains("## Global flags"));
+        assert!(output.contains("| `--db` | string |  | Database path |"));
+    }
+
+    #[test]
+    fn test_markdown_escapes_text() {
+        let output = markdown(&sample_tree());
+        assert!(output.contains(r"Add a dependency \<from\> \<to\>"));
+    }
+
+    #[test]
+    fn test_html_structure() {
+        let output = html(&sample_tree());
+
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        assert!(output.contains("<section id=\"bd-create\">\n<h3>bd create</h3>"));
+        assert!(output.contains("<h4>bd dep add</h4>"));
+        assert!(output.contains("<li><a href=\"#bd-dep\">bd dep</a> — Manage dependencies\n<ul>"));
+        assert!(output.contains("<p><strong>Aliases:</strong> <code>new</code></p>"));
+        assert!(output.contains(
+            "<tr><td><code>-p</code>, <code>--priority</code></td><td>string</td><td><code>2</code></td>"
+        ));
+        assert_eq!(output.matches("<ul>").count(), output.matches("</ul>").count());
+        assert!(output.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_html_escapes_text() {
+        let output = html(&sample_tree());
+        assert!(output.contains("Add a dependency &lt;from&gt; &lt;to&gt;"));
+        assert!(output.contains("bd create &quot;Fix &lt;div&gt; | layout&quot; -p 1"));
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The ways building a tree or writing its output can fail.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+/// An error from building a command tree or generating output from it.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BdCompleteError {
+    /// The binary to introspect isn't on `$PATH` or can't be executed.
+    BinaryNotFound { binary: String, source: io::Error },
+    /// `<command> --help` exited unsuccessfully.
+    NonZeroExit {
+        command: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+    /// `<command> --help` didn't finish in time and was killed.
+    Timeout { command: String, timeout: Duration },
+    /// The help of the command at `path` (e.g. "bd dep add") isn't help
+    /// output; `line` is the 1-based line it went wrong at.
+    Unparseable {
+        path: String,
+        line: usize,
+        message: String,
+    },
+    /// A command, alias, flag or value name that can't be written safely
+    /// into a completion script. `path` is the command it was found under.
+    UnsafeIdentifier {
+        kind: &'static str,
+        name: String,
+        path: String,
+    },
+    /// Writing the output failed.
+    Write(io::Error),
+    /// No generator is registered under this name.
+    UnknownGenerator(String),
+    /// The tree has no command at this path, e.g. "bd dep frob".
+    UnknownCommand(String),
+    /// An option value that doesn't parse, such as an unknown `--order`.
+    InvalidArgument(String),
+    /// An overrides file that doesn't parse, or names a command or flag the
+    /// tree doesn't have; `line` is the 1-based line of the entry.
+    InvalidOverrides { line: usize, message: String },
+    /// Any other I/O error, such as unreadable captured help.
+    Io(io::Error),
+}
+
+impl BdCompleteError {
+    /// The process exit code for this error, so scripts can tell a missing
+    /// bd from one whose help can't be parsed.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            BdCompleteError::BinaryNotFound { .. } => 3,
+            BdCompleteError::NonZeroExit { .. } => 4,
+            BdCompleteError::Timeout { .. } => 5,
+            BdCompleteError::Unparseable { .. } => 6,
+            BdCompleteError::UnsafeIdentifier { .. } => 7,
+            BdCompleteError::Write(_) => 8,
+            BdCompleteError::InvalidOverrides { .. } => 9,
+            BdCompleteError::UnknownGenerator(_)
+            | BdCompleteError::UnknownCommand(_)
+            | BdCompleteError::InvalidArgument(_)
+            | BdCompleteError::Io(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for BdCompleteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BdCompleteError::BinaryNotFound { binary, source } => {
+                write!(f, "can't run '{binary}': {source}")
+            }
+            BdCompleteError::NonZeroExit {
+                command,
+                status,
+                stderr,
+            } => {
+                write!(f, "`{command}` failed ({status})")?;
+                // Cobra's first stderr line is usually the whole story
+                match stderr.lines().map(str::trim).find(|l| !l.is_empty()) {
+                    Some(line) => write!(f, ": {line}"),
+                    None => Ok(()),
+                }
+            }
+            BdCompleteError::Timeout { command, timeout } => {
+                write!(f, "`{command}` didn't finish within {}s", timeout.as_secs())
+            }
+            BdCompleteError::Unparseable {
+                path,
+                line,
+                message,
+            } => write!(f, "help for `{path}`, line {line}: {message}"),
+            BdCompleteError::UnsafeIdentifier { kind, name, path } => {
+                write!(f, "{kind} name {name:?}")?;
+                if !path.is_empty() {
+                    write!(f, " in `{path}`")?;
+                }
+                write!(f, " can't be written safely into a completion script")
+            }
+            BdCompleteError::Write(e) | BdCompleteError::Io(e) => write!(f, "{e}"),
+            BdCompleteError::UnknownGenerator(name) => write!(f, "no generator named {name:?}"),
+            BdCompleteError::UnknownCommand(path) => write!(f, "no command `{path}`"),
+            BdCompleteError::InvalidArgument(message) => write!(f, "{message}"),
+            BdCompleteError::InvalidOverrides { line, message } => {
+                write!(f, "line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl Error for BdCompleteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BdCompleteError::BinaryNotFound { source, .. } => Some(source),
+            BdCompleteError::Write(e) | BdCompleteError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
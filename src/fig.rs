@@ -0,0 +1,546 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fig completion specs, as read by inshellisense, Warp and Amazon Q.
+//!
+//! The spec is written either as JSON or as a TypeScript module. Only the
+//! TypeScript form can carry generators, since their `postProcess` hook is a
+//! function.
+
+use crate::command_tree::{Command, CommandTree, Flag, PositionalArg, ValueCompletion, ValueKind};
+use crate::error::BdCompleteError;
+use crate::options::GenerateOptions;
+use std::io::{self, Write};
+
+/// A spec value. `Code` is emitted verbatim and only used for TypeScript.
+enum Value {
+    Str(String),
+    Bool(bool),
+    Array(Vec<Value>),
+    Object(Vec<(&'static str, Value)>),
+    Code(String),
+}
+
+impl Value {
+    fn str(s: &str) -> Value {
+        Value::Str(s.to_string())
+    }
+
+    /// A single name as a string, several as an array (`["create", "new"]`).
+    fn names(names: Vec<String>) -> Value {
+        if names.len() == 1 {
+            Value::Str(names.into_iter().next().unwrap())
+        } else {
+            Value::Array(names.into_iter().map(Value::Str).collect())
+        }
+    }
+
+    /// One item as itself, several as an array.
+    fn one_or_many(mut items: Vec<Value>) -> Value {
+        if items.len() == 1 {
+            items.pop().unwrap()
+        } else {
+            Value::Array(items)
+        }
+    }
+
+    fn write(&self, typescript: bool, indent: usize, out: &mut dyn Write) -> io::Result<()> {
+        let pad = "  ".repeat(indent + 1);
+        let close = "  ".repeat(indent);
+        match self {
+            Value::Str(s) => write!(out, "{}", json_string(s)),
+            Value::Bool(b) => write!(out, "{b}"),
+            Value::Code(code) => write!(out, "{code}"),
+            // Short arrays of strings stay on one line
+            Value::Array(items) if items.iter().all(|v| matches!(v, Value::Str(_))) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|v| match v {
+                        Value::Str(s) => json_string(s),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                write!(out, "[{}]", items.join(", "))
+            }
+            Value::Array(items) => {
+                writeln!(out, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(out, "{pad}")?;
+                    item.write(typescript, indent + 1, out)?;
+                    write_separator(typescript, i + 1 == items.len(), out)?;
+                }
+                write!(out, "{close}]")
+            }
+            Value::Object(fields) => {
+                writeln!(out, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if typescript {
+                        write!(out, "{pad}{key}: ")?;
+                    } else {
+                        write!(out, "{pad}\"{key}\": ")?;
+                    }
+                    value.write(typescript, indent + 1, out)?;
+                    write_separator(typescript, i + 1 == fields.len(), out)?;
+                }
+                write!(out, "{close}}}")
+            }
+        }
+    }
+}
+
+/// JSON forbids a trailing comma; TypeScript style wants one.
+fn write_separator(typescript: bool, last: bool, out: &mut dyn Write) -> io::Result<()> {
+    if last && !typescript {
+        writeln!(out)
+    } else {
+        writeln!(out, ",")
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            // Line and paragraph separators end a line in older JavaScript
+            '\u{2028}' => quoted.push_str("\\u2028"),
+            '\u{2029}' => quoted.push_str("\\u2029"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Name of the generator constant in the TypeScript spec.
+const ISSUE_ID_GENERATOR: &str = "issueIds";
+
+/// Builds spec objects from the tree.
+struct SpecBuilder<'a> {
+    opts: &'a GenerateOptions,
+    /// The name bd is invoked as, for generator scripts
+    root: &'a str,
+    /// Attach the issue ID generator to issue arguments
+    issue_ids: bool,
+}
+
+impl SpecBuilder<'_> {
+    fn root(&self, tree: &CommandTree) -> Value {
+        let root = &tree.root;
+        let mut fields = vec![("name", Value::str(&root.name))];
+        if !root.description.is_empty() {
+            fields.push(("description", Value::str(&root.description)));
+        }
+        self.push_subcommands(root, &mut fields);
+
+        // Global flags apply to every subcommand
+        let mut options: Vec<Value> = tree
+            .global_flags
+            .iter()
+            .map(|f| self.option(f, true))
+            .collect();
+        options.extend(
+            root.flags
+                .iter()
+                .filter(|f| !tree.global_flags.iter().any(|g| g.long == f.long))
+                .map(|f| self.option(f, false)),
+        );
+        if !options.is_empty() {
+            fields.push(("options", Value::Array(options)));
+        }
+        self.push_args(&root.args, &mut fields);
+        Value::Object(fields)
+    }
+
+    fn subcommand(&self, name: &str, cmd: &Command) -> Value {
+        let names = std::iter::once(name).chain(cmd.aliases.iter().map(String::as_str));
+        let mut fields = vec![("name", Value::names(names.map(String::from).collect()))];
+        if !cmd.description.is_empty() {
+            fields.push(("description", Value::str(&cmd.description)));
+        }
+        if cmd.deprecated {
+            fields.push(("deprecated", Value::Bool(true)));
+        }
+        // Hidden commands still run and complete their own arguments
+        if !self.opts.offers(cmd) {
+            fields.push(("hidden", Value::Bool(true)));
+        }
+        self.push_subcommands(cmd, &mut fields);
+        if !cmd.flags.is_empty() {
+            let options = cmd.flags.iter().map(|f| self.option(f, false)).collect();
+            fields.push(("options", Value::Array(options)));
+        }
+        self.push_args(&cmd.args, &mut fields);
+        Value::Object(fields)
+    }
+
+    fn push_subcommands(&self, cmd: &Command, fields: &mut Vec<(&'static str, Value)>) {
+        let subcommands: Vec<Value> = cmd
+            .subcommands
+            .iter()
+            .filter(|(_, c)| self.opts.includes(c))
+            .map(|(name, c)| self.subcommand(name, c))
+            .collect();
+        if !subcommands.is_empty() {
+            fields.push(("subcommands", Value::Array(subcommands)));
+        }
+    }
+
+    fn push_args(&self, args: &[PositionalArg], fields: &mut Vec<(&'static str, Value)>) {
+        let args: Vec<Value> = args.iter().map(|a| self.positional(a)).collect();
+        if !args.is_empty() {
+            fields.push(("args", Value::one_or_many(args)));
+        }
+    }
+
+    fn positional(&self, arg: &PositionalArg) -> Value {
+        let mut fields = vec![("name", Value::str(&arg.name))];
+        if !arg.required {
+            fields.push(("isOptional", Value::Bool(true)));
+        }
+        if arg.variadic {
+            fields.push(("isVariadic", Value::Bool(true)));
+        }
+        if self.issue_ids && arg.is_issue_id() {
+            fields.push(("generators", Value::Code(ISSUE_ID_GENERATOR.to_string())));
+        }
+        Value::Object(fields)
+    }
+
+    fn option(&self, flag: &Flag, persistent: bool) -> Value {
+        let mut names = Vec::new();
+        if let Some(s) = flag.short {
+            names.push(format!("-{s}"));
+        }
+        names.push(format!("--{}", flag.long));
+
+        let mut fields = vec![("name", Value::names(names))];
+        if !flag.description.is_empty() {
+            fields.push(("description", Value::str(&flag.description)));
+        }
+        if persistent {
+            fields.push(("isPersistent", Value::Bool(true)));
+        }
+        if flag.value_type == Some(ValueKind::Count) {
+            fields.push(("isRepeatable", Value::Bool(true)));
+        }
+        if flag.deprecated {
+            fields.push(("deprecated", Value::Bool(true)));
+        }
+        if flag.hidden {
+            fields.push(("hidden", Value::Bool(true)));
+        }
+        if flag.takes_value() {
+            fields.push(("args", self.option_arg(flag)));
+        }
+        Value::Object(fields)
+    }
+
+    fn option_arg(&self, flag: &Flag) -> Value {
+        let name = flag
+            .value_type
+            .as_ref()
+            .map(|k| k.type_name())
+            .unwrap_or("value");
+        let mut fields = vec![("name", Value::str(name))];
+        if let Some(default) = &flag.default {
+            fields.push(("default", Value::str(default)));
+        }
+        match flag.value_completion() {
+            ValueCompletion::IssueId if self.issue_ids => {
+                fields.push(("generators", Value::Code(ISSUE_ID_GENERATOR.to_string())))
+            }
+            // A script generator with no postProcess is plain data, so JSON can hold it
+            completion @ (ValueCompletion::Command(_) | ValueCompletion::IssueId) => {
+                let command = completion.command(self.root).unwrap_or_default();
+                let script = vec![Value::str("sh"), Value::str("-c"), Value::Str(command)];
+                fields.push((
+                    "generators",
+                    Value::Object(vec![
+                        ("script", Value::Array(script)),
+                        ("splitOn", Value::str("\n")),
+                    ]),
+                ));
+            }
+            ValueCompletion::Values(values) => fields.push((
+                "suggestions",
+                Value::Array(values.iter().map(|v| Value::str(v)).collect()),
+            )),
+            ValueCompletion::File => fields.push(("template", Value::str("filepaths"))),
+            ValueCompletion::Directory => fields.push(("template", Value::str("folders"))),
+            ValueCompletion::Bool => fields.push((
+                "suggestions",
+                Value::Array(vec![Value::str("true"), Value::str("false")]),
+            )),
+            ValueCompletion::Duration | ValueCompletion::KeyValue | ValueCompletion::Nothing => {}
+        }
+        Value::Object(fields)
+    }
+}
+
+/// Write the Fig spec as JSON.
+pub fn generate_fig_json(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    let tree = opts.ordered(tree);
+    write_fig_json(&tree, opts, out).map_err(BdCompleteError::Write)
+}
+
+fn write_fig_json(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let builder = SpecBuilder {
+        opts,
+        root: &tree.root.name,
+        issue_ids: false,
+    };
+    builder.root(tree).write(false, 0, out)?;
+    writeln!(out)
+}
+
+/// Write the Fig spec as a TypeScript module. With `opts.issue_ids`, issue
+/// arguments get a generator that lists issues with `bd list --json`.
+pub fn generate_fig_typescript(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    let tree = opts.ordered(tree);
+    write_fig_typescript(&tree, opts, out).map_err(BdCompleteError::Write)
+}
+
+fn write_fig_typescript(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(out, "// Fig completion spec for {}", tree.root.name)?;
+    writeln!(out, "// Generated by bd-complete")?;
+    writeln!(out)?;
+
+    if opts.issue_ids {
+        writeln!(out, "const {ISSUE_ID_GENERATOR}: Fig.Generator = {{")?;
+        writeln!(
+            out,
+            "  script: [{}, \"list\", \"--json\"],",
+            json_string(&tree.root.name)
+        )?;
+        writeln!(out, "  postProcess: (output) => {{")?;
+        writeln!(out, "    try {{")?;
+        writeln!(
+            out,
+            "      return JSON.parse(output).map((issue: {{ id: string; title: string }}) => ({{"
+        )?;
+        writeln!(out, "        name: issue.id,")?;
+        writeln!(out, "        description: issue.title,")?;
+        writeln!(out, "      }}));")?;
+        writeln!(out, "    }} catch {{")?;
+        writeln!(out, "      return [];")?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "  }},")?;
+        writeln!(out, "}};")?;
+        writeln!(out)?;
+    }
+
+    let builder = SpecBuilder {
+        opts,
+        root: &tree.root.name,
+        issue_ids: opts.issue_ids,
+    };
+    write!(out, "const completionSpec: Fig.Spec = ")?;
+    builder.root(tree).write(true, 0, out)?;
+    writeln!(out, ";")?;
+    writeln!(out)?;
+    writeln!(out, "export default completionSpec;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tree() -> CommandTree {
+        let mut root = Command::new("bd", "A lightweight issue tracker");
+
+        let mut create = Command::new("create", "Create a \"new\" issue");
+        create.aliases = vec!["new".to_string()];
+        create.args = vec![PositionalArg {
+            name: "title".to_string(),
+            required: false,
+            variadic: false,
+        }];
+        create.flags.push(Flag {
+            short: Some('p'),
+            value_type: Some(ValueKind::String),
+            default: Some("2".to_string()),
+            ..Flag::new("priority", "Priority (0-4)")
+        });
+        create.flags.push(Flag {
+            short: Some('f'),
+            value_type: Some(ValueKind::String),
+            ..Flag::new("file", "Markdown file")
+        });
+
+        let mut show = Command::new("show", "Show issue details");
+        show.args = vec![PositionalArg {
+            name: "id".to_string(),
+            required: true,
+            variadic: true,
+        }];
+
+        let mut help = Command::new("help", "Help about any command");
+        help.hidden = true;
+
+        root.subcommands.insert("create".to_string(), create);
+        root.subcommands.insert("show".to_string(), show);
+        root.subcommands.insert("help".to_string(), help);
+
+        let mut tree = CommandTree::new(root);
+        tree.global_flags.push(Flag::new("json", "Output in JSON format"));
+        tree
+    }
+
+    fn json(tree: &CommandTree) -> String {
+        json_with(tree, &GenerateOptions::default())
+    }
+
+    fn json_with(tree: &CommandTree, opts: &GenerateOptions) -> String {
+        let mut buf = Vec::new();
+        generate_fig_json(tree, opts, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn typescript(tree: &CommandTree, issue_ids: bool) -> String {
+        let mut buf = Vec::new();
+        let opts = GenerateOptions {
+            issue_ids,
+            ..GenerateOptions::default()
+        };
+        generate_fig_typescript(tree, &opts, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_json_maps_commands_and_aliases() {
+        let output = json(&sample_tree());
+
+        assert!(output.starts_with("{\n  \"name\": \"bd\",\n"));
+        assert!(output.contains("\"name\": [\"create\", \"new\"],"));
+        assert!(output.contains("\"description\": \"Create a \\\"new\\\" issue\""));
+        assert!(output.contains("\"name\": \"show\","));
+        // Hidden helpers are left out
+        assert!(!output.contains("\"help\""));
+    }
+
+    #[test]
+    fn test_json_maps_options_and_args() {
+        let output = json(&sample_tree());
+
+        assert!(output.contains(
+            "\"name\": [\"-p\", \"--priority\"],\n          \"description\": \"Priority (0-4)\",\n          \"args\": {\n            \"name\": \"string\",\n            \"default\": \"2\"\n          }"
+        ));
+        assert!(output.contains("\"template\": \"filepaths\""));
+        assert!(output.contains("\"name\": \"--json\",\n      \"description\": \"Output in JSON format\",\n      \"isPersistent\": true\n"));
+        assert!(output.contains("\"name\": \"title\",\n        \"isOptional\": true\n"));
+        assert!(output.contains("\"name\": \"id\",\n        \"isVariadic\": true\n"));
+    }
+
+    #[test]
+    fn test_hidden_groups_and_deprecated_commands() {
+        let mut tree = sample_tree();
+        let show = tree.root.subcommands.get_mut("show").unwrap();
+        show.group = Some("Viewing Issues".to_string());
+        tree.root.subcommands.get_mut("create").unwrap().deprecated = true;
+        let show = "\"name\": \"show\",\n      \"description\": \"Show issue details\",\n";
+
+        let output = json(&tree);
+        assert!(output.contains("\"deprecated\": true,\n      \"hidden\": true,\n"));
+        assert!(output.contains(&format!("{show}      \"args\"")));
+
+        let opts = GenerateOptions::default().hide_group("Viewing Issues");
+        let output = json_with(&tree, &opts);
+        assert!(output.contains(&format!("{show}      \"hidden\": true,\n")));
+    }
+
+    #[test]
+    fn test_json_has_no_trailing_commas() {
+        let output = json(&sample_tree());
+        let lines: Vec<&str> = output.lines().collect();
+        for pair in lines.windows(2) {
+            let closes = pair[1].trim_start().starts_with(['}', ']']);
+            assert!(!(pair[0].ends_with(',') && closes), "{pair:?}");
+        }
+        assert!(!output.contains("generators"));
+    }
+
+    #[test]
+    fn test_typescript_module() {
+        let output = typescript(&sample_tree(), false);
+
+        assert!(output.contains("const completionSpec: Fig.Spec = {\n  name: \"bd\",\n"));
+        assert!(output.contains("name: [\"create\", \"new\"],"));
+        assert!(output
+            .trim_end()
+            .ends_with("export default completionSpec;"));
+        assert!(!output.contains("issueIds"));
+    }
+
+    #[test]
+    fn test_typescript_issue_id_generators() {
+        let output = typescript(&sample_tree(), true);
+
+        assert!(output.contains(
+            "const issueIds: Fig.Generator = {\n  script: [\"bd\", \"list\", \"--json\"],"
+        ));
+        assert!(output
+            .contains("name: \"id\",\n        isVariadic: true,\n        generators: issueIds,\n"));
+        // Only issue arguments get it
+        assert_eq!(output.matches("generators: issueIds").count(), 1);
+    }
+
+    #[test]
+    fn test_override_completions() {
+        let mut tree = sample_tree();
+        let create = tree.root.subcommands.get_mut("create").unwrap();
+        create.flags[0].completion = Some(ValueCompletion::Values(vec![
+            "0".to_string(),
+            "1".to_string(),
+        ]));
+        create.flags[1].completion = Some(ValueCompletion::IssueId);
+
+        let output = json(&tree);
+        assert!(output.contains("\"suggestions\": [\"0\", \"1\"]"));
+        assert!(output.contains(
+            "\"generators\": {\n              \"script\": [\"sh\", \"-c\", \"bd list --json"
+        ));
+        assert!(output.contains("\"splitOn\": \"\\n\""));
+        // The TypeScript spec uses its own generator when it has one
+        let output = typescript(&tree, true);
+        assert_eq!(output.matches("generators: issueIds").count(), 2);
+    }
+
+    #[test]
+    fn test_json_string_escaping() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+}
@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::command_tree::{Command, CommandTree, Flag};
+use crate::command_tree::{Command, CommandTree, Flag, GroupSection, ValueCompletion};
+use crate::error::BdCompleteError;
+use crate::options::{GenerateOptions, Order};
+use crate::quote;
 use std::io::{self, Write};
 
 const LICENSE_HEADER: &str = r#"# Copyright 2006 JT Perry
@@ -30,7 +33,23 @@ const LICENSE_HEADER: &str = r#"# Copyright 2006 JT Perry
 # limitations under the License."#;
 
 /// Generate a complete Fish completion script for the given command tree.
-pub fn generate_fish_completion(tree: &CommandTree, out: &mut dyn Write) -> io::Result<()> {
+pub fn generate_fish_completion(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    let tree = opts.ordered(tree);
+    let tree = &*tree;
+    quote::check_tree(tree)?;
+    quote::check_aliases(&opts.aliases)?;
+    write_fish_script(tree, opts, out).map_err(BdCompleteError::Write)
+}
+
+fn write_fish_script(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
     writeln!(out, "{LICENSE_HEADER}")?;
     writeln!(out)?;
     writeln!(out, "# Fish completion for {}", tree.root.name)?;
@@ -43,35 +62,131 @@ pub fn generate_fish_completion(tree: &CommandTree, out: &mut dyn Write) -> io::
     writeln!(out, "complete -c {cmd} -f")?;
     writeln!(out)?;
 
+    if tree.root.subcommands.values().any(|c| !c.subcommands.is_empty()) {
+        write_at_command_function(tree, out)?;
+    }
+
     // Global flags
     if !tree.global_flags.is_empty() {
         writeln!(out, "# Global flags")?;
-        for flag in &tree.global_flags {
+        for flag in tree.global_flags.iter().filter(|f| !f.hidden) {
             write_flag_completion(cmd, None, flag, out)?;
         }
         writeln!(out)?;
     }
 
+    // Root flags that subcommands don't inherit
+    let root_flags: Vec<&Flag> = tree
+        .root
+        .flags
+        .iter()
+        .filter(|f| !f.hidden && !tree.global_flags.iter().any(|g| g.long == f.long))
+        .collect();
+    if !root_flags.is_empty() {
+        writeln!(out, "# Root flags")?;
+        for flag in root_flags {
+            write_flag_completion(cmd, Some("__fish_use_subcommand"), flag, out)?;
+        }
+        writeln!(out)?;
+    }
+
     // Top-level subcommands
-    generate_subcommand_completions(cmd, &tree.root, &tree.global_flags, out)?;
+    generate_subcommand_completions(cmd, &tree.root, opts.top_level(tree), opts, out)?;
+
+    // Aliases and wrappers complete as what they run; fish splices in the
+    // prefix arguments itself
+    if !opts.aliases.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "# Aliases")?;
+    }
+    for alias in &opts.aliases {
+        let wraps = std::iter::once(cmd.as_str())
+            .chain(alias.prefix.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(out, "complete -c {} -w {}", alias.name, quote::fish(&wraps))?;
+    }
 
     Ok(())
 }
 
-/// Write completions for subcommands of a command, recursively.
+/// Write `__<root>_at_command`, which tells whether the last word before
+/// the cursor that isn't a flag (or a flag's value) is one of its arguments.
+/// Nested subcommands are offered only there, so they stop once positional
+/// arguments have started, as they do in bash.
+fn write_at_command_function(tree: &CommandTree, out: &mut dyn Write) -> io::Result<()> {
+    // Words after these are values, not positionals
+    let mut value_flags: Vec<String> = Vec::new();
+    let mut pending = vec![&tree.root];
+    while let Some(cmd) = pending.pop() {
+        let flags = cmd.flags.iter().chain(if std::ptr::eq(cmd, &tree.root) {
+            &tree.global_flags[..]
+        } else {
+            &[]
+        });
+        for flag in flags.filter(|f| f.takes_value()) {
+            let spellings = std::iter::once(format!("--{}", flag.long))
+                .chain(flag.short.map(|s| format!("-{s}")));
+            for spelling in spellings {
+                if !value_flags.contains(&spelling) {
+                    value_flags.push(spelling);
+                }
+            }
+        }
+        pending.extend(cmd.subcommands.values());
+    }
+
+    let root = &tree.root.name;
+    writeln!(out, "# Whether the last word that isn't a flag or a flag's value is one of")?;
+    writeln!(out, "# the given commands, i.e. no positional argument follows it")?;
+    writeln!(out, "function __{root}_at_command")?;
+    writeln!(out, "    set -l words (commandline -opc)")?;
+    writeln!(out, "    set -e words[1]")?;
+    writeln!(out, "    set -l last")?;
+    writeln!(out, "    set -l skip 0")?;
+    writeln!(out, "    for word in $words")?;
+    writeln!(out, "        if test $skip -eq 1")?;
+    writeln!(out, "            set skip 0")?;
+    writeln!(out, "        else if contains -- $word {}", value_flags.join(" "))?;
+    writeln!(out, "            set skip 1")?;
+    writeln!(out, "        else if not string match -q -- '-*' $word")?;
+    writeln!(out, "            set last $word")?;
+    writeln!(out, "        end")?;
+    writeln!(out, "    end")?;
+    writeln!(out, "    contains -- \"$last\" $argv")?;
+    writeln!(out, "end")?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Write completions for subcommands of a command, recursively. `sections`
+/// are the subcommands by help group; in help order they are offered in
+/// that order rather than sorted.
 fn generate_subcommand_completions(
     cmd: &str,
     parent: &Command,
-    global_flags: &[Flag],
+    sections: Vec<GroupSection>,
+    opts: &GenerateOptions,
     out: &mut dyn Write,
 ) -> io::Result<()> {
-    if parent.subcommands.is_empty() {
+    let sections: Vec<GroupSection> = sections
+        .into_iter()
+        .map(|(group, commands)| {
+            let commands = commands.into_iter().filter(|(_, c)| opts.includes(c));
+            (group, commands.collect::<Vec<_>>())
+        })
+        .filter(|(_, commands)| !commands.is_empty())
+        .collect();
+    if sections.is_empty() {
         return Ok(());
     }
+    let subcommands: Vec<(&String, &Command)> = sections
+        .iter()
+        .flat_map(|(_, commands)| commands.iter().copied())
+        .collect();
 
     // Build the condition for "no subcommand yet" at this level
-    let subcmd_names: Vec<&str> = parent
-        .subcommands
+    let subcmd_names: Vec<&str> = subcommands
         .iter()
         .flat_map(|(name, sub)| {
             let mut names = vec![name.as_str()];
@@ -87,34 +202,47 @@ fn generate_subcommand_completions(
         // Top-level: offer subcommands when no subcommand has been typed yet
         build_no_subcommand_condition(cmd, &subcmd_names)
     } else {
-        // Nested: offer subcommands when parent command is present
-        format!("__fish_seen_subcommand_from {}", parent.name)
+        // Nested: offer subcommands when parent command is present, and no
+        // positional argument has been given to it yet
+        let names = std::iter::once(&parent.name)
+            .chain(&parent.aliases)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("__fish_seen_subcommand_from {}; and __{cmd}_at_command {names}", parent.name)
     };
 
     // Register each subcommand
-    for (name, subcmd) in &parent.subcommands {
-        let desc = escape_description(&subcmd.description);
-        writeln!(
-            out,
-            "complete -c {cmd} -n '{condition}' -a {name} -d '{desc}'"
-        )?;
-
-        // Register aliases as separate completions
-        for alias in &subcmd.aliases {
-            writeln!(
-                out,
-                "complete -c {cmd} -n '{condition}' -a {alias} -d '{desc}'"
-            )?;
+    if opts.order == Order::Help {
+        // Fish sorts candidates unless they're added with -k, and then lists
+        // the last added first, so the commands are written bottom up
+        let grouped = sections.iter().any(|(group, _)| group.is_some());
+        if grouped {
+            writeln!(out, "# Commands by help group, last group first")?;
+        }
+        for (group, commands) in sections.iter().rev() {
+            let heading = grouped.then(|| group.unwrap_or("Other Commands"));
+            if let Some(heading) = heading {
+                writeln!(out, "# {heading}")?;
+            }
+            for &entry in commands.iter().rev() {
+                write_command_candidates(cmd, &condition, entry, heading, true, opts, out)?;
+            }
+        }
+    } else {
+        for &entry in &subcommands {
+            write_command_candidates(cmd, &condition, entry, None, false, opts, out)?;
         }
     }
     writeln!(out)?;
 
     // Now generate flag completions and recurse for each subcommand
-    for (name, subcmd) in &parent.subcommands {
-        if !subcmd.flags.is_empty() {
+    for (name, subcmd) in subcommands {
+        if subcmd.flags.iter().any(|f| !f.hidden) {
             writeln!(out, "# Flags for '{name}'")?;
-            for flag in &subcmd.flags {
-                write_flag_completion(cmd, Some(name), flag, out)?;
+            for flag in subcmd.flags.iter().filter(|f| !f.hidden) {
+                let condition = format!("__fish_seen_subcommand_from {name}");
+                write_flag_completion(cmd, Some(&condition), flag, out)?;
             }
             writeln!(out)?;
         }
@@ -122,13 +250,55 @@ fn generate_subcommand_completions(
         // Recurse into sub-subcommands
         if !subcmd.subcommands.is_empty() {
             writeln!(out, "# Subcommands of '{name}'")?;
-            generate_subcommand_completions(cmd, subcmd, global_flags, out)?;
+            let sections = vec![(None, subcmd.subcommands.iter().collect())];
+            generate_subcommand_completions(cmd, subcmd, sections, opts, out)?;
         }
     }
 
     Ok(())
 }
 
+/// Offer a command and its aliases where `condition` holds, under the help
+/// group `heading`, if any. With `keep_order`, they're added with -k, last
+/// first.
+fn write_command_candidates(
+    cmd: &str,
+    condition: &str,
+    (name, subcmd): (&String, &Command),
+    heading: Option<&str>,
+    keep_order: bool,
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    // Fish has no headings in its menu, so the group leads the description
+    let desc = match heading {
+        Some(heading) if subcmd.description.is_empty() => quote::fish(heading),
+        Some(heading) => quote::fish(&format!("{heading}: {}", subcmd.description)),
+        None => quote::fish(&subcmd.description),
+    };
+    let mut names: Vec<&str> = std::iter::once(name.as_str())
+        .chain(subcmd.aliases.iter().map(String::as_str))
+        .collect();
+    if keep_order {
+        names.reverse();
+    }
+    let keep = if keep_order { " -k" } else { "" };
+    for name in names {
+        // Deprecated commands, and those in hidden groups, are completed
+        // only once fully typed
+        let condition = if opts.offers(subcmd) {
+            condition.to_string()
+        } else {
+            format!("{condition}; and test (commandline -ct) = {name}")
+        };
+        writeln!(
+            out,
+            "complete -c {cmd}{keep} -n '{condition}' -a {name} -d {desc}"
+        )?;
+    }
+    Ok(())
+}
+
 /// Build a condition string that checks no known subcommand has been seen yet.
 fn build_no_subcommand_condition(_cmd: &str, subcmd_names: &[&str]) -> String {
     let names = subcmd_names.join(" ");
@@ -138,16 +308,25 @@ fn build_no_subcommand_condition(_cmd: &str, subcmd_names: &[&str]) -> String {
 /// Write a single `complete` line for a flag.
 fn write_flag_completion(
     cmd: &str,
-    subcmd: Option<&str>,
+    condition: Option<&str>,
     flag: &Flag,
     out: &mut dyn Write,
 ) -> io::Result<()> {
-    let desc = escape_description(&flag.description);
+    let desc = quote::fish(&flag.description);
     let mut parts = vec![format!("complete -c {cmd}")];
 
-    // Condition: if under a subcommand, require it to be seen
-    if let Some(sub) = subcmd {
-        parts.push(format!("-n '__fish_seen_subcommand_from {sub}'"));
+    // Condition for where the flag applies, e.g. under a subcommand
+    let mut conditions: Vec<String> = condition.iter().map(|c| c.to_string()).collect();
+    // Deprecated flags are completed only once fully typed
+    if flag.deprecated {
+        let mut spellings = format!("--{}", flag.long);
+        if let Some(s) = flag.short {
+            spellings.push_str(&format!(" -{s}"));
+        }
+        conditions.push(format!("contains -- (commandline -ct) {spellings}"));
+    }
+    if !conditions.is_empty() {
+        parts.push(format!("-n '{}'", conditions.join("; and ")));
     }
 
     // Long flag
@@ -159,49 +338,62 @@ fn write_flag_completion(
     }
 
     // If the flag takes a value, mark it as requiring an argument
-    if flag.value_type.is_some() {
+    if flag.takes_value() {
         parts.push("-r".to_string());
 
-        // For file-like flags, enable file completion
-        if flag.long.contains("file") || flag.long.contains("path") || flag.long == "db" {
-            parts.push("-F".to_string());
+        let completion = flag.value_completion();
+        match &completion {
+            ValueCompletion::File => parts.push("-F".to_string()),
+            ValueCompletion::Directory => {
+                parts.push("-a '(__fish_complete_directories)'".to_string())
+            }
+            ValueCompletion::Bool => parts.push("-a 'true false'".to_string()),
+            // Unit suffixes once a number has been typed
+            ValueCompletion::Duration => parts.push(
+                "-a '(string match -r -- \"^[0-9]+\\$\" (commandline -ct)){ms,s,m,h}'"
+                    .to_string(),
+            ),
+            // The typed key closed with '='; fish adds no space after it
+            ValueCompletion::KeyValue => parts.push(
+                "-a '(string match -v -- \"*=*\" (commandline -ct))='".to_string(),
+            ),
+            ValueCompletion::Values(values) => {
+                parts.push(format!("-a '{}'", values.join(" ")))
+            }
+            ValueCompletion::Command(_) | ValueCompletion::IssueId => {
+                let command = completion.command(cmd).unwrap_or_default();
+                parts.push(format!("-a {}", quote::fish(&format!("({command})"))))
+            }
+            ValueCompletion::Nothing => {}
         }
     }
 
-    parts.push(format!("-d '{desc}'"));
+    parts.push(format!("-d {desc}"));
 
     writeln!(out, "{}", parts.join(" "))
 }
 
-/// Escape single quotes in description for Fish shell.
-fn escape_description(desc: &str) -> String {
-    desc.replace('\'', "\\'")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::command_tree::CommandGroup;
+    use crate::command_tree::{CommandGroup, ValueKind};
+    use crate::options::ShellAlias;
 
     fn sample_tree() -> CommandTree {
         let mut root = Command::new("bd", "A lightweight issue tracker");
 
         let mut show_cmd = Command::new("show", "Show issue details");
         show_cmd.flags.push(Flag {
-            long: "format".to_string(),
             short: Some('f'),
-            description: "Output format".to_string(),
-            value_type: Some("string".to_string()),
-            default: None,
+            value_type: Some(ValueKind::String),
+            ..Flag::new("format", "Output format")
         });
 
         let mut search_cmd = Command::new("search", "Search issues");
         search_cmd.flags.push(Flag {
-            long: "query".to_string(),
             short: Some('q'),
-            description: "Search query".to_string(),
-            value_type: Some("string".to_string()),
-            default: None,
+            value_type: Some(ValueKind::String),
+            ..Flag::new("query", "Search query")
         });
 
         let set_state_cmd = Command::new("set-state", "Set issue state");
@@ -231,25 +423,16 @@ mod tests {
 
         let global_flags = vec![
             Flag {
-                long: "db".to_string(),
-                short: None,
-                description: "Database path".to_string(),
-                value_type: Some("string".to_string()),
-                default: None,
+                value_type: Some(ValueKind::String),
+                ..Flag::new("db", "Database path")
             },
             Flag {
-                long: "verbose".to_string(),
                 short: Some('v'),
-                description: "Enable verbose output".to_string(),
-                value_type: None,
-                default: None,
+                ..Flag::new("verbose", "Enable verbose output")
             },
             Flag {
-                long: "help".to_string(),
                 short: Some('h'),
-                description: "help for bd".to_string(),
-                value_type: None,
-                default: None,
+                ..Flag::new("help", "help for bd")
             },
         ];
 
@@ -266,7 +449,7 @@ mod tests {
     fn test_generates_license_header() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         assert!(output.contains("Copyright 2006 JT Perry"));
@@ -277,7 +460,7 @@ mod tests {
     fn test_uses_complete_c_bd() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         assert!(output.contains("complete -c bd"));
@@ -287,7 +470,7 @@ mod tests {
     fn test_disables_file_completions() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         assert!(output.contains("complete -c bd -f"));
@@ -297,19 +480,19 @@ mod tests {
     fn test_includes_command_descriptions() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
-        assert!(output.contains("-a show -d 'Show issue details'"));
-        assert!(output.contains("-a search -d 'Search issues'"));
-        assert!(output.contains("-a dep -d 'Dependency management'"));
+        assert!(output.contains("-a show -d 'Working With Issues: Show issue details'"));
+        assert!(output.contains("-a search -d 'Working With Issues: Search issues'"));
+        assert!(output.contains("-a dep -d 'Other Commands: Dependency management'"));
     }
 
     #[test]
     fn test_completes_subcommands() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // dep subcommands should be present
@@ -322,7 +505,7 @@ mod tests {
     fn test_completes_flags_with_descriptions() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // Global flags
@@ -339,7 +522,7 @@ mod tests {
     fn test_flags_requiring_value_have_r() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // --format takes a value, should have -r
@@ -356,7 +539,7 @@ mod tests {
     fn test_file_flags_get_file_completion() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // --db is a file-like flag, should get -F
@@ -368,19 +551,37 @@ mod tests {
     fn test_handles_aliases() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // "create" has alias "new"
-        assert!(output.contains("-a create -d 'Create a new issue'"));
-        assert!(output.contains("-a new -d 'Create a new issue'"));
+        assert!(output.contains("-a create -d 'Working With Issues: Create a new issue'"));
+        assert!(output.contains("-a new -d 'Working With Issues: Create a new issue'"));
+    }
+
+    #[test]
+    fn test_alias_completions() {
+        let tree = sample_tree();
+        let opts = GenerateOptions {
+            aliases: vec![
+                ShellAlias::parse("b").unwrap(),
+                ShellAlias::parse("bdc=create").unwrap(),
+            ],
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &opts, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        assert!(output.contains("complete -c b -w 'bd'\n"));
+        assert!(output.contains("complete -c bdc -w 'bd create'\n"));
     }
 
     #[test]
     fn test_subcommand_conditions() {
         let tree = sample_tree();
         let mut buf = Vec::new();
-        generate_fish_completion(&tree, &mut buf).unwrap();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
 
         // Top-level subcommands should have "not __fish_seen_subcommand_from" condition
@@ -389,4 +590,156 @@ mod tests {
         // dep subcommands should have "__fish_seen_subcommand_from dep" condition
         assert!(output.contains("__fish_seen_subcommand_from dep"));
     }
+
+    #[test]
+    fn test_nested_subcommands_stop_after_positionals() {
+        let tree = sample_tree();
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        assert!(output.contains("function __bd_at_command\n"));
+        let skip = output.lines().find(|l| l.contains("else if contains -- $word")).unwrap();
+        assert!(skip.contains(" --db"), "{skip}");
+        assert!(!skip.contains(" --verbose"), "{skip}");
+        let condition = "-n '__fish_seen_subcommand_from dep; and __bd_at_command dep'";
+        assert!(output.contains(&format!("{condition} -a list")));
+    }
+
+    #[test]
+    fn test_group_order_and_hidden_groups() {
+        let mut tree = sample_tree();
+        for name in ["show", "search", "create"] {
+            tree.root.subcommands.get_mut(name).unwrap().group =
+                Some("Working With Issues".to_string());
+        }
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        // Fish lists later -k candidates first, so help order is written reversed
+        let position = |name: &str| output.find(&format!("' -a {name} ")).unwrap();
+        assert!(position("show") > position("search"));
+        assert!(position("search") > position("create"));
+        assert!(position("create") > position("dep"));
+        // Fish has no menu headings; the group leads each description
+        assert!(output.contains("-a show -d 'Working With Issues: Show issue details'\n"));
+        assert!(output.contains("-a dep -d 'Other Commands: Dependency management'\n"));
+        // Nested commands keep their help order as well
+        let nested =
+            "complete -c bd -k -n '__fish_seen_subcommand_from dep; and __bd_at_command dep' -a";
+        assert!(output.contains(&format!("{nested} list -d 'List dependencies'\n{nested} remove")));
+
+        let opts = GenerateOptions {
+            hidden_groups: vec!["Working With Issues".to_string()],
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &opts, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.contains("; and test (commandline -ct) = show' -a show"));
+        assert!(!output.contains("; and test (commandline -ct) = dep'"));
+    }
+
+    #[test]
+    fn test_value_kind_completions() {
+        let mut tree = sample_tree();
+        tree.global_flags.push(Flag {
+            value_type: Some(ValueKind::Duration),
+            ..Flag::new("timeout", "Lock timeout")
+        });
+        tree.global_flags.push(Flag {
+            value_type: Some(ValueKind::StringToString),
+            ..Flag::new("label", "Labels")
+        });
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        let timeout = output.lines().find(|l| l.contains("-l timeout")).unwrap();
+        assert!(timeout.contains("{ms,s,m,h}"));
+        let label = output.lines().find(|l| l.contains("-l label")).unwrap();
+        assert!(label.contains("(commandline -ct))='"));
+    }
+
+    #[test]
+    fn test_override_completions() {
+        let mut tree = sample_tree();
+        let show = tree.root.subcommands.get_mut("show").unwrap();
+        show.flags[0].completion = Some(ValueCompletion::Values(vec![
+            "json".to_string(),
+            "table".to_string(),
+        ]));
+        tree.root.subcommands.get_mut("search").unwrap().flags[0].completion =
+            Some(ValueCompletion::IssueId);
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        let format = output.lines().find(|l| l.contains("-l format")).unwrap();
+        assert!(format.contains("-r -a 'json table'"));
+        let query = output.lines().find(|l| l.contains("-l query")).unwrap();
+        assert!(query.contains(r#"-a '(bd list --json 2>/dev/null | grep -o \'"id": *"#));
+    }
+
+    #[test]
+    fn test_deprecated_and_hidden() {
+        let mut tree = sample_tree();
+        let mut migrate = Command::new("migrate", "DEPRECATED: use upgrade");
+        migrate.deprecated = true;
+        tree.root.subcommands.insert("migrate".to_string(), migrate);
+        let mut help = Command::new("help", "Help about any command");
+        help.hidden = true;
+        tree.root.subcommands.insert("help".to_string(), help);
+        tree.global_flags[1].deprecated = true;
+
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        assert!(output.contains("; and test (commandline -ct) = migrate' -a migrate"));
+        assert!(output.contains("-n 'contains -- (commandline -ct) --verbose -v' -l verbose"));
+        assert!(!output.contains("-a help"));
+    }
+
+    #[test]
+    fn test_escapes_descriptions() {
+        let mut tree = sample_tree();
+        tree.root.subcommands.get_mut("show").unwrap().description =
+            "Show C:\\issues\\ for $USER's\nteam".to_string();
+        let mut buf = Vec::new();
+        generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        assert!(output.contains(
+            r"-a show -d 'Working With Issues: Show C:\\issues\\ for $USER\'s team'"
+        ));
+    }
+
+    #[test]
+    fn test_rejects_hostile_names() {
+        let mut tree = sample_tree();
+        tree.root.subcommands.get_mut("create").unwrap().aliases = vec!["$(id)".to_string()];
+
+        let mut buf = Vec::new();
+        let err = generate_fish_completion(&tree, &GenerateOptions::default(), &mut buf)
+            .unwrap_err();
+        assert!(matches!(err, BdCompleteError::UnsafeIdentifier { .. }), "{err}");
+        assert!(buf.is_empty(), "nothing is written for a rejected tree");
+    }
+
+    #[test]
+    fn test_rejects_hostile_alias_names() {
+        let opts = GenerateOptions {
+            aliases: vec![ShellAlias {
+                name: "b;id".to_string(),
+                prefix: Vec::new(),
+            }],
+            ..GenerateOptions::default()
+        };
+        let mut buf = Vec::new();
+        let err = generate_fish_completion(&sample_tree(), &opts, &mut buf).unwrap_err();
+        assert!(err.to_string().starts_with("alias name \"b;id\""), "{err}");
+        assert!(buf.is_empty());
+    }
 }
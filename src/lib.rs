@@ -0,0 +1,82 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Completion scripts, specs and docs for bd, built from its `--help` output.
+//!
+//! Build a [`CommandTree`] from a bd binary ([`build_command_tree`]) or from
+//! help text ([`build_command_tree_from`] with a [`HelpSource`]), then hand
+//! it to a generator: directly ([`generate_bash_completion`], ...) or by name
+//! through a [`GeneratorRegistry`]. Both steps fail with a
+//! [`BdCompleteError`].
+//!
+//! ```
+//! use bd_complete::{build_command_tree_from, GenerateOptions, GeneratorRegistry, HelpSource};
+//!
+//! let help = "An issue tracker\n\nUsage:\n  bd [command]\n\n\
+//!             Available Commands:\n  create      Create an issue\n";
+//! let source = HelpSource::Text {
+//!     root: "bd".to_string(),
+//!     help: help.to_string(),
+//! };
+//! let tree = build_command_tree_from(&source)?;
+//!
+//! let mut script = Vec::new();
+//! GeneratorRegistry::default().generate("bash", &tree, &GenerateOptions::default(), &mut script)?;
+//! assert!(String::from_utf8(script).unwrap().contains("create"));
+//! # Ok::<(), bd_complete::BdCompleteError>(())
+//! ```
+//!
+//! Everything is re-exported here; the modules themselves are private.
+
+mod bash;
+mod cache;
+mod carapace;
+mod command_tree;
+mod docs;
+mod error;
+mod fig;
+mod fish;
+mod man;
+mod options;
+mod overrides;
+mod parser;
+mod plugins;
+mod quote;
+mod registry;
+mod toml;
+mod tree_file;
+mod yaml;
+
+pub use bash::{generate_bash_completion, generate_bash_split};
+pub use cache::Cache;
+pub use carapace::generate_carapace_spec;
+pub use command_tree::{
+    Command, CommandGroup, CommandMap, CommandTree, Flag, GroupSection, PositionalArg,
+    ValueCompletion, ValueKind,
+};
+pub use docs::{generate_html_docs, generate_markdown_docs};
+pub use error::BdCompleteError;
+pub use fig::{generate_fig_json, generate_fig_typescript};
+pub use fish::generate_fish_completion;
+pub use man::{generate_man_pages, write_man_page};
+pub use options::{GenerateOptions, Order, ShellAlias};
+pub use overrides::Overrides;
+pub use parser::{
+    build_command_tree, build_command_tree_from, build_command_tree_with_diagnostics,
+    parse_help_output, parse_help_output_with_diagnostics, parse_usage_args, Diagnostic,
+    DiagnosticKind, HelpSource,
+};
+pub use plugins::{add_plugins, add_plugins_with_diagnostics, find_plugins, PLUGIN_GROUP};
+pub use quote::check_tree;
+pub use registry::{GenerateFn, Generator, GeneratorKind, GeneratorRegistry};
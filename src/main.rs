@@ -12,127 +12,365 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub mod bash;
-pub mod command_tree;
-pub mod fish;
-pub mod parser;
-
-use bash::generate_bash_completion;
-use fish::generate_fish_completion;
-use parser::build_command_tree;
+mod cli;
+
+use bd_complete::{
+    add_plugins_with_diagnostics, build_command_tree_with_diagnostics, check_tree, find_plugins,
+    generate_bash_split, generate_man_pages, BdCompleteError, Cache, GenerateOptions,
+    GeneratorKind, GeneratorRegistry, HelpSource, Order, Overrides, ShellAlias,
+};
+use cli::{CommandSpec, Opt, Parsed};
 use std::fs::File;
 use std::io::{self, BufWriter, Write};
 use std::process;
 
-fn print_usage() {
-    eprintln!("Usage: bd-complete generate --shell <SHELL> [--output <FILE>]");
-    eprintln!();
-    eprintln!("Commands:");
-    eprintln!("  generate    Generate a shell completion script");
-    eprintln!();
-    eprintln!("Options:");
-    eprintln!("  --shell <SHELL>    Shell type: bash, fish");
-    eprintln!("  --output <FILE>    Write to file instead of stdout");
-    eprintln!("  --help             Show this help");
+/// Options of every command that reads bd's help.
+const SOURCE_OPTIONS: &[Opt] = &[
+    Opt::value("bd", "BINARY", "bd binary to introspect (default: bd on PATH)"),
+    Opt::value(
+        "help-dir",
+        "DIR",
+        "Read captured help (bd.txt, bd_create.txt, ...) instead of running bd",
+    ),
+    Opt::switch("no-cache", "Run bd for every command instead of using the tree cache"),
+    Opt::switch("refresh", "Rebuild the tree cache for this bd"),
+    Opt::switch("no-plugins", "Don't look for bd-<name> plugins on PATH"),
+    Opt::value(
+        "overrides",
+        "FILE",
+        "Merge this overrides file into the tree (default: ~/.config/bd-complete/overrides.toml if present)",
+    ),
+    Opt::switch("no-overrides", "Ignore the default overrides file"),
+    Opt::switch("include-helpers", "Include cobra's help and completion commands"),
+    Opt::value(
+        "order",
+        "ORDER",
+        "Command and flag order: help (as bd lists them, the default), alphabetical",
+    ),
+    Opt::switch("strict", "Fail if any help line can't be parsed unambiguously"),
+];
+
+/// bd-complete's commands and their options.
+fn app() -> CommandSpec {
+    let output = Opt::value("output", "FILE", "Write to file instead of stdout").short('o');
+    let hide_group = Opt::value(
+        "hide-group",
+        "GROUP",
+        "Don't offer commands in this help group, e.g. \"Additional Commands\" (repeatable)",
+    );
+    let reads_help = |name, about, options: &[Opt]| CommandSpec {
+        name,
+        about,
+        options: [options, SOURCE_OPTIONS].concat(),
+        ..CommandSpec::default()
+    };
+    CommandSpec {
+        name: "bd-complete",
+        about: "Shell completions, docs and man pages for bd, built from its --help output",
+        subcommands: vec![
+            reads_help(
+                "generate",
+                "Generate a shell completion script",
+                &[
+                    Opt::value("shell", "SHELL", "Shell type: bash, fish"),
+                    output,
+                    Opt::value(
+                        "split-dir",
+                        "DIR",
+                        "Write a bash loader plus per-command files loaded on demand",
+                    ),
+                    Opt::value(
+                        "alias",
+                        "NAME[=ARGS]",
+                        "Also complete NAME, an alias or wrapper running bd ARGS (repeatable)",
+                    ),
+                    hide_group,
+                ],
+            ),
+            reads_help(
+                "docs",
+                "Generate a command reference",
+                &[Opt::value("format", "FORMAT", "Docs format: markdown, html"), output],
+            ),
+            reads_help(
+                "man",
+                "Generate man pages (bd.1, bd-<command>.1, ...)",
+                &[Opt::value("output-dir", "DIR", "Directory for man pages")],
+            ),
+            reads_help(
+                "export",
+                "Export a completion spec for other tools",
+                &[
+                    Opt::value("format", "FORMAT", "Spec format: fig, fig-ts, carapace"),
+                    Opt::switch(
+                        "issue-ids",
+                        "Complete issue IDs via `bd list --json` (fig-ts only)",
+                    ),
+                    hide_group,
+                    output,
+                ],
+            ),
+            CommandSpec {
+                name: "cache",
+                about: "Manage the command tree cache",
+                subcommands: vec![CommandSpec {
+                    name: "clear",
+                    about: "Remove cached command trees",
+                    ..CommandSpec::default()
+                }],
+                ..CommandSpec::default()
+            },
+        ],
+        ..CommandSpec::default()
+    }
+}
+
+/// Report a command line that doesn't fit, pointing at the help of
+/// `command`, and exit.
+fn usage_error(command: &[&str], message: &str) -> ! {
+    eprintln!("Error: {message}");
+    let help = std::iter::once("bd-complete")
+        .chain(command.iter().copied())
+        .chain(std::iter::once("--help"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    eprintln!("Run '{help}' for usage.");
+    process::exit(1);
+}
+
+/// Report `e` with what to do about it and exit with the code for its kind.
+fn fail(context: &str, e: &BdCompleteError) -> ! {
+    eprintln!("Error {context}: {e}");
+    let hint = match e {
+        BdCompleteError::BinaryNotFound { .. } => {
+            Some("install bd, or point --bd at it (or --help-dir at captured help)")
+        }
+        BdCompleteError::NonZeroExit { .. } => {
+            Some("check that --bd is a working bd; --help-dir reads captured help instead")
+        }
+        BdCompleteError::Unparseable { .. } => {
+            Some("check that --bd is bd; if it is, its help format has changed")
+        }
+        BdCompleteError::Timeout { .. } => {
+            Some("bd may be waiting on something; --help-dir reads captured help instead")
+        }
+        BdCompleteError::UnsafeIdentifier { .. } => {
+            Some("rename it in the overrides file")
+        }
+        _ => None,
+    };
+    if let Some(hint) = hint {
+        eprintln!("Hint: {hint}");
+    }
+    process::exit(e.exit_code());
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().skip(1).collect();
+    let app = app();
+    if args.is_empty() {
+        eprint!("{}", cli::help(&app, &[]));
+        process::exit(1);
+    }
+    let matches = match cli::parse(&app, &args) {
+        Ok(Parsed::Run(matches)) => matches,
+        Ok(Parsed::Help(path)) => {
+            print!("{}", cli::help(&app, &path));
+            return;
+        }
+        Ok(Parsed::Version) => {
+            println!("bd-complete {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(e) => usage_error(&e.command, &e.message),
+    };
 
-    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
-        print_usage();
-        if args.is_empty() {
+    if matches.command == ["cache", "clear"] {
+        let Some(cache) = Cache::from_env() else {
+            eprintln!("Error: no cache directory (neither XDG_CACHE_HOME nor HOME is set)");
+            process::exit(1);
+        };
+        if let Err(e) = cache.clear() {
+            eprintln!("Error removing '{}': {e}", cache.root.display());
             process::exit(1);
         }
         return;
     }
 
-    if args[0] != "generate" {
-        eprintln!("Error: unknown command '{}'. Expected 'generate'.", args[0]);
-        eprintln!();
-        print_usage();
-        process::exit(1);
-    }
+    let command = matches.command[0];
+    let owned = |long: &str| matches.value(long).map(str::to_string);
+    let shell = owned("shell");
+    let format = owned("format");
+    let output = owned("output");
+    let output_dir = owned("output-dir");
+    let split_dir = owned("split-dir");
+    let binary = owned("bd").unwrap_or_else(|| String::from("bd"));
+    let help_dir = owned("help-dir");
+    let no_cache = matches.flag("no-cache");
+    let refresh = matches.flag("refresh");
+    let overrides = owned("overrides");
+    let no_overrides = matches.flag("no-overrides");
+    let no_plugins = matches.flag("no-plugins");
+    let strict = matches.flag("strict");
 
-    let mut shell: Option<String> = None;
-    let mut output: Option<String> = None;
+    let mut opts = GenerateOptions::default()
+        .include_helpers(matches.flag("include-helpers"))
+        .issue_ids(matches.flag("issue-ids"));
+    for group in matches.values("hide-group") {
+        opts = opts.hide_group(group);
+    }
+    for spec in matches.values("alias") {
+        match ShellAlias::parse(spec) {
+            Ok(alias) => opts = opts.alias(alias),
+            Err(e) => usage_error(&[command], &e.to_string()),
+        }
+    }
+    if let Some(order) = matches.value("order") {
+        match Order::parse(order) {
+            Ok(order) => opts = opts.order(order),
+            Err(e) => usage_error(&[command], &e.to_string()),
+        }
+    }
 
-    let mut i = 1;
-    while i < args.len() {
-        match args[i].as_str() {
-            "--shell" => {
-                i += 1;
-                if i >= args.len() {
-                    eprintln!("Error: --shell requires a value");
-                    process::exit(1);
-                }
-                shell = Some(args[i].clone());
-            }
-            "--output" | "-o" => {
-                i += 1;
-                if i >= args.len() {
-                    eprintln!("Error: --output requires a value");
-                    process::exit(1);
-                }
-                output = Some(args[i].clone());
+    // The generator to run, by command; man pages have their own writer
+    let registry = GeneratorRegistry::default();
+    let target = match (command, shell, format) {
+        ("man", _, _) if output_dir.is_some() => None,
+        ("generate", Some(name), _) | ("docs" | "export", _, Some(name)) => {
+            let (kind, what) = match command {
+                "generate" => (GeneratorKind::Completion, "shell"),
+                "docs" => (GeneratorKind::Docs, "format"),
+                _ => (GeneratorKind::Spec, "format"),
+            };
+            let Some(generator) = registry.get(&name).filter(|g| g.kind == kind) else {
+                let supported = registry.names(kind).join(", ");
+                eprintln!("Error: unsupported {what} '{name}'. Supported: {supported}");
+                process::exit(1);
+            };
+            if split_dir.is_some() && (name != "bash" || output.is_some()) {
+                eprintln!("Error: --split-dir needs --shell bash and can't be combined with --output");
+                process::exit(1);
             }
-            other => {
-                eprintln!("Error: unknown option '{other}'");
+            if opts.issue_ids && name != "fig-ts" {
+                eprintln!("Error: --issue-ids needs --format fig-ts (JSON specs can't hold generators)");
                 process::exit(1);
             }
+            Some(*generator)
         }
-        i += 1;
-    }
-
-    let shell = match shell {
-        Some(s) => s,
-        None => {
-            eprintln!("Error: --shell is required");
-            eprintln!();
-            print_usage();
-            process::exit(1);
+        _ => {
+            let option = match command {
+                "docs" | "export" => "--format",
+                "man" => "--output-dir",
+                _ => "--shell",
+            };
+            usage_error(&[command], &format!("{option} is required"));
         }
     };
 
-    if shell != "bash" && shell != "fish" {
-        eprintln!("Error: unsupported shell '{shell}'. Supported: bash, fish");
+    let source = match help_dir {
+        Some(dir) => HelpSource::Dir {
+            dir: dir.into(),
+            root: HelpSource::Binary(binary).root_name(),
+        },
+        None => HelpSource::Binary(binary),
+    };
+    if no_cache && refresh {
+        eprintln!("Error: --no-cache and --refresh can't be combined");
         process::exit(1);
     }
-
-    let tree = match build_command_tree("bd") {
-        Ok(t) => t,
-        Err(e) => {
-            eprintln!("Error building command tree: {e}");
-            process::exit(1);
+    // Only live runs are cached; --help-dir is already on disk
+    let cache = if no_cache { None } else { Cache::from_env() };
+    let mut diagnostics = Vec::new();
+    let built = match (&source, &cache) {
+        (HelpSource::Binary(binary), Some(cache)) => {
+            cache.build_tree_with_diagnostics(binary, refresh, &mut diagnostics)
         }
+        _ => build_command_tree_with_diagnostics(&source, &mut diagnostics),
     };
-
-    let generate = |writer: &mut dyn Write| -> io::Result<()> {
-        match shell.as_str() {
-            "bash" => generate_bash_completion(&tree, writer),
-            "fish" => generate_fish_completion(&tree, writer),
-            _ => unreachable!(),
+    let mut tree = built.unwrap_or_else(|e| fail("building command tree", &e));
+    // Plugins are only looked for next to a live bd, not in captured help
+    if let (HelpSource::Binary(_), false) = (&source, no_plugins) {
+        if let Some(path) = std::env::var_os("PATH") {
+            let plugins = find_plugins(&tree.root.name, &path);
+            let skipped = match &cache {
+                Some(cache) => cache.add_plugins(&mut tree, &plugins, refresh, &mut diagnostics),
+                None => add_plugins_with_diagnostics(&mut tree, &plugins, &mut diagnostics),
+            };
+            for (name, e) in skipped {
+                eprintln!("Warning: skipping plugin '{}': {e}", plugins[&name].display());
+            }
+        }
+    }
+    // Any help line the parser didn't fully understand may mean bd's help
+    // format has drifted
+    if strict && !diagnostics.is_empty() {
+        for diagnostic in &diagnostics {
+            eprintln!("{diagnostic}");
         }
+        eprintln!(
+            "Error: --strict: {} help line(s) skipped or ambiguous; bd's help format may have changed",
+            diagnostics.len()
+        );
+        process::exit(diagnostics[0].to_error().exit_code());
+    }
+    if overrides.is_some() && no_overrides {
+        eprintln!("Error: --overrides and --no-overrides can't be combined");
+        process::exit(1);
+    }
+    let overrides = match overrides {
+        Some(path) => Some(path.into()),
+        None if no_overrides => None,
+        None => Overrides::default_path().filter(|p| p.exists()),
     };
+    if let Some(path) = overrides {
+        if let Err(e) = Overrides::load(&path).and_then(|o| o.apply(&mut tree)) {
+            fail(&format!("in overrides '{}'", path.display()), &e);
+        }
+    }
+    // Checked up front too, so a rejected tree doesn't leave an empty --output
+    // file or an empty man page directory
+    if command != "docs" {
+        if let Err(e) = check_tree(&tree) {
+            fail("checking command tree", &e);
+        }
+    }
 
-    let result = match output {
+    if let Some(dir) = output_dir {
+        if let Err(e) = generate_man_pages(&tree, &opts, dir.as_ref()) {
+            fail(&format!("writing man pages to '{dir}'"), &e);
+        }
+        return;
+    }
+    if let Some(dir) = split_dir {
+        if let Err(e) = generate_bash_split(&tree, &opts, dir.as_ref()) {
+            fail(&format!("writing split completion to '{dir}'"), &e);
+        }
+        return;
+    }
+
+    let generator = target.expect("only man pages have no generator");
+    let generate = |writer: &mut dyn Write| (generator.generate)(&tree, &opts, writer);
+
+    let destination = match &output {
+        Some(path) => format!("'{path}'"),
+        None => "stdout".to_string(),
+    };
+    let result = match &output {
         Some(path) => {
-            let file = File::create(&path).unwrap_or_else(|e| {
-                eprintln!("Error creating file '{path}': {e}");
-                process::exit(1);
+            let file = File::create(path).unwrap_or_else(|e| {
+                fail(&format!("creating file '{path}'"), &BdCompleteError::Write(e))
             });
             let mut writer = BufWriter::new(file);
-            generate(&mut writer).and_then(|_| writer.flush())
+            generate(&mut writer).and_then(|_| writer.flush().map_err(BdCompleteError::Write))
         }
         None => {
             let stdout = io::stdout();
             let mut writer = BufWriter::new(stdout.lock());
-            generate(&mut writer).and_then(|_| writer.flush())
+            generate(&mut writer).and_then(|_| writer.flush().map_err(BdCompleteError::Write))
         }
     };
 
     if let Err(e) = result {
-        eprintln!("Error generating completion script: {e}");
-        process::exit(1);
+        fail(&format!("writing {} to {destination}", generator.kind.noun()), &e);
     }
 }
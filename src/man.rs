@@ -0,0 +1,414 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Section 1 man pages in roff: `bd.1` for the root and `bd-<command>.1`
+//! (e.g. `bd-dep-add.1`) for every subcommand.
+
+use crate::command_tree::{is_deprecated_description, Command, CommandTree, Flag};
+use crate::error::BdCompleteError;
+use crate::options::GenerateOptions;
+use crate::quote;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Write a man page for every included command into `dir`, returning the
+/// paths written. File names come from command names, so the tree must pass
+/// [`quote::check_tree`].
+pub fn generate_man_pages(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, BdCompleteError> {
+    quote::check_tree(tree)?;
+    let tree = opts.ordered(tree);
+    write_man_pages(&tree, opts, dir).map_err(BdCompleteError::Write)
+}
+
+fn write_man_pages(
+    tree: &CommandTree,
+    opts: &GenerateOptions,
+    dir: &Path,
+) -> io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    let mut pending: Vec<Vec<&str>> = vec![Vec::new()];
+
+    while let Some(path) = pending.pop() {
+        let cmd = lookup(&tree.root, &path).expect("paths come from the tree");
+        for (name, sub) in cmd.subcommands.iter().rev() {
+            if opts.includes(sub) {
+                let mut child = path.clone();
+                child.push(name.as_str());
+                pending.push(child);
+            }
+        }
+
+        let file = dir.join(format!("{}.1", page_name(&tree.root.name, &path)));
+        let mut out = BufWriter::new(File::create(&file)?);
+        write_page(tree, &path, opts, &mut out)?;
+        out.flush()?;
+        written.push(file);
+    }
+    Ok(written)
+}
+
+/// Write the man page of the command at `path` (e.g. `["dep", "add"]`; empty
+/// for the root). A path the tree doesn't have is an `UnknownCommand` error.
+pub fn write_man_page(
+    tree: &CommandTree,
+    path: &[&str],
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> Result<(), BdCompleteError> {
+    if lookup(&tree.root, path).is_none() {
+        let invocation = std::iter::once(tree.root.name.as_str())
+            .chain(path.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+        return Err(BdCompleteError::UnknownCommand(invocation));
+    }
+    let tree = opts.ordered(tree);
+    write_page(&tree, path, opts, out).map_err(BdCompleteError::Write)
+}
+
+/// Write one page from `tree`, already put in `opts.order`.
+fn write_page(
+    tree: &CommandTree,
+    path: &[&str],
+    opts: &GenerateOptions,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let root = &tree.root.name;
+    let cmd = lookup(&tree.root, path).expect("path checked by the caller");
+    let name = page_name(root, path);
+    let invocation = std::iter::once(root.as_str())
+        .chain(path.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    writeln!(
+        out,
+        ".\\\" Generated by bd-complete from `{invocation} --help`; do not edit."
+    )?;
+    writeln!(
+        out,
+        ".TH \"{}\" \"1\" \"\" \"{root}\" \"User Commands\"",
+        name.to_uppercase()
+    )?;
+
+    writeln!(out, ".SH NAME")?;
+    writeln!(out, "{} \\- {}", name, escape(&cmd.description))?;
+
+    writeln!(out, ".SH SYNOPSIS")?;
+    let usage = cmd.usage.as_deref().unwrap_or(&invocation);
+    let rest = usage
+        .strip_prefix(invocation.as_str())
+        .unwrap_or(usage)
+        .trim();
+    writeln!(out, ".B {invocation}")?;
+    if !rest.is_empty() {
+        writeln!(out, "{}", escape(rest))?;
+    }
+
+    writeln!(out, ".SH DESCRIPTION")?;
+    let description = cmd.long_description.as_deref().unwrap_or(&cmd.description);
+    if cmd.deprecated && !is_deprecated_description(description) {
+        writeln!(out, "\\fBDeprecated.\\fR")?;
+        writeln!(out, ".PP")?;
+    }
+    write_paragraphs(description, out)?;
+
+    if !cmd.aliases.is_empty() {
+        writeln!(out, ".SH ALIASES")?;
+        let aliases: Vec<String> = cmd
+            .aliases
+            .iter()
+            .map(|a| format!("\\fB{}\\fR", escape(a)))
+            .collect();
+        writeln!(out, "{}", aliases.join(", "))?;
+    }
+
+    let subcommands: Vec<(&String, &Command)> = cmd
+        .subcommands
+        .iter()
+        .filter(|(_, c)| opts.includes(c))
+        .collect();
+    if !subcommands.is_empty() {
+        writeln!(out, ".SH COMMANDS")?;
+        for (sub_name, sub) in &subcommands {
+            writeln!(out, ".TP")?;
+            writeln!(out, ".B {}", escape(sub_name))?;
+            writeln!(out, "{}", escape(&sub.description))?;
+        }
+    }
+
+    write_options("OPTIONS", &cmd.flags, out)?;
+    if !path.is_empty() {
+        write_options("GLOBAL OPTIONS", &tree.global_flags, out)?;
+    }
+
+    if let Some(examples) = &cmd.examples {
+        writeln!(out, ".SH EXAMPLES")?;
+        writeln!(out, ".PP")?;
+        writeln!(out, ".nf")?;
+        writeln!(out, ".RS 4")?;
+        for line in examples.lines() {
+            writeln!(out, "{}", escape(line))?;
+        }
+        writeln!(out, ".RE")?;
+        writeln!(out, ".fi")?;
+    }
+
+    // The parent, then the children
+    let mut see_also = Vec::new();
+    if let Some((_, parent)) = path.split_last() {
+        see_also.push(page_name(root, parent));
+    }
+    for (sub_name, _) in &subcommands {
+        let mut child = path.to_vec();
+        child.push(sub_name.as_str());
+        see_also.push(page_name(root, &child));
+    }
+    if !see_also.is_empty() {
+        writeln!(out, ".SH SEE ALSO")?;
+        let refs: Vec<String> = see_also.iter().map(|p| format!(".BR {p} (1)")).collect();
+        writeln!(out, "{}", refs.join(",\n"))?;
+    }
+    Ok(())
+}
+
+fn write_options(heading: &str, flags: &[Flag], out: &mut dyn Write) -> io::Result<()> {
+    let flags: Vec<&Flag> = flags.iter().filter(|f| !f.hidden).collect();
+    if flags.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, ".SH {heading}")?;
+    for flag in flags {
+        writeln!(out, ".TP")?;
+        let mut head = String::new();
+        if let Some(s) = flag.short {
+            head.push_str(&format!("\\fB\\-{}\\fR, ", escape(&s.to_string())));
+        }
+        head.push_str(&format!(
+            "\\fB\\-\\-{}\\fR",
+            escape(&flag.long).replace('-', "\\-")
+        ));
+        if flag.takes_value() {
+            let kind = flag
+                .value_type
+                .as_ref()
+                .map(|k| k.type_name())
+                .unwrap_or("value");
+            head.push_str(&format!(" \\fI{}\\fR", escape(kind)));
+        }
+        writeln!(out, "{head}")?;
+
+        let mut description = escape(&flag.description);
+        // Help usually says so already ("Deprecated: use --no-daemon")
+        if flag.deprecated && !is_deprecated_description(&flag.description) {
+            description = format!("\\fBDeprecated.\\fR {description}");
+        }
+        writeln!(out, "{description}")?;
+        // Only when help's description doesn't already mention it
+        if let Some(default) = &flag.default {
+            if !flag.description.contains("(default") {
+                writeln!(out, "Default: \\fB{}\\fR.", escape(default))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Paragraphs separated by `.PP`, keeping help's line breaks with `.br`.
+fn write_paragraphs(text: &str, out: &mut dyn Write) -> io::Result<()> {
+    let paragraphs: Vec<&str> = text
+        .split("\n\n")
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    for (i, paragraph) in paragraphs.iter().enumerate() {
+        if i > 0 {
+            writeln!(out, ".PP")?;
+        }
+        let lines: Vec<String> = paragraph.lines().map(|l| escape(l.trim())).collect();
+        writeln!(out, "{}", lines.join("\n.br\n"))?;
+    }
+    Ok(())
+}
+
+/// The command at `path` below `root`, if there is one.
+fn lookup<'a>(root: &'a Command, path: &[&str]) -> Option<&'a Command> {
+    path.iter().try_fold(root, |cmd, name| cmd.subcommands.get(name))
+}
+
+/// "bd" for the root, "bd-dep-add" for `bd dep add`.
+fn page_name(root: &str, path: &[&str]) -> String {
+    std::iter::once(root)
+        .chain(path.iter().copied())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Escape text for roff: backslashes are written `\e`, and a leading `.` or
+/// `'` would otherwise start a request.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e");
+    if escaped.starts_with(['.', '\'']) {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_tree::ValueKind;
+
+    fn sample_tree() -> CommandTree {
+        let mut root = Command::new("bd", "A lightweight issue tracker");
+        root.usage = Some("bd [command]".to_string());
+
+        let mut create = Command::new("create", "Create a new issue");
+        create.usage = Some("bd create [title] [flags]".to_string());
+        create.aliases = vec!["new".to_string()];
+        create.long_description =
+            Some("Create a new issue.\n\n.dotfiles and C:\\paths are escaped".to_string());
+        create.examples = Some("bd create \"Fix login\" -p 1".to_string());
+        create.flags.push(Flag {
+            short: Some('p'),
+            value_type: Some(ValueKind::Int),
+            default: Some("2".to_string()),
+            ..Flag::new("priority", "Priority (0-4)")
+        });
+        create.flags.push(Flag::new("no-daemon", "Bypass the daemon"));
+
+        let mut dep = Command::new("dep", "Manage dependencies");
+        let mut add = Command::new("add", "Add a dependency");
+        add.usage = Some("bd dep add <issue> <depends-on> [flags]".to_string());
+        dep.subcommands.insert("add".to_string(), add);
+
+        let mut help = Command::new("help", "Help about any command");
+        help.hidden = true;
+
+        root.subcommands.insert("create".to_string(), create);
+        root.subcommands.insert("dep".to_string(), dep);
+        root.subcommands.insert("help".to_string(), help);
+
+        let mut tree = CommandTree::new(root);
+        tree.global_flags.push(Flag {
+            value_type: Some(ValueKind::String),
+            ..Flag::new("db", "Database path")
+        });
+        tree
+    }
+
+    fn page(tree: &CommandTree, path: &[&str]) -> String {
+        let mut buf = Vec::new();
+        write_man_page(tree, path, &GenerateOptions::default(), &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_root_page() {
+        let output = page(&sample_tree(), &[]);
+
+        assert!(output.contains(".TH \"BD\" \"1\""));
+        assert!(output.contains(".SH NAME\nbd \\- A lightweight issue tracker\n"));
+        assert!(output.contains(".SH SYNOPSIS\n.B bd\n[command]\n"));
+        assert!(output.contains(".SH COMMANDS\n.TP\n.B create\nCreate a new issue\n"));
+        assert!(output.contains(".SH SEE ALSO\n.BR bd-create (1),\n.BR bd-dep (1)\n"));
+        // Hidden helpers and global options stay off the root page
+        assert!(!output.contains(".B help"));
+        assert!(!output.contains("GLOBAL OPTIONS"));
+    }
+
+    #[test]
+    fn test_command_page() {
+        let output = page(&sample_tree(), &["create"]);
+
+        assert!(output.contains(".TH \"BD-CREATE\" \"1\""));
+        assert!(output.contains(".SH SYNOPSIS\n.B bd create\n[title] [flags]\n"));
+        assert!(output.contains(".SH ALIASES\n\\fBnew\\fR\n"));
+        assert!(output.contains(
+            ".TP\n\\fB\\-p\\fR, \\fB\\-\\-priority\\fR \\fIint\\fR\nPriority (0-4)\nDefault: \\fB2\\fR.\n"
+        ));
+        assert!(output.contains(".TP\n\\fB\\-\\-no\\-daemon\\fR\nBypass the daemon\n"));
+        assert!(output.contains(".SH GLOBAL OPTIONS\n.TP\n\\fB\\-\\-db\\fR \\fIstring\\fR\n"));
+        assert!(output
+            .contains(".SH EXAMPLES\n.PP\n.nf\n.RS 4\nbd create \"Fix login\" -p 1\n.RE\n.fi\n"));
+        assert!(output.contains(".SH SEE ALSO\n.BR bd (1)\n"));
+    }
+
+    #[test]
+    fn test_nested_page_links_parent_and_children() {
+        let tree = sample_tree();
+        assert!(page(&tree, &["dep"]).contains(".SH SEE ALSO\n.BR bd (1),\n.BR bd-dep-add (1)\n"));
+
+        let add = page(&tree, &["dep", "add"]);
+        assert!(add.contains(".SH NAME\nbd-dep-add \\- Add a dependency\n"));
+        assert!(add.contains(".B bd dep add\n<issue> <depends-on> [flags]\n"));
+        assert!(add.contains(".SH SEE ALSO\n.BR bd-dep (1)\n"));
+    }
+
+    #[test]
+    fn test_unknown_command_is_an_error() {
+        let opts = GenerateOptions::default();
+        let mut buf = Vec::new();
+        let err = write_man_page(&sample_tree(), &["dep", "frob"], &opts, &mut buf).unwrap_err();
+        assert!(
+            matches!(&err, BdCompleteError::UnknownCommand(path) if path == "bd dep frob"),
+            "{err:?}"
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_escapes_roff() {
+        let output = page(&sample_tree(), &["create"]);
+        assert!(output.contains("\n.PP\n\\&.dotfiles and C:\\epaths are escaped\n"));
+    }
+
+    #[test]
+    fn test_writes_one_file_per_command() {
+        let dir = std::env::temp_dir().join(format!("bd-complete-man-{}", std::process::id()));
+        let written =
+            generate_man_pages(&sample_tree(), &GenerateOptions::default(), &dir).unwrap();
+
+        let names: Vec<String> = written
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["bd.1", "bd-create.1", "bd-dep.1", "bd-dep-add.1"]);
+        assert!(std::fs::read_to_string(dir.join("bd-dep-add.1"))
+            .unwrap()
+            .starts_with(".\\\" Generated by bd-complete from `bd dep add --help`"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rejects_names_that_leave_the_directory() {
+        let mut tree = sample_tree();
+        let evil = Command::new("../evil", "Escapes the output directory");
+        tree.root.subcommands.insert("../evil".to_string(), evil);
+        let dir = std::env::temp_dir().join(format!("bd-complete-man-evil-{}", std::process::id()));
+
+        let err = generate_man_pages(&tree, &GenerateOptions::default(), &dir).unwrap_err();
+        assert!(
+            matches!(&err, BdCompleteError::UnsafeIdentifier { name, .. } if name == "../evil"),
+            "{err:?}"
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::command_tree::{Command, CommandTree, Flag, GroupSection};
+use crate::error::BdCompleteError;
+use crate::quote;
+use std::borrow::Cow;
+
+/// Options shared by all generators.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct GenerateOptions {
+    /// Offer cobra's `help` and `completion` helper commands
+    pub include_helpers: bool,
+    /// Extra command names the completion scripts register (`alias b=bd`)
+    pub aliases: Vec<ShellAlias>,
+    /// Help groups whose commands aren't offered, e.g. "Additional Commands".
+    /// They still complete once typed in full.
+    pub hidden_groups: Vec<String>,
+    /// Order commands and flags are written in
+    pub order: Order,
+    /// Complete issue IDs by running `bd list --json` (Fig TypeScript specs only)
+    pub issue_ids: bool,
+}
+
+/// Order of commands and flags in generated output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Order {
+    /// As bd's help lists them, top-level commands group by group
+    #[default]
+    Help,
+    /// Sorted by name
+    Alphabetical,
+}
+
+impl Order {
+    /// Parse the value of `--order`.
+    pub fn parse(s: &str) -> Result<Order, BdCompleteError> {
+        match s {
+            "help" => Ok(Order::Help),
+            "alphabetical" => Ok(Order::Alphabetical),
+            _ => Err(BdCompleteError::InvalidArgument(format!(
+                "unknown order {s:?} (expected help or alphabetical)"
+            ))),
+        }
+    }
+}
+
+/// A shell alias or wrapper for the root command, completed like it.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ShellAlias {
+    /// The name typed instead of bd, e.g. "bdw"
+    pub name: String,
+    /// Arguments the alias adds after bd, e.g. ["--db", "work.db"]
+    pub prefix: Vec<String>,
+}
+
+impl ShellAlias {
+    /// Parse `name` or `name=prefix args` as given to `--alias`. The prefix
+    /// is split on whitespace.
+    pub fn parse(spec: &str) -> Result<ShellAlias, BdCompleteError> {
+        let (name, prefix) = spec.split_once('=').unwrap_or((spec, ""));
+        if !quote::is_safe_word(name) {
+            return Err(BdCompleteError::InvalidArgument(format!(
+                "alias name {name:?} can't be written safely into a completion script"
+            )));
+        }
+        Ok(ShellAlias {
+            name: name.to_string(),
+            prefix: prefix.split_whitespace().map(String::from).collect(),
+        })
+    }
+}
+
+impl GenerateOptions {
+    /// Offer cobra's `help` and `completion` helper commands as well.
+    pub fn include_helpers(mut self, include: bool) -> Self {
+        self.include_helpers = include;
+        self
+    }
+
+    /// Also register completion for `alias`.
+    pub fn alias(mut self, alias: ShellAlias) -> Self {
+        self.aliases.push(alias);
+        self
+    }
+
+    /// Stop offering the commands of the help group `group`.
+    pub fn hide_group(mut self, group: impl Into<String>) -> Self {
+        self.hidden_groups.push(group.into());
+        self
+    }
+
+    /// Write commands and flags in `order`.
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Complete issue IDs by running `bd list --json` (Fig TypeScript specs only).
+    pub fn issue_ids(mut self, issue_ids: bool) -> Self {
+        self.issue_ids = issue_ids;
+        self
+    }
+
+    /// Whether a command is known to the generated script at all.
+    /// Deprecated commands are known (so they complete once typed) but not offered.
+    pub fn includes(&self, cmd: &Command) -> bool {
+        !cmd.hidden || (self.include_helpers && cmd.is_helper())
+    }
+
+    /// Whether a command is offered as a completion candidate.
+    pub fn offers(&self, cmd: &Command) -> bool {
+        self.includes(cmd) && !cmd.deprecated && !self.hides_group(cmd)
+    }
+
+    /// Whether the command is in one of the hidden groups.
+    fn hides_group(&self, cmd: &Command) -> bool {
+        cmd.group.as_ref().is_some_and(|g| self.hidden_groups.contains(g))
+    }
+
+    /// The tree to generate from: `tree` itself in help order, or a copy
+    /// with every command's subcommands and flags sorted.
+    pub fn ordered<'a>(&self, tree: &'a CommandTree) -> Cow<'a, CommandTree> {
+        match self.order {
+            Order::Help => Cow::Borrowed(tree),
+            Order::Alphabetical => {
+                let mut tree = tree.clone();
+                tree.sort_by_name();
+                Cow::Owned(tree)
+            }
+        }
+    }
+
+    /// Top-level commands as completions offer them: by help group in help
+    /// order, or all together in the tree's order.
+    pub fn top_level<'a>(&self, tree: &'a CommandTree) -> Vec<GroupSection<'a>> {
+        match self.order {
+            Order::Help => tree.grouped_subcommands(),
+            Order::Alphabetical => vec![(None, tree.root.subcommands.iter().collect())],
+        }
+    }
+
+    /// Whether a flag is offered as a completion candidate.
+    pub fn offers_flag(&self, flag: &Flag) -> bool {
+        !flag.hidden && !flag.deprecated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alias() {
+        assert_eq!(
+            ShellAlias::parse("b").unwrap(),
+            ShellAlias {
+                name: "b".to_string(),
+                prefix: Vec::new(),
+            }
+        );
+        let alias = ShellAlias::parse("bdw=--db  work.db create").unwrap();
+        assert_eq!(alias.name, "bdw");
+        assert_eq!(alias.prefix, ["--db", "work.db", "create"]);
+        assert!(ShellAlias::parse("=create").is_err());
+        let err = ShellAlias::parse("b;rm=create").unwrap_err();
+        assert!(matches!(err, BdCompleteError::InvalidArgument(_)));
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_parse_order() {
+        assert_eq!(Order::parse("help").unwrap(), Order::Help);
+        assert_eq!(Order::parse("alphabetical").unwrap(), Order::Alphabetical);
+        let err = Order::parse("random").unwrap_err();
+        assert!(matches!(err, BdCompleteError::InvalidArgument(_)));
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_builder() {
+        let opts = GenerateOptions::default()
+            .include_helpers(true)
+            .alias(ShellAlias::parse("b").unwrap())
+            .hide_group("Additional Commands")
+            .hide_group("Advanced")
+            .order(Order::Alphabetical)
+            .issue_ids(true);
+        assert!(opts.include_helpers && opts.issue_ids);
+        assert_eq!(opts.aliases[0].name, "b");
+        assert_eq!(opts.hidden_groups, ["Additional Commands", "Advanced"]);
+        assert_eq!(opts.order, Order::Alphabetical);
+    }
+}
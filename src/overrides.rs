@@ -0,0 +1,677 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hand-written corrections merged into the tree before any generator runs.
+//!
+//! Help text can't say that `--parent` takes an epic ID or that `--due`
+//! takes a date, so an overrides file (normally
+//! `~/.config/bd-complete/overrides.toml`) can patch what was parsed:
+//!
+//! ```toml
+//! [command.create]
+//! aliases = ["add"]
+//!
+//! [command.create.flag.parent]
+//! complete = "issue-id"          # or "file", "dir", "none"
+//!
+//! [command.create.flag.from-template]
+//! command = "ls ~/.beads/templates"
+//!
+//! [command."dep add"]
+//! hidden = true
+//!
+//! [command.template]
+//! add = true
+//! description = "Manage issue templates"
+//!
+//! [global.flag.sandbox]
+//! values = ["on", "off"]
+//! ```
+//!
+//! Commands can also be renamed (`rename = "new-name"`). Paths are always
+//! written as bd prints them, since renames are applied after everything else.
+
+use crate::command_tree::{Command, CommandGroup, CommandTree, Flag, ValueCompletion, ValueKind};
+use crate::error::BdCompleteError;
+use crate::toml::{parse_toml, Toml};
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+/// Everything an overrides file asks for, in file order, to merge into a
+/// tree with [`Overrides::apply`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Overrides {
+    pub(crate) commands: Vec<CommandOverride>,
+    pub(crate) global_flags: Vec<FlagOverride>,
+}
+
+/// A `[command.<path>]` table and the flag tables under it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CommandOverride {
+    /// Path below the root, e.g. ["dep", "add"]; empty for the root itself
+    pub path: Vec<String>,
+    /// Create the command if bd doesn't have it
+    pub add: bool,
+    pub hidden: Option<bool>,
+    pub description: Option<String>,
+    /// Added to the aliases bd reports
+    pub aliases: Vec<String>,
+    /// Group to list a top-level command under, created if needed
+    pub group: Option<String>,
+    pub rename: Option<String>,
+    pub flags: Vec<FlagOverride>,
+    /// Line of the table header
+    pub line: usize,
+}
+
+/// A `[command.<path>.flag.<name>]` or `[global.flag.<name>]` table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FlagOverride {
+    pub long: String,
+    /// Create the flag if bd doesn't have it
+    pub add: bool,
+    pub hidden: Option<bool>,
+    pub description: Option<String>,
+    pub short: Option<char>,
+    /// Value type; a flag added without one is boolean
+    pub value_type: Option<ValueKind>,
+    pub completion: Option<ValueCompletion>,
+    /// Line of the table header
+    pub line: usize,
+}
+
+impl Overrides {
+    /// The user's overrides file: `$XDG_CONFIG_HOME/bd-complete/overrides.toml`,
+    /// falling back to `~/.config`. None if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("bd-complete").join("overrides.toml"))
+    }
+
+    pub fn load(path: &Path) -> Result<Overrides, BdCompleteError> {
+        let text = std::fs::read_to_string(path).map_err(BdCompleteError::Io)?;
+        Overrides::parse(&text)
+    }
+
+    /// Read an overrides file's text. Errors are `InvalidOverrides`.
+    pub fn parse(text: &str) -> Result<Overrides, BdCompleteError> {
+        let mut overrides = Overrides::default();
+        for table in parse_toml(text)? {
+            let path: Vec<&str> = table.path.iter().map(String::as_str).collect();
+            match path.as_slice() {
+                ["command", cmd] => {
+                    let command = overrides.command(cmd, table.line);
+                    command.line = table.line;
+                    for (key, value, line) in table.entries {
+                        match key.as_str() {
+                            "add" => command.add = boolean(&key, value, line)?,
+                            "hidden" => command.hidden = Some(boolean(&key, value, line)?),
+                            "description" => command.description = Some(string(&key, value, line)?),
+                            "aliases" => command.aliases = strings(&key, value, line)?,
+                            "group" => command.group = Some(string(&key, value, line)?),
+                            "rename" => command.rename = Some(string(&key, value, line)?),
+                            _ => return Err(invalid(line, format!("unknown command key {key:?}"))),
+                        }
+                    }
+                }
+                ["command", cmd, "flag", long] => {
+                    let flag = FlagOverride::parse(long, table.line, table.entries)?;
+                    overrides.command(cmd, table.line).flags.push(flag);
+                }
+                ["global", "flag", long] => {
+                    let flag = FlagOverride::parse(long, table.line, table.entries)?;
+                    overrides.global_flags.push(flag);
+                }
+                _ => {
+                    return Err(invalid(
+                        table.line,
+                        format!(
+                            "unknown table [{}]; expected [command.<path>], \
+                             [command.<path>.flag.<name>] or [global.flag.<name>]",
+                            table.path.join(".")
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// The override for the command at `path` ("dep add"), added if new.
+    fn command(&mut self, path: &str, line: usize) -> &mut CommandOverride {
+        let path: Vec<String> = path.split_whitespace().map(String::from).collect();
+        match self.commands.iter().position(|c| c.path == path) {
+            Some(i) => &mut self.commands[i],
+            None => {
+                self.commands.push(CommandOverride {
+                    path,
+                    line,
+                    ..CommandOverride::default()
+                });
+                self.commands.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Merge the overrides into `tree`. Fails on the first one that names a
+    /// command or flag the tree doesn't have (unless it sets `add`), with an
+    /// `InvalidOverrides` error.
+    pub fn apply(&self, tree: &mut CommandTree) -> Result<(), BdCompleteError> {
+        for flag in &self.global_flags {
+            flag.apply(&mut tree.global_flags, "global flags")?;
+            // The root lists persistent flags among its own too; its copy
+            // has to change with them
+            if tree.root.flags.iter().any(|f| f.long == flag.long) {
+                flag.apply(&mut tree.root.flags, &tree.root.name)?;
+            }
+        }
+        for command in &self.commands {
+            command.apply(tree)?;
+        }
+        // Deepest first, so each path still names the commands bd printed
+        let mut renames: Vec<&CommandOverride> = self
+            .commands
+            .iter()
+            .filter(|c| c.rename.is_some())
+            .collect();
+        renames.sort_by_key(|c| Reverse(c.path.len()));
+        for command in renames {
+            command.apply_rename(tree)?;
+        }
+        Ok(())
+    }
+}
+
+impl CommandOverride {
+    fn apply(&self, tree: &mut CommandTree) -> Result<(), BdCompleteError> {
+        let display = command_path(&tree.root.name, &self.path);
+        if self.group.is_some() && self.path.len() != 1 {
+            return Err(invalid(self.line, "only top-level commands have a group"));
+        }
+        let cmd = match self.path.split_last() {
+            None => &mut tree.root,
+            Some((name, parent)) => {
+                let parent = find_command(&mut tree.root, parent)
+                    .ok_or_else(|| invalid(self.line, format!("no command `{display}`")))?;
+                if !parent.subcommands.contains_key(name) {
+                    if !self.add {
+                        return Err(invalid(
+                            self.line,
+                            format!("no command `{display}` (set add = true to add it)"),
+                        ));
+                    }
+                    parent
+                        .subcommands
+                        .insert(name.clone(), Command::new(name.as_str(), ""));
+                }
+                parent.subcommands.get_mut(name).unwrap()
+            }
+        };
+
+        if let Some(hidden) = self.hidden {
+            cmd.hidden = hidden;
+        }
+        if let Some(description) = &self.description {
+            cmd.description = description.clone();
+        }
+        for alias in &self.aliases {
+            if !cmd.aliases.contains(alias) {
+                cmd.aliases.push(alias.clone());
+            }
+        }
+        for flag in &self.flags {
+            flag.apply(&mut cmd.flags, &display)?;
+        }
+
+        if let Some(group) = &self.group {
+            cmd.group = Some(group.clone());
+            let name = &self.path[0];
+            for g in &mut tree.groups {
+                g.commands.retain(|c| c != name);
+            }
+            match tree.groups.iter_mut().find(|g| g.name == *group) {
+                Some(g) => g.commands.push(name.clone()),
+                None => tree.groups.push(CommandGroup {
+                    name: group.clone(),
+                    commands: vec![name.clone()],
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_rename(&self, tree: &mut CommandTree) -> Result<(), BdCompleteError> {
+        let Some(new) = &self.rename else {
+            return Ok(());
+        };
+        let display = command_path(&tree.root.name, &self.path);
+        let Some((name, parent)) = self.path.split_last() else {
+            return Err(invalid(self.line, "the root command can't be renamed"));
+        };
+        // apply() already found it
+        let parent = find_command(&mut tree.root, parent).unwrap();
+        if parent.subcommands.contains_key(new) {
+            return Err(invalid(
+                self.line,
+                format!("can't rename `{display}` to {new:?}: that command already exists"),
+            ));
+        }
+        // Keeps its place in help order
+        let cmd = parent.subcommands.rename(name, new).unwrap();
+        cmd.name = new.clone();
+        cmd.aliases.retain(|a| a != new);
+
+        if self.path.len() == 1 {
+            for command in tree.groups.iter_mut().flat_map(|g| &mut g.commands) {
+                if command == name {
+                    *command = new.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FlagOverride {
+    fn parse(
+        long: &str,
+        line: usize,
+        entries: Vec<(String, Toml, usize)>,
+    ) -> Result<Self, BdCompleteError> {
+        let mut flag = FlagOverride {
+            long: long.to_string(),
+            line,
+            ..FlagOverride::default()
+        };
+        for (key, value, line) in entries {
+            let completion = match key.as_str() {
+                "add" => {
+                    flag.add = boolean(&key, value, line)?;
+                    continue;
+                }
+                "hidden" => {
+                    flag.hidden = Some(boolean(&key, value, line)?);
+                    continue;
+                }
+                "description" => {
+                    flag.description = Some(string(&key, value, line)?);
+                    continue;
+                }
+                "short" => {
+                    let short = string(&key, value, line)?;
+                    let mut chars = short.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => flag.short = Some(c),
+                        _ => return Err(invalid(line, "short must be a single character")),
+                    }
+                    continue;
+                }
+                "type" => {
+                    flag.value_type = Some(ValueKind::from_type_name(&string(&key, value, line)?));
+                    continue;
+                }
+                "complete" => match string(&key, value, line)?.as_str() {
+                    "file" => ValueCompletion::File,
+                    "dir" => ValueCompletion::Directory,
+                    "issue-id" => ValueCompletion::IssueId,
+                    "none" => ValueCompletion::Nothing,
+                    other => {
+                        return Err(invalid(
+                            line,
+                            format!(
+                            "unknown completion {other:?}; expected file, dir, issue-id or none"
+                        ),
+                        ))
+                    }
+                },
+                "values" => ValueCompletion::Values(strings(&key, value, line)?),
+                "command" => ValueCompletion::Command(string(&key, value, line)?),
+                _ => return Err(invalid(line, format!("unknown flag key {key:?}"))),
+            };
+            if flag.completion.is_some() {
+                return Err(invalid(
+                    line,
+                    "set only one of complete, values and command",
+                ));
+            }
+            flag.completion = Some(completion);
+        }
+        Ok(flag)
+    }
+
+    /// Apply to the flag of the same name in `flags`; `location` names the
+    /// command for errors.
+    fn apply(&self, flags: &mut Vec<Flag>, location: &str) -> Result<(), BdCompleteError> {
+        let flag = match flags.iter().position(|f| f.long == self.long) {
+            Some(i) => &mut flags[i],
+            None if self.add => {
+                flags.push(Flag::new(self.long.clone(), ""));
+                flags.last_mut().unwrap()
+            }
+            None => {
+                return Err(invalid(
+                    self.line,
+                    format!(
+                        "no flag --{} in {location} (set add = true to add it)",
+                        self.long
+                    ),
+                ))
+            }
+        };
+
+        if let Some(hidden) = self.hidden {
+            flag.hidden = hidden;
+        }
+        if let Some(description) = &self.description {
+            flag.description = description.clone();
+        }
+        if self.short.is_some() {
+            flag.short = self.short;
+        }
+        if self.value_type.is_some() {
+            flag.value_type = self.value_type.clone();
+        }
+        if let Some(completion) = &self.completion {
+            if !flag.takes_value() {
+                return Err(invalid(
+                    self.line,
+                    format!("--{} takes no value; set a type to complete one", self.long),
+                ));
+            }
+            flag.completion = Some(completion.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Follow `path` down from `cmd`.
+fn find_command<'a>(cmd: &'a mut Command, path: &[String]) -> Option<&'a mut Command> {
+    path.iter()
+        .try_fold(cmd, |cmd, name| cmd.subcommands.get_mut(name))
+}
+
+/// "bd dep add", for messages.
+fn command_path(root: &str, path: &[String]) -> String {
+    std::iter::once(root)
+        .chain(path.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn invalid(line: usize, message: impl std::fmt::Display) -> BdCompleteError {
+    BdCompleteError::InvalidOverrides {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn string(key: &str, value: Toml, line: usize) -> Result<String, BdCompleteError> {
+    match value {
+        Toml::Str(s) => Ok(s),
+        _ => Err(invalid(line, format!("{key} must be a string"))),
+    }
+}
+
+fn boolean(key: &str, value: Toml, line: usize) -> Result<bool, BdCompleteError> {
+    match value {
+        Toml::Bool(b) => Ok(b),
+        _ => Err(invalid(line, format!("{key} must be true or false"))),
+    }
+}
+
+fn strings(key: &str, value: Toml, line: usize) -> Result<Vec<String>, BdCompleteError> {
+    match value {
+        Toml::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                Toml::Str(s) => Ok(s),
+                _ => Err(invalid(line, format!("{key} must be an array of strings"))),
+            })
+            .collect(),
+        _ => Err(invalid(line, format!("{key} must be an array of strings"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bash::generate_bash_completion;
+    use crate::options::GenerateOptions;
+
+    fn flag(long: &str, kind: Option<ValueKind>) -> Flag {
+        Flag {
+            value_type: kind,
+            ..Flag::new(long, "")
+        }
+    }
+
+    fn sample_tree() -> CommandTree {
+        let mut create = Command::new("create", "Create a new issue");
+        create.aliases.push("new".to_string());
+        create.group = Some("Working With Issues".to_string());
+        create.flags.push(flag("parent", Some(ValueKind::String)));
+        create.flags.push(flag("due", Some(ValueKind::String)));
+        create.flags.push(flag("json", None));
+
+        let mut dep = Command::new("dep", "Manage dependencies");
+        dep.subcommands
+            .insert("add".to_string(), Command::new("add", "Add a dependency"));
+
+        let mut root = Command::new("bd", "A lightweight issue tracker");
+        root.subcommands.insert("create".to_string(), create);
+        root.subcommands.insert("dep".to_string(), dep);
+        let mut tree = CommandTree::new(root);
+        tree.global_flags.push(flag("db", Some(ValueKind::String)));
+        tree.groups.push(CommandGroup {
+            name: "Working With Issues".to_string(),
+            commands: vec!["create".to_string()],
+        });
+        tree
+    }
+
+    fn apply(text: &str) -> Result<CommandTree, BdCompleteError> {
+        let mut tree = sample_tree();
+        Overrides::parse(text)?.apply(&mut tree)?;
+        Ok(tree)
+    }
+
+    #[test]
+    fn test_global_flags_also_change_the_root_copy() {
+        let mut tree = sample_tree();
+        let mut verbose = flag("verbose", None);
+        verbose.short = Some('v');
+        tree.global_flags.push(verbose.clone());
+        tree.root.flags = tree.global_flags.clone();
+        let overrides = Overrides::parse(
+            r#"
+[global.flag.db]
+complete = "dir"
+
+[global.flag.verbose]
+hidden = true
+"#,
+        )
+        .unwrap();
+        overrides.apply(&mut tree).unwrap();
+        assert_eq!(tree.root.flags, tree.global_flags);
+
+        let mut buf = Vec::new();
+        generate_bash_completion(&tree, &GenerateOptions::default(), &mut buf).unwrap();
+        let script = String::from_utf8(buf).unwrap();
+        let root_case = &script[script.find("        *)\n").unwrap()..];
+        let db = "--db)\n                    mapfile -t COMPREPLY < <(compgen -d";
+        assert!(root_case.contains(db), "{root_case}");
+        assert!(!root_case.contains("compgen -f"), "{root_case}");
+        assert!(!script.contains("--verbose"), "{script}");
+    }
+
+    #[test]
+    fn test_flag_value_hints() {
+        let tree = apply(
+            r#"
+[command.create.flag.parent]
+complete = "issue-id"
+
+[command.create.flag.due]
+values = ["today", "tomorrow"]
+
+[global.flag.db]
+command = "ls *.db"
+"#,
+        )
+        .unwrap();
+        let create = &tree.root.subcommands["create"];
+        assert_eq!(create.flags[0].value_completion(), ValueCompletion::IssueId);
+        assert_eq!(
+            create.flags[1].value_completion(),
+            ValueCompletion::Values(vec!["today".to_string(), "tomorrow".to_string()])
+        );
+        assert_eq!(
+            tree.global_flags[0].value_completion(),
+            ValueCompletion::Command("ls *.db".to_string())
+        );
+    }
+
+    #[test]
+    fn test_add_hide_and_alias_commands() {
+        let tree = apply(
+            r#"
+[command."dep add"]
+hidden = true
+
+[command.create]
+aliases = ["add", "new"]
+
+[command.template]
+add = true
+description = "Manage templates"
+group = "Working With Issues"
+
+[command.template.flag.name]
+add = true
+short = "n"
+type = "string"
+values = ["bug", "epic"]
+"#,
+        )
+        .unwrap();
+        assert!(tree.root.subcommands["dep"].subcommands["add"].hidden);
+        assert_eq!(tree.root.subcommands["create"].aliases, ["new", "add"]);
+
+        let template = &tree.root.subcommands["template"];
+        assert_eq!(template.description, "Manage templates");
+        assert_eq!(template.group.as_deref(), Some("Working With Issues"));
+        assert_eq!(tree.groups[0].commands, ["create", "template"]);
+        assert_eq!(template.flags[0].short, Some('n'));
+        assert!(template.flags[0].takes_value());
+    }
+
+    #[test]
+    fn test_renames_apply_last() {
+        let tree = apply(
+            r#"
+[command.dep]
+rename = "deps"
+
+[command."dep add"]
+rename = "link"
+description = "Link two issues"
+
+[command.create]
+rename = "new"
+"#,
+        )
+        .unwrap();
+        let deps = &tree.root.subcommands["deps"];
+        assert_eq!(deps.name, "deps");
+        assert_eq!(deps.subcommands["link"].description, "Link two issues");
+        assert!(!tree.root.subcommands.contains_key("dep"));
+        // The old alias would shadow the new name
+        assert!(tree.root.subcommands["new"].aliases.is_empty());
+        assert_eq!(tree.groups[0].commands, ["new"]);
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        for (text, message) in [
+            (
+                "[command.crate]\nhidden = true\n",
+                "line 1: no command `bd crate` (set add = true",
+            ),
+            (
+                "\n[command.create.flag.prio]\nvalues = []\n",
+                "line 2: no flag --prio in bd create",
+            ),
+            (
+                "[command.create.flag.json]\ncomplete = \"file\"\n",
+                "line 1: --json takes no value",
+            ),
+            (
+                "[command.create]\nhide = true\n",
+                "line 2: unknown command key \"hide\"",
+            ),
+            (
+                "[command.create]\naliases = \"add\"\n",
+                "line 2: aliases must be an array",
+            ),
+            (
+                "[global.flag.db]\ncomplete = \"file\"\nvalues = [\"a\"]\n",
+                "line 3: set only one of",
+            ),
+            (
+                "[global.flag.db]\ncomplete = \"path\"\n",
+                "line 2: unknown completion \"path\"",
+            ),
+            (
+                "[global.flag.db]\nshort = \"db\"\n",
+                "line 2: short must be a single",
+            ),
+            ("[flags.db]\n", "line 1: unknown table [flags.db]"),
+            (
+                "[command.dep]\nrename = \"create\"\n",
+                "line 1: can't rename `bd dep`",
+            ),
+            (
+                "[command.\"dep add\"]\ngroup = \"x\"\n",
+                "line 1: only top-level commands",
+            ),
+        ] {
+            let err = apply(text).unwrap_err();
+            assert_eq!(err.exit_code(), 9, "{text:?}: {err:?}");
+            assert!(err.to_string().starts_with(message), "{text:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_load_tells_missing_files_from_bad_ones() {
+        let dir = std::env::temp_dir().join(format!("bd-complete-overrides-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("overrides.toml");
+
+        let err = Overrides::load(&path).unwrap_err();
+        assert!(matches!(err, BdCompleteError::Io(_)), "{err:?}");
+
+        std::fs::write(&path, "[command.create]\nhidden = \"yes\"\n").unwrap();
+        let err = Overrides::load(&path).unwrap_err();
+        assert!(
+            matches!(&err, BdCompleteError::InvalidOverrides { line: 2, .. }),
+            "{err:?}"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
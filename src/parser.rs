@@ -12,10 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::command_tree::{Command, CommandGroup, CommandTree, Flag};
-use std::collections::BTreeMap;
-use std::io;
+use crate::command_tree::{
+    is_deprecated_description, Command, CommandGroup, CommandMap, CommandTree, Flag,
+    PositionalArg, ValueKind,
+};
+use crate::error::BdCompleteError;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long one `--help` run may take before it's killed.
+pub const HELP_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Sections we recognize in cobra-style help output.
 #[derive(Debug, PartialEq)]
@@ -23,28 +35,148 @@ enum Section {
     Preamble,
     Usage,
     Aliases,
+    Examples,
     Commands(String), // group name like "Available Commands", "Working With Issues", etc.
     Flags,
     GlobalFlags,
+    /// Unindented trailer text after the last section (e.g. `Use "bd [command] --help" ...`).
+    Footer,
+}
+
+impl Section {
+    /// The section's header as help prints it, for diagnostics.
+    fn name(&self) -> &str {
+        match self {
+            Section::Preamble => "description",
+            Section::Usage => "Usage",
+            Section::Aliases => "Aliases",
+            Section::Examples => "Examples",
+            Section::Commands(group) => group,
+            Section::Flags => "Flags",
+            Section::GlobalFlags => "Global Flags",
+            Section::Footer => "footer",
+        }
+    }
+}
+
+/// What the parser made of a line it reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// The line was left out of the tree.
+    Skipped,
+    /// The line was used, but could have meant something else.
+    Ambiguous,
+}
+
+/// A help line the parser skipped or wasn't sure about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    /// The command whose help it's in, e.g. "bd dep add"
+    pub path: String,
+    /// The section it's in, e.g. "Flags" or "Working With Issues"
+    pub section: String,
+    /// 1-based line number
+    pub line: usize,
+    /// The line itself, without trailing whitespace
+    pub text: String,
+    pub kind: DiagnosticKind,
+    /// Why it was reported, e.g. "not a flag entry"
+    pub reason: &'static str,
+}
+
+impl Diagnostic {
+    /// The error `--strict` fails with for this line.
+    pub fn to_error(&self) -> BdCompleteError {
+        BdCompleteError::Unparseable {
+            path: self.path.clone(),
+            line: self.line,
+            message: format!("{} in {}: {:?}", self.reason, self.section, self.text),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            DiagnosticKind::Skipped => "skipped",
+            DiagnosticKind::Ambiguous => "ambiguous",
+        };
+        write!(
+            f,
+            "{}, line {} ({}): {kind}, {}: {:?}",
+            self.path, self.line, self.section, self.reason, self.text
+        )
+    }
+}
+
+/// Every `Diagnostic::reason` the parser reports.
+const COMMAND_LISTED_TWICE: &str = "command listed twice";
+const NOT_A_COMMAND: &str = "not a command entry";
+const FLAG_INDENTED_AS_CONTINUATION: &str = "flag indented like a wrapped description";
+const FLAG_LISTED_TWICE: &str = "flag listed twice";
+const NOT_A_FLAG: &str = "not a flag entry";
+const TEXT_AFTER_LAST_SECTION: &str = "text after the last section";
+
+/// The reason `text` names, for diagnostics read back from the cache.
+pub(crate) fn known_reason(text: &str) -> Option<&'static str> {
+    [
+        COMMAND_LISTED_TWICE,
+        NOT_A_COMMAND,
+        FLAG_INDENTED_AS_CONTINUATION,
+        FLAG_LISTED_TWICE,
+        NOT_A_FLAG,
+        TEXT_AFTER_LAST_SECTION,
+    ]
+    .into_iter()
+    .find(|reason| *reason == text)
 }
 
 /// Parse the help output of a cobra-style CLI command.
 pub fn parse_help_output(text: &str) -> (Command, Vec<Flag>, Vec<CommandGroup>) {
-    let mut description_lines: Vec<&str> = Vec::new();
+    parse_help_output_with_diagnostics(text, "", &mut Vec::new())
+}
+
+/// Like [`parse_help_output`], recording each line it skips or isn't sure
+/// about in `diagnostics` under the command `path`.
+pub fn parse_help_output_with_diagnostics(
+    text: &str,
+    path: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (Command, Vec<Flag>, Vec<CommandGroup>) {
+    let mut preamble_lines: Vec<&str> = Vec::new();
     let mut usage: Option<String> = None;
     let mut aliases: Vec<String> = Vec::new();
+    let mut example_lines: Vec<&str> = Vec::new();
     let mut flags: Vec<Flag> = Vec::new();
     let mut global_flags: Vec<Flag> = Vec::new();
-    let mut subcommands: BTreeMap<String, Command> = BTreeMap::new();
+    let mut subcommands = CommandMap::new();
     let mut groups: Vec<CommandGroup> = Vec::new();
     let mut current_group_name: Option<String> = None;
     let mut current_group_cmds: Vec<String> = Vec::new();
 
     let mut section = Section::Preamble;
+    // Column at which the description of the last flag/command entry starts.
+    // Indented lines at or beyond it are wrapped continuations of that entry.
+    let mut continuation_col: Option<usize> = None;
+
+    let mut report = |kind, section: &Section, index: usize, line: &str, reason| {
+        diagnostics.push(Diagnostic {
+            path: path.to_string(),
+            section: section.name().to_string(),
+            line: index + 1,
+            text: line.trim_end().to_string(),
+            kind,
+            reason,
+        })
+    };
 
-    for line in text.lines() {
-        // Detect section headers (lines ending with ':' and not starting with whitespace)
-        if !line.starts_with(' ') && !line.starts_with('\t') && line.ends_with(':') {
+    let text = strip_ansi(text);
+    let mut prev_blank = true;
+    for (index, line) in text.lines().enumerate() {
+        let after_blank = std::mem::replace(&mut prev_blank, line.trim().is_empty());
+        if is_section_header(line, &section, after_blank) {
             // Flush current group if any
             if let Some(gname) = current_group_name.take() {
                 if !current_group_cmds.is_empty() {
@@ -59,6 +191,7 @@ pub fn parse_help_output(text: &str) -> (Command, Vec<Flag>, Vec<CommandGroup>)
             section = match header {
                 "Usage" => Section::Usage,
                 "Aliases" => Section::Aliases,
+                "Examples" => Section::Examples,
                 "Flags" => Section::Flags,
                 "Global Flags" => Section::GlobalFlags,
                 _ => {
@@ -67,20 +200,33 @@ pub fn parse_help_output(text: &str) -> (Command, Vec<Flag>, Vec<CommandGroup>)
                     Section::Commands(header.to_string())
                 }
             };
+            continuation_col = None;
             continue;
         }
 
-        // Skip blank lines in some contexts
         let trimmed = line.trim();
         if trimmed.is_empty() {
+            // Blank lines separate paragraphs of the long description
+            if section == Section::Preamble && !preamble_lines.is_empty() {
+                preamble_lines.push("");
+            } else if section == Section::Examples && !example_lines.is_empty() {
+                example_lines.push("");
+            }
             continue;
         }
 
+        let indent = line.len() - line.trim_start().len();
+        // Examples are printed verbatim and may start in column 0
+        if indent == 0 && section != Section::Preamble && section != Section::Examples {
+            section = Section::Footer;
+        }
+        let is_continuation = continuation_col.is_some_and(|col| indent >= col);
+
         match &section {
             Section::Preamble => {
                 // Lines before the first section header are description
                 if !trimmed.starts_with("Use \"") {
-                    description_lines.push(trimmed);
+                    preamble_lines.push(line.trim_end());
                 }
             }
             Section::Usage => {
@@ -97,22 +243,66 @@ pub fn parse_help_output(text: &str) -> (Command, Vec<Flag>, Vec<CommandGroup>)
                     }
                 }
             }
+            Section::Examples => {
+                example_lines.push(line.trim_end());
+            }
             Section::Commands(group_name) => {
-                if let Some(cmd) = parse_command_line(trimmed) {
+                if is_continuation {
+                    if let Some(last) = current_group_cmds.last() {
+                        let cmd = subcommands.get_mut(last).unwrap();
+                        append_continuation(&mut cmd.description, trimmed);
+                        cmd.deprecated = is_deprecated_description(&cmd.description);
+                    }
+                } else if let Some(cmd) = parse_command_line(trimmed) {
+                    if subcommands.contains_key(&cmd.name) {
+                        let reason = COMMAND_LISTED_TWICE;
+                        report(DiagnosticKind::Ambiguous, &section, index, line, reason);
+                    }
                     let mut c = cmd;
                     c.group = Some(group_name.clone());
+                    c.deprecated = is_deprecated_description(&c.description);
+                    // Cobra's auto-added helpers are noise in completions
+                    c.hidden = c.is_helper();
+                    continuation_col = description_column(line, &c.description);
                     current_group_cmds.push(c.name.clone());
                     subcommands.insert(c.name.clone(), c);
+                } else {
+                    let reason = NOT_A_COMMAND;
+                    report(DiagnosticKind::Skipped, &section, index, line, reason);
                 }
             }
-            Section::Flags => {
-                if let Some(flag) = parse_flag_line(trimmed) {
-                    flags.push(flag);
+            Section::Flags | Section::GlobalFlags => {
+                let target = if section == Section::Flags {
+                    &mut flags
+                } else {
+                    &mut global_flags
+                };
+                if is_continuation {
+                    if trimmed.starts_with('-') {
+                        let reason = FLAG_INDENTED_AS_CONTINUATION;
+                        report(DiagnosticKind::Ambiguous, &section, index, line, reason);
+                    }
+                    if let Some(flag) = target.last_mut() {
+                        append_continuation(&mut flag.description, trimmed);
+                        flag.default = extract_default(&flag.description);
+                        flag.deprecated = is_deprecated_description(&flag.description);
+                    }
+                } else if let Some(flag) = parse_flag_line(trimmed) {
+                    if target.iter().any(|f| f.long == flag.long) {
+                        let reason = FLAG_LISTED_TWICE;
+                        report(DiagnosticKind::Ambiguous, &section, index, line, reason);
+                    }
+                    continuation_col = description_column(line, &flag.description);
+                    target.push(flag);
+                } else {
+                    let reason = NOT_A_FLAG;
+                    report(DiagnosticKind::Skipped, &section, index, line, reason);
                 }
             }
-            Section::GlobalFlags => {
-                if let Some(flag) = parse_flag_line(trimmed) {
-                    global_flags.push(flag);
+            Section::Footer => {
+                if !trimmed.starts_with("Use \"") {
+                    let reason = TEXT_AFTER_LAST_SECTION;
+                    report(DiagnosticKind::Skipped, &section, index, line, reason);
                 }
             }
         }
@@ -128,7 +318,23 @@ pub fn parse_help_output(text: &str) -> (Command, Vec<Flag>, Vec<CommandGroup>)
         }
     }
 
-    let description = description_lines.join(" ");
+    while preamble_lines.last() == Some(&"") {
+        preamble_lines.pop();
+    }
+
+    // The short description is the first paragraph; the long description keeps
+    // the whole preamble with its original line breaks.
+    let description = preamble_lines
+        .iter()
+        .take_while(|l| !l.is_empty())
+        .map(|l| l.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let long_description = if preamble_lines.is_empty() {
+        None
+    } else {
+        Some(preamble_lines.join("\n"))
+    };
 
     // The first alias is usually the command name itself in cobra
     // e.g. "create, new" — "create" is the name, "new" is the alias
@@ -140,6 +346,9 @@ pub fn parse_help_output(text: &str) -> (Command, Vec<Flag>, Vec<CommandGroup>)
     }
 
     let mut cmd = Command::new("", description);
+    cmd.long_description = long_description;
+    cmd.args = usage.as_deref().map(parse_usage_args).unwrap_or_default();
+    cmd.examples = dedent_block(&example_lines);
     cmd.usage = usage;
     cmd.aliases = aliases;
     cmd.flags = flags;
@@ -148,20 +357,149 @@ pub fn parse_help_output(text: &str) -> (Command, Vec<Flag>, Vec<CommandGroup>)
     (cmd, global_flags, groups)
 }
 
-/// Parse a command line like "  create           Create a new issue..."
+/// Whether a line starts a new section: an unindented line ending in ':'.
+///
+/// Descriptions and examples contain such lines too ("This command will:"),
+/// so the description only ends at "Usage:", and any other header has to
+/// follow a blank line as cobra's template always prints one before it.
+fn is_section_header(line: &str, section: &Section, after_blank: bool) -> bool {
+    if line.starts_with([' ', '\t']) || !line.ends_with(':') {
+        return false;
+    }
+    match section {
+        Section::Preamble => line == "Usage:",
+        _ => after_blank,
+    }
+}
+
+/// Strip the common space and tab indentation from a block of lines,
+/// dropping trailing blank lines.
+fn dedent_block(lines: &[&str]) -> Option<String> {
+    let end = lines.iter().rposition(|l| !l.is_empty())? + 1;
+    let lines = &lines[..end];
+    // Only ASCII indentation counts, so the slice below stays on a char boundary
+    let indent = lines
+        .iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.len() - l.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+    let dedented: Vec<&str> = lines
+        .iter()
+        .map(|l| if l.is_empty() { "" } else { &l[indent..] })
+        .collect();
+    Some(dedented.join("\n"))
+}
+
+/// Decompose a usage line like "bd dep add <issue> <depends-on> [flags]" into
+/// its positional arguments.
+///
+/// Leading bare lowercase words are the command path. `<x>` and ALL-CAPS words
+/// are required, `[x]` is optional, and a trailing `...` marks a variadic
+/// argument. Cobra's own `[flags]` and `[command]` placeholders are skipped.
+pub fn parse_usage_args(usage: &str) -> Vec<PositionalArg> {
+    let mut args = Vec::new();
+
+    for token in split_usage_tokens(usage) {
+        if token == "[flags]" || token == "[command]" {
+            continue;
+        }
+
+        let variadic = token.contains("...");
+        let (required, inner) = if let Some(inner) = token.strip_prefix('[') {
+            (false, inner)
+        } else if let Some(inner) = token.strip_prefix('<') {
+            (true, inner)
+        } else if args.is_empty() && !is_upper_word(&token) {
+            // Still part of the command path
+            continue;
+        } else {
+            (true, token.as_str())
+        };
+
+        // "id [id...]" keeps only the first name; brackets and dots are dropped
+        let name: String = inner
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .trim_end_matches("...")
+            .trim_end_matches(['>', ']'])
+            .trim_end_matches("...")
+            .to_string();
+        if name.is_empty() {
+            continue;
+        }
+
+        args.push(PositionalArg {
+            name,
+            required,
+            variadic,
+        });
+    }
+
+    args
+}
+
+/// Split a usage line on whitespace, keeping bracketed groups like `[id [id...]]` whole.
+fn split_usage_tokens(usage: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+
+    for c in usage.chars() {
+        match c {
+            '[' | '<' => depth += 1,
+            ']' | '>' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if c.is_whitespace() && depth == 0 {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn is_upper_word(s: &str) -> bool {
+    s.chars().any(|c| c.is_ascii_uppercase()) && !s.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// Column (byte offset in the raw line) where an entry's description starts.
+fn description_column(line: &str, description: &str) -> Option<usize> {
+    if description.is_empty() {
+        return None;
+    }
+    Some(line.trim_end().len() - description.len())
+}
+
+/// Append a wrapped continuation line to an entry's description.
+fn append_continuation(description: &mut String, text: &str) {
+    if !description.is_empty() {
+        description.push(' ');
+    }
+    description.push_str(text);
+}
+
+/// Parse a command line like "  create           Create a new issue...".
+/// The name ends at the first whitespace, however little padding follows it.
 fn parse_command_line(line: &str) -> Option<Command> {
     let trimmed = line.trim();
     if trimmed.is_empty() {
         return None;
     }
 
-    // Split on first run of 2+ spaces (command name is followed by spacing then description)
-    let parts: Vec<&str> = trimmed.splitn(2, "  ").collect();
-    let name = parts[0].trim();
-    let description = if parts.len() > 1 {
-        parts[1].trim()
-    } else {
-        ""
+    // Command names never contain spaces, so split on the first whitespace run.
+    // Cobra pads names to the longest one plus a single space, so the longest
+    // name in a listing is followed by just one space.
+    let (name, description) = match trimmed.split_once(char::is_whitespace) {
+        Some((name, rest)) => (name, rest.trim()),
+        None => (trimmed, ""),
     };
 
     if name.is_empty() || name.starts_with('-') {
@@ -181,8 +519,7 @@ fn parse_flag_line(line: &str) -> Option<Flag> {
 
     let mut short: Option<char> = None;
     let mut long = String::new();
-    let mut value_type: Option<String> = None;
-    let mut default: Option<String> = None;
+    let mut value_type: Option<ValueKind> = None;
 
     // Split into flag part and description part.
     let (flag_part, desc_part) = split_flag_description(trimmed);
@@ -197,7 +534,9 @@ fn parse_flag_line(line: &str) -> Option<Flag> {
         } else if tok.starts_with('-') && tok.len() == 2 {
             short = tok.chars().nth(1);
         } else if !tok.is_empty() && !tok.starts_with('-') {
-            value_type = Some(tok.to_string());
+            // Drop an optional-value suffix like `string[="always"]`
+            let name = tok.split("[=").next().unwrap_or(tok);
+            value_type = Some(ValueKind::from_type_name(name));
         }
         i += 1;
     }
@@ -207,25 +546,8 @@ fn parse_flag_line(line: &str) -> Option<Flag> {
     }
 
     let description = desc_part.to_string();
-
-    // Extract default value from description like (default: "value") or (default "value")
-    if let Some(start) = description.find("(default") {
-        if let Some(end) = description[start..].find(')') {
-            let default_str = &description[start..start + end + 1];
-            // Extract the value after "default" or "default:"
-            let val = default_str
-                .trim_start_matches("(default")
-                .trim_start_matches(':')
-                .trim()
-                .trim_end_matches(')')
-                .trim()
-                .trim_matches('"')
-                .to_string();
-            if !val.is_empty() {
-                default = Some(val);
-            }
-        }
-    }
+    let default = extract_default(&description);
+    let deprecated = is_deprecated_description(&description);
 
     Some(Flag {
         long,
@@ -233,9 +555,33 @@ fn parse_flag_line(line: &str) -> Option<Flag> {
         description,
         value_type,
         default,
+        deprecated,
+        hidden: false,
+        completion: None,
     })
 }
 
+/// Extract a default value from a description like `(default: "value")` or `(default "value")`.
+fn extract_default(description: &str) -> Option<String> {
+    let start = description.find("(default")?;
+    let end = description[start..].find(')')?;
+    let default_str = &description[start..start + end + 1];
+    // Extract the value after "default" or "default:"
+    let val = default_str
+        .trim_start_matches("(default")
+        .trim_start_matches(':')
+        .trim()
+        .trim_end_matches(')')
+        .trim()
+        .trim_matches('"')
+        .to_string();
+    if val.is_empty() {
+        None
+    } else {
+        Some(val)
+    }
+}
+
 /// Split a flag line into the flag portion and description portion.
 /// The description starts after the first run of 2+ spaces that follows a flag token.
 fn split_flag_description(line: &str) -> (&str, &str) {
@@ -283,8 +629,8 @@ fn split_flag_description(line: &str) -> (&str, &str) {
             }
             let next_token = &line[next_start..j];
 
-            // Value types are: string, strings, int, duration, etc. (lowercase, no dashes, short)
-            if is_value_type(next_token) {
+            // Value types are pflag type names: string, stringArray, int, duration, etc.
+            if ValueKind::known(next_token.split("[=").next().unwrap_or("")).is_some() {
                 // Include value type in flag part, continue
                 i = j;
                 // Skip spaces after value type
@@ -301,21 +647,164 @@ fn split_flag_description(line: &str) -> (&str, &str) {
     (line, "")
 }
 
-fn is_value_type(s: &str) -> bool {
-    matches!(
-        s,
-        "string" | "strings" | "int" | "int32" | "int64" | "float" | "float64" | "duration" | "uint" | "count"
-    )
+/// Remove ANSI escape sequences (CSI colors/cursor moves, OSC hyperlinks/titles,
+/// and other escapes such as tput's `ESC ( B` charset switch) from captured
+/// terminal output.
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters and intermediates, then a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: terminated by BEL or ST (ESC \)
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Others: intermediate bytes in ' '..'/', then a final byte
+                Some(c) if (' '..='/').contains(&c) => {
+                    for c in chars.by_ref() {
+                        if !(' '..='/').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            // 8-bit CSI
+            '\u{9b}' => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A fresh, empty HOME for the commands of one build, private to this user
+/// and removed when dropped.
+pub(crate) struct ScratchHome(PathBuf);
+
+impl ScratchHome {
+    pub(crate) fn create() -> io::Result<ScratchHome> {
+        // A new name each time, so nothing another user (or an earlier run)
+        // put in the temp dir can be picked up
+        let random = || {
+            use std::hash::{BuildHasher, Hasher};
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+            let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+            hasher.write_u128(now.unwrap_or_default().as_nanos());
+            hasher.finish()
+        };
+        loop {
+            let name = format!("bd-complete-home-{}-{:016x}", process::id(), random());
+            let path = std::env::temp_dir().join(name);
+            let mut builder = std::fs::DirBuilder::new();
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            match builder.create(&path) {
+                Ok(()) => return Ok(ScratchHome(path)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchHome {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
 }
 
 /// Run a command and capture its help output.
-pub fn run_help(command: &[&str]) -> io::Result<String> {
-    let output = process::Command::new(command[0])
+///
+/// The command runs in a controlled environment so the output is plain and
+/// unwrapped: colors and pagers are disabled, the terminal is wide and dumb,
+/// and HOME/XDG dirs point at a new, empty directory so user config can't
+/// leak in. It's killed if it runs longer than [`HELP_TIMEOUT`].
+pub fn run_help(command: &[&str]) -> Result<String, BdCompleteError> {
+    let home = ScratchHome::create().map_err(BdCompleteError::Io)?;
+    run_help_in(command, &home.0)
+}
+
+/// `program` set up to run in [`run_help`]'s controlled environment, with
+/// `home` as HOME, no stdin and its output captured.
+pub(crate) fn plain_command(program: &str, home: &Path) -> process::Command {
+    let mut command = process::Command::new(program);
+    command
+        .env("COLUMNS", "1000")
+        .env("NO_COLOR", "1")
+        .env("CLICOLOR", "0")
+        .env_remove("CLICOLOR_FORCE")
+        .env_remove("FORCE_COLOR")
+        .env("TERM", "dumb")
+        .env("PAGER", "cat")
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env("XDG_DATA_HOME", home.join(".local/share"))
+        .env("XDG_CACHE_HOME", home.join(".cache"))
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped());
+    command
+}
+
+/// [`run_help`] with `home` as HOME, so a whole build can share one.
+fn run_help_in(command: &[&str], home: &Path) -> Result<String, BdCompleteError> {
+    let child = plain_command(command[0], home)
         .args(&command[1..])
         .arg("--help")
-        .output()?;
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied => {
+                BdCompleteError::BinaryNotFound {
+                    binary: command[0].to_string(),
+                    source: e,
+                }
+            }
+            _ => BdCompleteError::Io(e),
+        })?;
+    let shown = format!("{} --help", command.join(" "));
+    let Some(output) = wait_with_timeout(child, HELP_TIMEOUT).map_err(BdCompleteError::Io)? else {
+        return Err(BdCompleteError::Timeout {
+            command: shown,
+            timeout: HELP_TIMEOUT,
+        });
+    };
+    if !output.status.success() {
+        return Err(BdCompleteError::NonZeroExit {
+            command: shown,
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
 
-    // Cobra outputs help to stdout on success, stderr on error
+    // Cobra outputs help to stdout; some tools use stderr instead
     let text = if output.stdout.is_empty() {
         String::from_utf8_lossy(&output.stderr).into_owned()
     } else {
@@ -324,46 +813,290 @@ pub fn run_help(command: &[&str]) -> io::Result<String> {
     Ok(text)
 }
 
+/// Collect the output of `child`, whose stdout and stderr are piped, or kill
+/// it once `timeout` has passed (None).
+pub(crate) fn wait_with_timeout(
+    mut child: process::Child,
+    timeout: Duration,
+) -> io::Result<Option<process::Output>> {
+    let deadline = Instant::now() + timeout;
+    // The pipes are drained on their own threads so a chatty child can't
+    // block on a full pipe while we wait for it
+    let (tx, rx) = mpsc::channel();
+    for (is_stdout, pipe) in [
+        (true, child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>)),
+        (false, child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>)),
+    ] {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            let _ = tx.send((is_stdout, buf));
+        });
+    }
+
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    for _ in 0..2 {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(left) {
+            Ok((true, buf)) => stdout = buf,
+            Ok((false, buf)) => stderr = buf,
+            Err(_) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+        }
+    }
+    // Both pipes are closed, so the child has almost always exited already
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(process::Output {
+                status,
+                stdout,
+                stderr,
+            }));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Where help text comes from.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum HelpSource {
+    /// Run `<binary> <path...> --help` live.
+    Binary(String),
+    /// Read help captured earlier: `bd create --help` is `<dir>/bd_create.txt`.
+    Dir { dir: PathBuf, root: String },
+    /// The root command's help text alone. Its commands are listed with
+    /// their descriptions but none of their own flags.
+    Text { root: String, help: String },
+}
+
+impl HelpSource {
+    /// The root command name; "/opt/bin/bd" still completes as "bd".
+    pub fn root_name(&self) -> String {
+        match self {
+            HelpSource::Binary(binary) => Path::new(binary)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| binary.to_string()),
+            HelpSource::Dir { root, .. } | HelpSource::Text { root, .. } => root.clone(),
+        }
+    }
+
+    /// Help text for the command at `path` below the root (empty for the root itself).
+    ///
+    /// Help that was never captured (a missing `--help-dir` file, or any
+    /// subcommand of `Text`) is an `Io` error of kind `NotFound`.
+    pub fn help(&self, path: &[&str]) -> Result<String, BdCompleteError> {
+        self.help_in(path, None)
+    }
+
+    /// [`HelpSource::help`], running live commands with `home` as HOME if
+    /// given, or else a new one for each.
+    fn help_in(&self, path: &[&str], home: Option<&Path>) -> Result<String, BdCompleteError> {
+        match self {
+            HelpSource::Binary(binary) => {
+                let mut command = vec![binary.as_str()];
+                command.extend_from_slice(path);
+                match home {
+                    Some(home) => run_help_in(&command, home),
+                    None => run_help(&command),
+                }
+            }
+            HelpSource::Dir { dir, root } => {
+                let file = dir.join(help_file_name(root, path));
+                std::fs::read_to_string(&file).map_err(|e| {
+                    BdCompleteError::Io(io::Error::new(
+                        e.kind(),
+                        format!("{}: {e}", file.display()),
+                    ))
+                })
+            }
+            HelpSource::Text { help, .. } if path.is_empty() => Ok(help.clone()),
+            HelpSource::Text { .. } => Err(BdCompleteError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                "only the root command's help was given",
+            ))),
+        }
+    }
+}
+
+/// Reads the help of one build. Live runs share a scratch HOME, removed
+/// when the reader is dropped.
+struct HelpReader<'a> {
+    source: &'a HelpSource,
+    home: Option<ScratchHome>,
+}
+
+impl<'a> HelpReader<'a> {
+    fn new(source: &'a HelpSource) -> Result<Self, BdCompleteError> {
+        let home = match source {
+            HelpSource::Binary(_) => Some(ScratchHome::create().map_err(BdCompleteError::Io)?),
+            _ => None,
+        };
+        Ok(HelpReader { source, home })
+    }
+
+    /// Help text for the command at `path`, checked to be help at all; None
+    /// if it was never captured.
+    fn read(&self, path: &[&str]) -> Result<Option<String>, BdCompleteError> {
+        let home = self.home.as_ref().map(|h| h.0.as_path());
+        let text = match self.source.help_in(path, home) {
+            Ok(text) => text,
+            Err(BdCompleteError::Io(e))
+                if e.kind() == io::ErrorKind::NotFound && !path.is_empty() =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+        let root = self.source.root_name();
+        let command = std::iter::once(root.as_str())
+            .chain(path.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+        check_is_help(&text, &command)?;
+        Ok(Some(text))
+    }
+}
+
+/// Reject output that isn't cobra help at all, like the error a binary
+/// without `--help` prints: help always has a "Usage:" section.
+fn check_is_help(text: &str, command: &str) -> Result<(), BdCompleteError> {
+    let text = strip_ansi(text);
+    if text.lines().any(|l| l == "Usage:") {
+        return Ok(());
+    }
+    let first = text
+        .lines()
+        .enumerate()
+        .find(|(_, l)| !l.trim().is_empty());
+    let (line, message) = match first {
+        Some((i, l)) => (
+            i + 1,
+            format!("expected help with a \"Usage:\" section, got {:?}", l.trim()),
+        ),
+        None => (1, "no help output".to_string()),
+    };
+    Err(BdCompleteError::Unparseable {
+        path: command.to_string(),
+        line,
+        message,
+    })
+}
+
+/// The file holding the help of the command at `path`: `bd_dep_add.txt`.
+fn help_file_name(root: &str, path: &[&str]) -> String {
+    let mut name = root.to_string();
+    for part in path {
+        name.push('_');
+        name.push_str(part);
+    }
+    name.push_str(".txt");
+    name
+}
+
 /// Build a full CommandTree by running `bd --help` and recursively parsing subcommands.
-pub fn build_command_tree(binary: &str) -> io::Result<CommandTree> {
-    let help_text = run_help(&[binary])?;
-    let (mut root_cmd, global_flags, groups) = parse_help_output(&help_text);
-    root_cmd.name = binary.to_string();
+pub fn build_command_tree(binary: &str) -> Result<CommandTree, BdCompleteError> {
+    build_command_tree_from(&HelpSource::Binary(binary.to_string()))
+}
+
+/// Build a full CommandTree from any help source. A subcommand whose help
+/// can't be read fails the whole build, unless it was never captured.
+pub fn build_command_tree_from(source: &HelpSource) -> Result<CommandTree, BdCompleteError> {
+    build_command_tree_with_diagnostics(source, &mut Vec::new())
+}
+
+/// Like [`build_command_tree_from`], recording the help lines the parser
+/// skipped or wasn't sure about in `diagnostics`.
+pub fn build_command_tree_with_diagnostics(
+    source: &HelpSource,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<CommandTree, BdCompleteError> {
+    build_tree(HelpReader::new(source)?, diagnostics)
+}
+
+fn build_tree(
+    reader: HelpReader<'_>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<CommandTree, BdCompleteError> {
+    let source = reader.source;
+    let root = source.root_name();
+    let help_text = reader.read(&[])?.expect("root help is always read");
+    let (mut root_cmd, global_flags, groups) =
+        parse_help_output_with_diagnostics(&help_text, &root, diagnostics);
+    root_cmd.name = source.root_name();
+
+    // Long names of the flags subcommands list under "Global Flags:", or None
+    // if no subcommand help could be read
+    let mut persistent: Option<BTreeSet<String>> = None;
 
     // Recursively parse each subcommand
     let subcommand_names: Vec<String> = root_cmd.subcommands.keys().cloned().collect();
     for name in subcommand_names {
-        if let Ok(sub_help) = run_help(&[binary, &name]) {
-            let (parsed, _sub_globals, _sub_groups) = parse_help_output(&sub_help);
-            let entry = root_cmd.subcommands.get_mut(&name).unwrap();
-            entry.flags = parsed.flags;
-            entry.aliases = parsed.aliases;
-            entry.usage = parsed.usage;
-
-            // If this subcommand itself has subcommands, recurse one more level
-            if !parsed.subcommands.is_empty() {
-                for (sub_name, mut sub_cmd) in parsed.subcommands {
-                    if let Ok(sub_sub_help) = run_help(&[binary, &name, &sub_name]) {
-                        let (parsed2, _, _) = parse_help_output(&sub_sub_help);
-                        sub_cmd.flags = parsed2.flags;
-                        sub_cmd.aliases = parsed2.aliases;
-                        sub_cmd.usage = parsed2.usage;
-                        // Could recurse deeper, but 2 levels covers bd's structure
-                        sub_cmd.subcommands = parsed2.subcommands;
-                    }
-                    entry.subcommands.insert(sub_name, sub_cmd);
-                }
+        let Some(sub_help) = reader.read(&[&name])? else {
+            continue;
+        };
+        let (parsed, sub_globals, _sub_groups) =
+            parse_help_output_with_diagnostics(&sub_help, &format!("{root} {name}"), diagnostics);
+        persistent
+            .get_or_insert_with(BTreeSet::new)
+            .extend(sub_globals.into_iter().map(|f| f.long));
+        let entry = root_cmd.subcommands.get_mut(&name).unwrap();
+        entry.long_description = parsed.long_description;
+        entry.flags = parsed.flags;
+        entry.aliases = parsed.aliases;
+        entry.usage = parsed.usage;
+        entry.args = parsed.args;
+        entry.examples = parsed.examples;
+
+        // If this subcommand itself has subcommands, recurse one more level
+        for (sub_name, mut sub_cmd) in parsed.subcommands {
+            if let Some(sub_sub_help) = reader.read(&[&name, &sub_name])? {
+                let path = format!("{root} {name} {sub_name}");
+                let (parsed2, sub_globals, _) =
+                    parse_help_output_with_diagnostics(&sub_sub_help, &path, diagnostics);
+                persistent
+                    .get_or_insert_with(BTreeSet::new)
+                    .extend(sub_globals.into_iter().map(|f| f.long));
+                sub_cmd.long_description = parsed2.long_description;
+                sub_cmd.flags = parsed2.flags;
+                sub_cmd.aliases = parsed2.aliases;
+                sub_cmd.usage = parsed2.usage;
+                sub_cmd.args = parsed2.args;
+                sub_cmd.examples = parsed2.examples;
+                // Could recurse deeper, but 2 levels covers bd's structure
+                sub_cmd.subcommands = parsed2.subcommands;
             }
+            entry.subcommands.insert(sub_name, sub_cmd);
         }
     }
 
     let mut tree = CommandTree::new(root_cmd);
-    // At the root level, cobra puts global flags under "Flags:" (no separate "Global Flags:").
-    // Use the root flags as global flags if no explicit Global Flags section was found.
-    tree.global_flags = if global_flags.is_empty() {
-        tree.root.flags.clone()
-    } else {
+    // At the root level, cobra lists persistent flags under "Flags:" together with
+    // root-only ones like --version. The persistent ones are those subcommands show
+    // under "Global Flags:"; without any subcommand help, assume all of them are.
+    tree.global_flags = if !global_flags.is_empty() {
         global_flags
+    } else if let Some(persistent) = &persistent {
+        tree.root
+            .flags
+            .iter()
+            .filter(|f| persistent.contains(&f.long))
+            .cloned()
+            .collect()
+    } else {
+        tree.root.flags.clone()
     };
     tree.groups = groups;
     Ok(tree)
@@ -372,6 +1105,7 @@ pub fn build_command_tree(binary: &str) -> io::Result<CommandTree> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::command_tree::ValueCompletion;
 
     const BD_HELP: &str = r#"Issues chained together like beads. A lightweight issue tracker with first-class dependency support.
 
@@ -443,6 +1177,68 @@ Global Flags:
 
 Use "bd epic [command] --help" for more information about a command."#;
 
+    const WRAPPED_HELP: &str = r#"Update one or more issues.
+
+If no issue ID is given, updates the last touched issue.
+Fields not given on the command line are left unchanged.
+
+Usage:
+  bd update [id...] [flags]
+
+Available Commands:
+  fields        List the fields that can be updated, including custom
+                fields registered by plugins
+
+Flags:
+      --due string        Due date in any format understood by the
+                          date parser, relative or absolute
+                          (default "tomorrow")
+  -h, --help              help for update
+  -s, --status string     New status
+
+Global Flags:
+      --db string   Database path
+
+Use "bd update [command] --help" for more information about a command."#;
+
+    const DEP_ADD_HELP: &str = r#"Add a dependency between two issues
+
+Usage:
+  bd dep add <issue> <depends-on> [flags]
+
+Examples:
+  # bd-2 blocks bd-1
+  bd dep add bd-1 bd-2
+
+  # Record a softer relationship
+  bd dep add bd-1 bd-3 --type related
+
+Flags:
+  -h, --help          help for add
+  -t, --type string   Dependency type (default "blocks")
+"#;
+
+    const TYPES_HELP: &str = r#"List issues
+
+Usage:
+  bd list [flags]
+
+Flags:
+      --all                          Show closed issues too
+      --archived bool                Include archived issues
+      --color string[="always"]      Colorize output
+  -l, --label stringArray            Filter by label (repeatable)
+      --ids strings                  Filter by issue IDs
+      --limit int                    Maximum number of results
+      --priorities ints              Filter by priorities
+      --since duration               Only issues updated within this window
+      --server ip                    Sync server address
+      --token bytesHex               Raw API token
+      --meta stringToString          Filter by metadata (default [])
+      --out path                     Write results to path
+  -v, --verbose count                Increase verbosity
+"#;
+
     #[test]
     fn test_parse_top_level_commands() {
         let (cmd, _globals, _groups) = parse_help_output(BD_HELP);
@@ -501,7 +1297,7 @@ Use "bd epic [command] --help" for more information about a command."#;
         let (cmd, _globals, _) = parse_help_output(BD_HELP);
 
         let db_flag = cmd.flags.iter().find(|f| f.long == "db").unwrap();
-        assert_eq!(db_flag.value_type.as_deref(), Some("string"));
+        assert_eq!(db_flag.value_type, Some(ValueKind::String));
         assert!(db_flag.description.contains("Database path"));
         assert_eq!(db_flag.default.as_deref(), Some("auto-discover .beads/*.db"));
 
@@ -526,7 +1322,7 @@ Use "bd epic [command] --help" for more information about a command."#;
 
         // --db is a string flag
         let db_flag = cmd.flags.iter().find(|f| f.long == "db").unwrap();
-        assert_eq!(db_flag.value_type.as_deref(), Some("string"));
+        assert_eq!(db_flag.value_type, Some(ValueKind::String));
     }
 
     #[test]
@@ -543,15 +1339,15 @@ Use "bd epic [command] --help" for more information about a command."#;
         // Local flags
         let priority = cmd.flags.iter().find(|f| f.long == "priority").unwrap();
         assert_eq!(priority.short, Some('p'));
-        assert_eq!(priority.value_type.as_deref(), Some("string"));
+        assert_eq!(priority.value_type, Some(ValueKind::String));
         assert_eq!(priority.default.as_deref(), Some("2"));
 
         let labels = cmd.flags.iter().find(|f| f.long == "labels").unwrap();
-        assert_eq!(labels.value_type.as_deref(), Some("strings"));
+        assert_eq!(labels.value_type, Some(ValueKind::Strings));
 
         // Global flags
         let db = globals.iter().find(|f| f.long == "db").unwrap();
-        assert_eq!(db.value_type.as_deref(), Some("string"));
+        assert_eq!(db.value_type, Some(ValueKind::String));
     }
 
     #[test]
@@ -584,4 +1380,584 @@ Use "bd epic [command] --help" for more information about a command."#;
         let assignee = cmd.flags.iter().find(|f| f.long == "assignee").unwrap();
         assert_eq!(assignee.default, None);
     }
+
+    #[test]
+    fn test_parse_wrapped_flag_description() {
+        let (cmd, _, _) = parse_help_output(WRAPPED_HELP);
+
+        assert_eq!(cmd.flags.len(), 3);
+        let due = cmd.flags.iter().find(|f| f.long == "due").unwrap();
+        assert_eq!(
+            due.description,
+            "Due date in any format understood by the date parser, relative or absolute (default \"tomorrow\")"
+        );
+        assert_eq!(due.default.as_deref(), Some("tomorrow"));
+
+        let status = cmd.flags.iter().find(|f| f.long == "status").unwrap();
+        assert_eq!(status.description, "New status");
+    }
+
+    #[test]
+    fn test_parse_wrapped_command_description() {
+        let (cmd, _, _) = parse_help_output(WRAPPED_HELP);
+
+        assert_eq!(cmd.subcommands.len(), 1);
+        assert_eq!(
+            cmd.subcommands["fields"].description,
+            "List the fields that can be updated, including custom fields registered by plugins"
+        );
+    }
+
+    #[test]
+    fn test_parse_long_description() {
+        let (cmd, _, _) = parse_help_output(WRAPPED_HELP);
+
+        assert_eq!(cmd.description, "Update one or more issues.");
+        assert_eq!(
+            cmd.long_description.as_deref(),
+            Some(
+                "Update one or more issues.\n\nIf no issue ID is given, updates the last touched issue.\nFields not given on the command line are left unchanged."
+            )
+        );
+    }
+
+    #[test]
+    fn test_description_lines_ending_in_colon_are_not_headers() {
+        let text = "Delete issues and their references.\nThis command will:\n1. Remove dependency links\n2. Delete the issues\n\nNote:\n  Deleted IDs are never reused.\n\nUsage:\n  bd delete <issue-id> [flags]\n\nExamples:\n  # Preview first\n  bd delete bd-1 --dry-run\nDelete for real:\n  bd delete bd-1 --force\n\nFlags:\n      --force   Actually delete\n";
+        let (cmd, _, groups) = parse_help_output(text);
+
+        assert!(groups.is_empty(), "misparsed headers: {groups:?}");
+        assert!(cmd.subcommands.is_empty());
+        let long = cmd.long_description.unwrap();
+        assert!(long.contains("This command will:\n1. Remove dependency links"));
+        assert!(long.contains("Note:\n  Deleted IDs are never reused."));
+        assert!(cmd.examples.unwrap().contains("Delete for real:"));
+        assert_eq!(cmd.flags.len(), 1);
+    }
+
+    #[test]
+    fn test_is_section_header() {
+        let preamble = Section::Preamble;
+        assert!(is_section_header("Usage:", &preamble, false));
+        // The description only ends at "Usage:"
+        assert!(!is_section_header("Note:", &preamble, true));
+        assert!(!is_section_header("Flags:", &preamble, true));
+
+        let usage = Section::Usage;
+        assert!(is_section_header("Flags:", &usage, true));
+        // Elsewhere a header needs the blank line cobra prints before it
+        assert!(!is_section_header("Delete for real:", &usage, false));
+        assert!(!is_section_header("  Flags:", &usage, true));
+        assert!(!is_section_header("Flags", &usage, true));
+    }
+
+    #[test]
+    fn test_footer_is_not_parsed_as_flag() {
+        let (_, globals, _) = parse_help_output(EPIC_HELP);
+
+        assert_eq!(globals.len(), 2);
+        assert!(globals.iter().all(|f| f.long != "help\""));
+    }
+
+    #[test]
+    fn test_parse_examples() {
+        let (cmd, _, _) = parse_help_output(DEP_ADD_HELP);
+
+        assert_eq!(
+            cmd.examples.as_deref(),
+            Some(
+                "# bd-2 blocks bd-1\nbd dep add bd-1 bd-2\n\n# Record a softer relationship\nbd dep add bd-1 bd-3 --type related"
+            )
+        );
+        // The examples block must not leak into flags or the description
+        assert_eq!(cmd.flags.len(), 2);
+        assert_eq!(cmd.description, "Add a dependency between two issues");
+    }
+
+    #[test]
+    fn test_dedent_block() {
+        assert_eq!(
+            dedent_block(&["  a", "", "    b", "", ""]).as_deref(),
+            Some("a\n\n  b")
+        );
+        assert_eq!(dedent_block(&["", ""]), None);
+        // Only spaces and tabs count as indentation: U+3000 is whitespace to
+        // trim_start() but three bytes wide, and slicing by it would panic
+        assert_eq!(
+            dedent_block(&["  bd x", "\u{3000}bd x --all"]).as_deref(),
+            Some("  bd x\n\u{3000}bd x --all")
+        );
+    }
+
+    #[test]
+    fn test_examples_with_unicode_indentation() {
+        let text = "Usage:\n  bd x\n\nExamples:\n  bd x\n\u{3000}bd x --all\n";
+        let (cmd, _, _) = parse_help_output(text);
+        assert_eq!(cmd.examples.as_deref(), Some("  bd x\n\u{3000}bd x --all"));
+    }
+
+    #[test]
+    fn test_parse_positional_args() {
+        let (cmd, _, _) = parse_help_output(DEP_ADD_HELP);
+        assert_eq!(
+            cmd.args,
+            vec![
+                PositionalArg {
+                    name: "issue".to_string(),
+                    required: true,
+                    variadic: false,
+                },
+                PositionalArg {
+                    name: "depends-on".to_string(),
+                    required: true,
+                    variadic: false,
+                },
+            ]
+        );
+
+        let (cmd, _, _) = parse_help_output(CREATE_HELP);
+        assert_eq!(cmd.args.len(), 1);
+        assert_eq!(cmd.args[0].name, "title");
+        assert!(!cmd.args[0].required);
+
+        // "[command]" and "[flags]" are not positionals
+        let (cmd, _, _) = parse_help_output(EPIC_HELP);
+        assert!(cmd.args.is_empty());
+    }
+
+    #[test]
+    fn test_parse_usage_args_variadic() {
+        let args = parse_usage_args("bd close <id>... [flags]");
+        assert_eq!(args.len(), 1);
+        assert!(args[0].required && args[0].variadic);
+        assert_eq!(args[0].name, "id");
+
+        let args = parse_usage_args("bd update [id [id...]] [flags]");
+        assert_eq!(args.len(), 1);
+        assert!(!args[0].required && args[0].variadic);
+        assert_eq!(args[0].name, "id");
+
+        let args = parse_usage_args("bd show ID");
+        assert_eq!(args.len(), 1);
+        assert_eq!(args[0].name, "ID");
+        assert!(args[0].required && !args[0].variadic);
+    }
+
+    #[test]
+    fn test_parse_value_kinds() {
+        let (cmd, _, _) = parse_help_output(TYPES_HELP);
+        let kind = |name: &str| {
+            cmd.flags
+                .iter()
+                .find(|f| f.long == name)
+                .unwrap()
+                .value_type
+                .clone()
+        };
+
+        assert_eq!(kind("all"), None);
+        assert_eq!(kind("archived"), Some(ValueKind::Bool));
+        assert_eq!(kind("color"), Some(ValueKind::String));
+        assert_eq!(kind("label"), Some(ValueKind::StringArray));
+        assert_eq!(kind("ids"), Some(ValueKind::Strings));
+        assert_eq!(kind("limit"), Some(ValueKind::Int));
+        assert_eq!(kind("priorities"), Some(ValueKind::Ints));
+        assert_eq!(kind("since"), Some(ValueKind::Duration));
+        assert_eq!(kind("server"), Some(ValueKind::Ip));
+        assert_eq!(kind("token"), Some(ValueKind::BytesHex));
+        assert_eq!(kind("meta"), Some(ValueKind::StringToString));
+        assert_eq!(kind("out"), Some(ValueKind::Custom("path".to_string())));
+        assert_eq!(kind("verbose"), Some(ValueKind::Count));
+
+        // Type tokens never leak into descriptions
+        let label = cmd.flags.iter().find(|f| f.long == "label").unwrap();
+        assert_eq!(label.description, "Filter by label (repeatable)");
+    }
+
+    #[test]
+    fn test_value_completion_by_kind() {
+        let (cmd, _, _) = parse_help_output(TYPES_HELP);
+        let completion = |name: &str| {
+            cmd.flags
+                .iter()
+                .find(|f| f.long == name)
+                .unwrap()
+                .value_completion()
+        };
+
+        assert_eq!(completion("all"), ValueCompletion::Nothing);
+        assert_eq!(completion("archived"), ValueCompletion::Bool);
+        assert_eq!(completion("limit"), ValueCompletion::Nothing);
+        assert_eq!(completion("since"), ValueCompletion::Duration);
+        assert_eq!(completion("meta"), ValueCompletion::KeyValue);
+        assert_eq!(completion("out"), ValueCompletion::File);
+
+        // Count flags are repeated, not given a value
+        let verbose = cmd.flags.iter().find(|f| f.long == "verbose").unwrap();
+        assert!(!verbose.takes_value());
+    }
+
+    #[test]
+    fn test_value_kind_names_round_trip() {
+        for name in ["bool", "strings", "stringArray", "int64", "uints", "float", "durationSlice", "ipNet", "stringToInt64"] {
+            assert_eq!(ValueKind::from_type_name(name).type_name(), name);
+        }
+        assert_eq!(ValueKind::from_type_name("stringSlice"), ValueKind::Strings);
+        assert_eq!(ValueKind::from_type_name("float64"), ValueKind::Float64);
+        assert_eq!(ValueKind::known("path"), None);
+    }
+
+    #[test]
+    fn test_parse_deprecated_and_helpers() {
+        let help = r#"Usage:
+  bd [command]
+
+Available Commands:
+  completion  Generate the autocompletion script for the specified shell
+  help        Help about any command
+  init        Initialize bd in the current directory
+  migrate     DEPRECATED: use 'bd upgrade' instead
+
+Flags:
+      --no-db        Use JSONL only
+      --sandbox      Deprecated: use --no-daemon
+      --no-daemon    Run without the background daemon
+"#;
+        let (cmd, _, _) = parse_help_output(help);
+
+        assert!(cmd.subcommands["help"].hidden);
+        assert!(cmd.subcommands["completion"].hidden);
+        assert!(!cmd.subcommands["init"].hidden);
+        assert!(cmd.subcommands["migrate"].deprecated);
+        assert!(!cmd.subcommands["init"].deprecated);
+
+        let sandbox = cmd.flags.iter().find(|f| f.long == "sandbox").unwrap();
+        assert!(sandbox.deprecated);
+        let no_db = cmd.flags.iter().find(|f| f.long == "no-db").unwrap();
+        assert!(!no_db.deprecated);
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[1;32mFlags:\x1b[0m"), "Flags:");
+        assert_eq!(
+            strip_ansi("see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\ now"),
+            "see docs now"
+        );
+        assert_eq!(strip_ansi("\x1b]0;title\x07plain"), "plain");
+        assert_eq!(strip_ansi("\u{9b}31mred\u{9b}0m"), "red");
+        assert_eq!(strip_ansi("naïve — unchanged"), "naïve — unchanged");
+        // tput sgr0 switches the charset back before resetting attributes
+        assert_eq!(strip_ansi("\x1b[1mbold\x1b(B\x1b[m plain"), "bold plain");
+        assert_eq!(strip_ansi("\x1b=keypad\x1b>\x1b%G"), "keypad");
+    }
+
+    #[test]
+    fn test_parse_colored_help() {
+        let colored = "\x1b[1mCreate a new issue\x1b[0m\n\n\x1b[1;4mUsage:\x1b[0m\n  bd create [title] [flags]\n\n\x1b[1;4mAliases:\x1b[0m\n  create, new\n\n\x1b[1;4mFlags:\x1b[0m\n  \x1b[36m-p\x1b[0m, \x1b[36m--priority\x1b[0m \x1b[33mstring\x1b[0m   Priority (default \"2\")\n      \x1b[36m--json\x1b[0m              Output in JSON format\n";
+        // Reset the way `tput sgr0` does in a terminfo-colored help template
+        let tput = colored.replace("\x1b[0m", "\x1b(B\x1b[m");
+        let (plain, _, _) = parse_help_output(CREATE_HELP);
+        let (cmd, _, _) = parse_help_output(colored);
+        let (tput_cmd, _, _) = parse_help_output(&tput);
+        assert_eq!(tput_cmd.description, "Create a new issue");
+        assert_eq!(tput_cmd.flags, cmd.flags);
+
+        assert_eq!(cmd.description, "Create a new issue");
+        assert_eq!(cmd.usage, plain.usage);
+        assert_eq!(cmd.aliases, plain.aliases);
+
+        let priority = cmd.flags.iter().find(|f| f.long == "priority").unwrap();
+        assert_eq!(priority.short, Some('p'));
+        assert_eq!(priority.value_type, Some(ValueKind::String));
+        assert_eq!(priority.default.as_deref(), Some("2"));
+        let json = cmd.flags.iter().find(|f| f.long == "json").unwrap();
+        assert_eq!(json.value_type, None);
+    }
+
+    #[test]
+    fn test_parse_command_line() {
+        let parse = |line| parse_command_line(line).map(|c| (c.name, c.description));
+        let pair = |name: &str, description: &str| {
+            Some((name.to_string(), description.to_string()))
+        };
+
+        assert_eq!(
+            parse("  create      Create a new issue"),
+            pair("create", "Create a new issue")
+        );
+        // The longest name in a listing is followed by a single space
+        assert_eq!(
+            parse("  close-eligible Close epics  where all"),
+            pair("close-eligible", "Close epics  where all")
+        );
+        assert_eq!(parse("  list\tList issues"), pair("list", "List issues"));
+        assert_eq!(parse("  version"), pair("version", ""));
+        assert_eq!(parse("   "), None);
+        assert_eq!(parse("  --flag   Not a command"), None);
+    }
+
+    #[test]
+    fn test_parse_command_with_single_space_padding() {
+        let help = "Usage:\n  bd epic [command]\n\nAvailable Commands:\n  close-eligible Close epics where all children are complete\n  status         Show epic completion status\n";
+        let (cmd, _, _) = parse_help_output(help);
+
+        assert_eq!(
+            cmd.subcommands["close-eligible"].description,
+            "Close epics where all children are complete"
+        );
+        assert_eq!(cmd.subcommands["status"].description, "Show epic completion status");
+    }
+
+    #[test]
+    fn test_build_tree_from_help_dir() {
+        let dir = std::env::temp_dir().join(format!("bd-complete-helpdir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("bd.txt"), BD_HELP).unwrap();
+        std::fs::write(dir.join("bd_create.txt"), CREATE_HELP).unwrap();
+
+        let source = HelpSource::Dir {
+            dir: dir.clone(),
+            root: "bd".to_string(),
+        };
+        let tree = build_command_tree_from(&source).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(tree.root.name, "bd");
+        // Commands with captured help are filled in; the rest keep their listing
+        assert_eq!(tree.root.subcommands["create"].aliases, vec!["new"]);
+        assert_eq!(
+            tree.root.subcommands["count"].description,
+            "Count issues matching filters"
+        );
+        // Persistent flags are the root flags create lists as global
+        let globals: Vec<&str> = tree.global_flags.iter().map(|f| f.long.as_str()).collect();
+        assert_eq!(globals, ["db", "json", "verbose"]);
+        // Root-only flags stay on the root
+        assert!(tree.root.flags.iter().any(|f| f.long == "help"));
+        assert!(tree.root.flags.iter().any(|f| f.long == "quiet"));
+    }
+
+    #[test]
+    fn test_build_tree_without_subcommand_help() {
+        let dir = std::env::temp_dir().join(format!("bd-complete-rootonly-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("bd.txt"), BD_HELP).unwrap();
+
+        let source = HelpSource::Dir {
+            dir: dir.clone(),
+            root: "bd".to_string(),
+        };
+        let tree = build_command_tree_from(&source).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Nothing to tell persistent flags apart, so all root flags are kept
+        assert_eq!(tree.global_flags, tree.root.flags);
+    }
+
+    #[test]
+    fn test_build_tree_from_root_help_text() {
+        let source = HelpSource::Text {
+            root: "bd".to_string(),
+            help: BD_HELP.to_string(),
+        };
+        let tree = build_command_tree_from(&source).unwrap();
+        assert_eq!(tree.root.name, "bd");
+        assert_eq!(tree.groups[0].name, "Working With Issues");
+        assert_eq!(
+            tree.root.subcommands["create"].description,
+            "Create a new issue (or multiple issues from markdown file)"
+        );
+        assert!(tree.root.subcommands["create"].flags.is_empty());
+    }
+
+    #[test]
+    fn test_diagnostics_for_unrecognized_lines() {
+        let help = "Manage dependencies\n\n\
+            Usage:\n  bd dep [command]\n\n\
+            Available Commands:\n  add         Add a dependency\n  -x\n  add         Add it again\n\n\
+            Flags:\n  -h, --help   help for dep\n  ???\n  -h, --help   help again\n\n\
+            Use \"bd dep [command] --help\" for more.\n\
+            Stray trailer\n";
+        let mut diagnostics = Vec::new();
+        let (cmd, _, _) = parse_help_output_with_diagnostics(help, "bd dep", &mut diagnostics);
+        // Reported lines are still parsed as before
+        assert_eq!(cmd.subcommands.len(), 1);
+        assert_eq!(cmd.flags.len(), 2);
+
+        let found: Vec<(usize, &str, DiagnosticKind, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.line, d.section.as_str(), d.kind, d.reason))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (8, "Available Commands", DiagnosticKind::Skipped, "not a command entry"),
+                (9, "Available Commands", DiagnosticKind::Ambiguous, "command listed twice"),
+                (13, "Flags", DiagnosticKind::Skipped, "not a flag entry"),
+                (14, "Flags", DiagnosticKind::Ambiguous, "flag listed twice"),
+                (17, "footer", DiagnosticKind::Skipped, "text after the last section"),
+            ]
+        );
+        assert_eq!(diagnostics[2].path, "bd dep");
+        assert_eq!(diagnostics[2].text, "  ???");
+        assert_eq!(
+            diagnostics[2].to_string(),
+            "bd dep, line 13 (Flags): skipped, not a flag entry: \"  ???\""
+        );
+        assert_eq!(diagnostics[2].to_error().exit_code(), 6);
+
+        // Well-formed help has nothing to report
+        let mut diagnostics = Vec::new();
+        for text in [BD_HELP, CREATE_HELP, EPIC_HELP, WRAPPED_HELP] {
+            parse_help_output_with_diagnostics(text, "bd", &mut diagnostics);
+        }
+        assert_eq!(diagnostics, []);
+    }
+
+    #[test]
+    fn test_build_tree_diagnostics_name_the_command() {
+        let dir = std::env::temp_dir().join(format!("bd-complete-diag-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("bd.txt"), BD_HELP).unwrap();
+        let create = CREATE_HELP.replace("  -h, --help ", "  -h         ");
+        std::fs::write(dir.join("bd_create.txt"), create).unwrap();
+
+        let source = HelpSource::Dir {
+            dir: dir.clone(),
+            root: "bd".to_string(),
+        };
+        let mut diagnostics = Vec::new();
+        build_command_tree_with_diagnostics(&source, &mut diagnostics).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].path, "bd create");
+        assert_eq!(diagnostics[0].section, "Flags");
+    }
+
+    /// An executable script in a scratch directory.
+    #[cfg(unix)]
+    fn write_script(name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let dir =
+            std::env::temp_dir().join(format!("bd-complete-run-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bd");
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_tree_errors() {
+        let missing = build_command_tree("/nonexistent/bd").unwrap_err();
+        assert!(matches!(missing, BdCompleteError::BinaryNotFound { .. }), "{missing:?}");
+
+        let failing = write_script("failing", "echo 'Error: no database' >&2; exit 1");
+        let err = build_command_tree(failing.to_str().unwrap()).unwrap_err();
+        assert!(matches!(err, BdCompleteError::NonZeroExit { .. }), "{err:?}");
+        assert!(err.to_string().ends_with("(exit status: 1): Error: no database"), "{err}");
+
+        let junk = write_script("junk", "echo; echo 'unknown flag: --help'");
+        let err = build_command_tree(junk.to_str().unwrap()).unwrap_err();
+        match &err {
+            BdCompleteError::Unparseable { path, line, .. } => {
+                assert_eq!((path.as_str(), *line), ("bd", 2));
+            }
+            _ => panic!("{err:?}"),
+        }
+
+        // A subcommand that fails is reported, not left out
+        let broken_create = write_script(
+            "subcommand",
+            &format!(
+                "[ \"$1\" = create ] && exit 2\ncat <<'EOF'\n{BD_HELP}\nEOF"
+            ),
+        );
+        let err = build_command_tree(broken_create.to_str().unwrap()).unwrap_err();
+        match &err {
+            BdCompleteError::NonZeroExit { command, .. } => {
+                assert!(command.ends_with("bd create --help"), "{command}");
+            }
+            _ => panic!("{err:?}"),
+        }
+
+        for script in [failing, junk, broken_create] {
+            let _ = std::fs::remove_dir_all(script.parent().unwrap());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_help_uses_a_private_home() {
+        let script = write_script(
+            "home",
+            "echo Usage:; echo \"$HOME\"; ls -ld \"$HOME\" | cut -c1-10; ls -A \"$HOME\" | wc -l",
+        );
+        let run = || run_help(&[script.to_str().unwrap()]).unwrap();
+        let (first, second) = (run(), run());
+        let lines: Vec<&str> = first.lines().map(str::trim).collect();
+        assert_eq!(lines[2..], ["drwx------", "0"], "{first}");
+        assert!(!Path::new(lines[1]).exists(), "{} was left behind", lines[1]);
+        assert_ne!(lines[1], second.lines().nth(1).unwrap());
+
+        // A build shares one HOME between its commands and removes it after
+        let logging = write_script(
+            "home-build",
+            &format!("echo \"$HOME\" >> \"${{0%/*}}/homes\"\ncat <<'EOF'\n{BD_HELP}\nEOF"),
+        );
+        build_command_tree(logging.to_str().unwrap()).unwrap();
+        let homes = std::fs::read_to_string(logging.with_file_name("homes")).unwrap();
+        let homes: BTreeSet<&str> = homes.lines().collect();
+        assert_eq!(homes.len(), 1, "{homes:?}");
+        assert!(!Path::new(homes.first().unwrap()).exists());
+
+        for script in [script, logging] {
+            let _ = std::fs::remove_dir_all(script.parent().unwrap());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wait_with_timeout_kills_the_child() {
+        let child = process::Command::new("sleep")
+            .arg("5")
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let start = Instant::now();
+        assert!(wait_with_timeout(child, Duration::from_millis(50)).unwrap().is_none());
+        assert!(start.elapsed() < Duration::from_secs(4));
+
+        let child = process::Command::new("echo")
+            .arg("hi")
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let output = wait_with_timeout(child, HELP_TIMEOUT).unwrap().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hi\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_tree_names_the_root_after_the_executable() {
+        let script = write_script("root-name", &format!("cat <<'EOF'\n{EPIC_HELP}\nEOF"));
+        let tree = build_command_tree(script.to_str().unwrap()).unwrap();
+        assert_eq!(tree.root.name, "bd");
+        let _ = std::fs::remove_dir_all(script.parent().unwrap());
+    }
+
+    #[test]
+    fn test_binary_source_root_name() {
+        assert_eq!(HelpSource::Binary("/opt/bin/bd".to_string()).root_name(), "bd");
+        assert_eq!(HelpSource::Binary("bd".to_string()).root_name(), "bd");
+    }
 }
+
+#[cfg(test)]
+mod fuzz_tests;
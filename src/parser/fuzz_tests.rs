@@ -0,0 +1,290 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Randomized parser tests. Inputs come from a seeded xorshift generator with
+//! a fixed default seed, so `cargo test` checks the same inputs every run: set
+//! `BD_COMPLETE_FUZZ_SEED` to explore others and `BD_COMPLETE_FUZZ_ITERS` to
+//! run longer. The cargo-fuzz target in `fuzz/` covers the same entry points
+//! with coverage guidance.
+
+use super::*;
+
+/// Small xorshift64* generator; good enough to shake out parser edge cases.
+struct Rng(u64);
+
+/// The seed used unless `BD_COMPLETE_FUZZ_SEED` is set.
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+impl Rng {
+    fn from_env() -> (Self, u64) {
+        let seed = std::env::var("BD_COMPLETE_FUZZ_SEED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_SEED);
+        (Rng(seed.max(1)), seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}
+
+fn iterations() -> usize {
+    std::env::var("BD_COMPLETE_FUZZ_ITERS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(500)
+}
+
+/// Fragments that look like help output, plus hostile bytes: tabs, stray
+/// colons, wide and combining characters, escapes and lone brackets.
+const FRAGMENTS: &[&str] = &[
+    " ", "  ", "      ", "\t", "\n", "\n\n", ":", "-", "--", "-x", "--flag", " string",
+    " strings", " int", " duration", "[=\"x\"]", "(default ", "\"", ")", "(", "[", "]", "<",
+    ">", "...", "Usage:", "Flags:", "Global Flags:", "Aliases:", "Examples:",
+    "Available Commands:", "Note:", "Use \"bd [command] --help\"", "DEPRECATED", "é", "日本語",
+    "\u{3000}", "\u{301}", "🙂", "\x1b[1m", "\x1b]8;;", "\x07", "\u{9b}", "\r", ",", "=",
+    "bd", "create", "new",
+];
+
+fn random_text(rng: &mut Rng) -> String {
+    let mut text = String::new();
+    for _ in 0..rng.below(80) {
+        text.push_str(rng.pick(FRAGMENTS));
+    }
+    text
+}
+
+#[test]
+fn test_random_input_never_panics() {
+    let (mut rng, seed) = Rng::from_env();
+    for _ in 0..iterations() {
+        let text = random_text(&mut rng);
+        let result = std::panic::catch_unwind(|| {
+            parse_help_output(&text);
+            for line in text.lines() {
+                parse_flag_line(line);
+                split_flag_description(line);
+                parse_command_line(line);
+                parse_usage_args(line);
+            }
+        });
+        assert!(result.is_ok(), "seed {seed}: parser panicked on {text:?}");
+    }
+}
+
+const WORDS: &[&str] = &[
+    "Show", "issue", "details", "for", "the", "given", "ID", "naïve", "über", "日本語",
+    "🙂", "with", "a:b", "path/to/file", "0-4", "P0-P4", "key=value", "'quoted'", "x,y",
+    "[optional]", "<arg>", "Note:", "e.g.:",
+];
+
+fn random_sentence(rng: &mut Rng) -> String {
+    let count = 1 + rng.below(8);
+    (0..count)
+        .map(|_| rng.pick(WORDS))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn random_name(rng: &mut Rng, taken: &mut Vec<String>) -> String {
+    const HEAD: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+    const TAIL: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789-";
+    loop {
+        let mut name = String::new();
+        name.push(rng.pick(HEAD) as char);
+        for _ in 0..rng.below(12) {
+            name.push(rng.pick(TAIL) as char);
+        }
+        if !taken.contains(&name) && !name.ends_with('-') {
+            taken.push(name.clone());
+            return name;
+        }
+    }
+}
+
+const TYPE_NAMES: &[&str] = &[
+    "string", "strings", "stringArray", "int", "int64", "ints", "uint", "float", "duration",
+    "bool", "ip", "ipNet", "bytesHex", "stringToString", "count", "path",
+];
+
+/// A random command: short/long description (possibly with lines ending in
+/// ':'), a usage line, aliases, subcommands and flags.
+fn random_command(rng: &mut Rng) -> (Command, Vec<Flag>) {
+    let mut cmd = Command::new("", random_sentence(rng));
+    let mut long = vec![cmd.description.clone()];
+    for _ in 0..rng.below(3) {
+        long.push(String::new());
+        for _ in 0..1 + rng.below(3) {
+            // Lines ending in ':' must not be taken for section headers
+            let mut line = random_sentence(rng);
+            if rng.chance(30) {
+                line.push(':');
+            }
+            long.push(line);
+        }
+    }
+    cmd.long_description = Some(long.join("\n"));
+    cmd.usage = Some("bd thing [flags]".to_string());
+
+    let mut names = Vec::new();
+    if rng.chance(40) {
+        cmd.aliases = (0..1 + rng.below(2)).map(|_| random_name(rng, &mut names)).collect();
+    }
+    for _ in 0..rng.below(6) {
+        let name = random_name(rng, &mut names);
+        let mut sub = Command::new(name.clone(), random_sentence(rng));
+        sub.group = Some("Available Commands".to_string());
+        cmd.subcommands.insert(name, sub);
+    }
+
+    let mut flag_names = Vec::new();
+    let mut shorts: Vec<char> = Vec::new();
+    for _ in 0..rng.below(8) {
+        let short = if rng.chance(50) {
+            let c = (b'a' + rng.below(26) as u8) as char;
+            (!shorts.contains(&c)).then(|| {
+                shorts.push(c);
+                c
+            })
+        } else {
+            None
+        };
+        let value_type = rng
+            .chance(60)
+            .then(|| ValueKind::from_type_name(rng.pick(TYPE_NAMES)));
+        let mut description = random_sentence(rng);
+        let default = rng.chance(30).then(|| "x".to_string());
+        if let Some(d) = &default {
+            description.push_str(&format!(" (default \"{d}\")"));
+        }
+        cmd.flags.push(Flag {
+            long: random_name(rng, &mut flag_names),
+            short,
+            description,
+            value_type,
+            default,
+            deprecated: false,
+            hidden: false,
+            completion: None,
+        });
+    }
+
+    let globals = (0..rng.below(3))
+        .map(|_| Flag {
+            long: random_name(rng, &mut flag_names),
+            short: None,
+            description: random_sentence(rng),
+            value_type: None,
+            default: None,
+            deprecated: false,
+            hidden: false,
+            completion: None,
+        })
+        .collect();
+    (cmd, globals)
+}
+
+/// Render flags the way pflag does: padded to the longest flag plus three spaces.
+fn render_flags(flags: &[Flag]) -> Vec<String> {
+    let heads: Vec<String> = flags
+        .iter()
+        .map(|f| {
+            let mut head = match f.short {
+                Some(s) => format!("  -{s}, --{}", f.long),
+                None => format!("      --{}", f.long),
+            };
+            if let Some(kind) = &f.value_type {
+                head.push(' ');
+                head.push_str(kind.type_name());
+            }
+            head
+        })
+        .collect();
+    let width = heads.iter().map(|h| h.chars().count()).max().unwrap_or(0) + 3;
+    heads
+        .iter()
+        .zip(flags)
+        .map(|(head, f)| format!("{head:width$}{}", f.description))
+        .collect()
+}
+
+/// Render a command as cobra's default help template would.
+fn render_help(cmd: &Command, globals: &[Flag]) -> String {
+    let mut out = vec![cmd.long_description.clone().unwrap_or_default(), String::new()];
+    out.push("Usage:".to_string());
+    out.push(format!("  {}", cmd.usage.as_deref().unwrap_or("")));
+    out.push(String::new());
+    if !cmd.aliases.is_empty() {
+        out.push("Aliases:".to_string());
+        out.push(format!("  thing, {}", cmd.aliases.join(", ")));
+        out.push(String::new());
+    }
+    if !cmd.subcommands.is_empty() {
+        out.push("Available Commands:".to_string());
+        let pad = cmd.subcommands.keys().map(|k| k.len()).max().unwrap_or(0).max(11);
+        for (name, sub) in &cmd.subcommands {
+            out.push(format!("  {name:pad$} {}", sub.description));
+        }
+        out.push(String::new());
+    }
+    if !cmd.flags.is_empty() {
+        out.push("Flags:".to_string());
+        out.extend(render_flags(&cmd.flags));
+        out.push(String::new());
+    }
+    if !globals.is_empty() {
+        out.push("Global Flags:".to_string());
+        out.extend(render_flags(globals));
+        out.push(String::new());
+    }
+    if !cmd.subcommands.is_empty() {
+        out.push("Use \"bd thing [command] --help\" for more information about a command.".to_string());
+    }
+    out.join("\n")
+}
+
+#[test]
+fn test_rendered_help_round_trips() {
+    let (mut rng, seed) = Rng::from_env();
+    for _ in 0..iterations() {
+        let (expected, globals) = random_command(&mut rng);
+        let text = render_help(&expected, &globals);
+        let (parsed, parsed_globals, _) = parse_help_output(&text);
+
+        let context = format!("seed {seed}, help text:\n{text}");
+        assert_eq!(parsed.description, expected.description, "{context}");
+        assert_eq!(parsed.long_description, expected.long_description, "{context}");
+        assert_eq!(parsed.usage, expected.usage, "{context}");
+        assert_eq!(parsed.aliases, expected.aliases, "{context}");
+        assert_eq!(parsed.subcommands, expected.subcommands, "{context}");
+        assert_eq!(parsed.flags, expected.flags, "{context}");
+        assert_eq!(parsed_globals, globals, "{context}");
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! External commands, git-style: an executable `bd-foo` on `$PATH` runs as
+//! `bd foo` but never shows up in `bd --help`. Each one found is asked for
+//! its own `--help` and added to the tree under a "Plugins" group.
+
+use crate::command_tree::{Command, CommandGroup, CommandTree};
+use crate::error::BdCompleteError;
+use crate::parser::{build_command_tree_with_diagnostics, Diagnostic, HelpSource};
+use crate::quote;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// The group plugins are listed under.
+pub const PLUGIN_GROUP: &str = "Plugins";
+
+/// Executables named `<root>-<name>` in the directories of `path` (a
+/// `$PATH`-style list), by name. The first directory wins, as in the shell.
+pub fn find_plugins(root: &str, path: &OsStr) -> BTreeMap<String, PathBuf> {
+    let prefix = format!("{root}-");
+    let mut plugins = BTreeMap::new();
+    for dir in std::env::split_paths(path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+            let stem = file_name
+                .strip_suffix(std::env::consts::EXE_SUFFIX)
+                .unwrap_or(file_name);
+            // bd-complete itself is not a bd command
+            if stem == env!("CARGO_PKG_NAME") {
+                continue;
+            }
+            let Some(name) = stem
+                .strip_prefix(&prefix)
+                .filter(|n| quote::is_safe_word(n))
+            else {
+                continue;
+            };
+            let path = entry.path();
+            if !plugins.contains_key(name) && is_executable(&path) {
+                plugins.insert(name.to_string(), path);
+            }
+        }
+    }
+    plugins
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Introspect each plugin and add it as a top-level command in the
+/// "Plugins" group. Built-in commands win over plugins of the same name, or
+/// named like one of their aliases, as they do when bd runs. Plugins whose help can't be run, or whose names
+/// can't be written safely into a script, are left out and returned with
+/// the reason, so one stray executable doesn't break completion for bd.
+pub fn add_plugins(
+    tree: &mut CommandTree,
+    plugins: &BTreeMap<String, PathBuf>,
+) -> Vec<(String, BdCompleteError)> {
+    add_plugins_with_diagnostics(tree, plugins, &mut Vec::new())
+}
+
+/// Like [`add_plugins`], recording the help lines the parser skipped or
+/// wasn't sure about in the plugins that were added in `diagnostics`.
+pub fn add_plugins_with_diagnostics(
+    tree: &mut CommandTree,
+    plugins: &BTreeMap<String, PathBuf>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<(String, BdCompleteError)> {
+    add_plugins_built_by(tree, plugins, diagnostics, |binary, diagnostics| {
+        let source = HelpSource::Binary(binary.to_string());
+        build_command_tree_with_diagnostics(&source, diagnostics)
+    })
+}
+
+/// [`add_plugins_with_diagnostics`], building each plugin's tree from its
+/// binary with `build`.
+pub(crate) fn add_plugins_built_by(
+    tree: &mut CommandTree,
+    plugins: &BTreeMap<String, PathBuf>,
+    diagnostics: &mut Vec<Diagnostic>,
+    mut build: impl FnMut(&str, &mut Vec<Diagnostic>) -> Result<CommandTree, BdCompleteError>,
+) -> Vec<(String, BdCompleteError)> {
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+    for (name, path) in plugins {
+        let taken = tree.root.subcommands.contains_key(name)
+            || tree.root.subcommands.values().any(|c| c.aliases.contains(name));
+        if taken {
+            continue;
+        }
+        // Only reported for plugins that make it into the tree
+        let mut plugin_diagnostics = Vec::new();
+        let mut plugin = match build(&path.to_string_lossy(), &mut plugin_diagnostics) {
+            Ok(plugin) => plugin,
+            Err(e) => {
+                skipped.push((name.clone(), e));
+                continue;
+            }
+        };
+        plugin.root.name = name.clone();
+        if let Err(e) = quote::check_tree(&plugin) {
+            skipped.push((name.clone(), e));
+            continue;
+        }
+        let mut cmd = plugin.root;
+        let binary = format!("{}-{name}", tree.root.name);
+        rewrite_usage(&mut cmd, &binary, &format!("{} {name}", tree.root.name));
+        cmd.group = Some(PLUGIN_GROUP.to_string());
+        tree.root.subcommands.insert(name.clone(), cmd);
+        added.push(name.clone());
+        diagnostics.append(&mut plugin_diagnostics);
+    }
+    if !added.is_empty() {
+        tree.groups.push(CommandGroup {
+            name: PLUGIN_GROUP.to_string(),
+            commands: added,
+        });
+    }
+    skipped
+}
+
+/// Usage lines name the plugin's binary (`bd-sync [flags]`); they should
+/// show how it's run through bd.
+fn rewrite_usage(cmd: &mut Command, binary: &str, command: &str) {
+    if let Some(usage) = &mut cmd.usage {
+        *usage = usage.replace(binary, command);
+    }
+    for sub in cmd.subcommands.values_mut() {
+        rewrite_usage(sub, binary, command);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("bd-complete-plugins-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A script that prints `help` for `--help`.
+    fn write_plugin(dir: &Path, file: &str, help: &str, mode: u32) -> PathBuf {
+        let path = dir.join(file);
+        let script = format!("#!/bin/sh\ncat <<'EOF'\n{help}\nEOF\n");
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        path
+    }
+
+    const SYNC_HELP: &str = "Sync issues with a remote tracker\n\n\
+        Usage:\n  bd-sync [flags]\n\n\
+        Flags:\n  -h, --help            help for bd-sync\n      --remote string   Remote to sync with\n";
+
+    #[test]
+    fn test_find_plugins_on_path() {
+        let first = scratch("first");
+        let second = scratch("second");
+        let sync = write_plugin(&first, "bd-sync", SYNC_HELP, 0o755);
+        write_plugin(&second, "bd-sync", "shadowed", 0o755);
+        write_plugin(&second, "bd-lint", "Lint issues", 0o755);
+        write_plugin(&second, "bd-notes", "not executable", 0o644);
+        write_plugin(&second, "bd-complete", "this tool", 0o755);
+        write_plugin(&second, "bd-a;b", "unsafe name", 0o755);
+        write_plugin(&second, "git-sync", "another tool", 0o755);
+
+        let path = std::env::join_paths([&first, &second]).unwrap();
+        let plugins = find_plugins("bd", &path);
+        assert_eq!(plugins.keys().collect::<Vec<_>>(), ["lint", "sync"]);
+        assert_eq!(plugins["sync"], sync);
+
+        let _ = std::fs::remove_dir_all(&first);
+        let _ = std::fs::remove_dir_all(&second);
+    }
+
+    #[test]
+    fn test_add_plugins_to_a_group() {
+        let dir = scratch("add");
+        write_plugin(&dir, "bd-sync", SYNC_HELP, 0o755);
+        write_plugin(&dir, "bd-create", "Not the real create", 0o755);
+        write_plugin(&dir, "bd-new", "Not the real create either", 0o755);
+        let plugins = find_plugins("bd", dir.as_os_str());
+
+        let mut tree = CommandTree::new(Command::new("bd", ""));
+        let mut create = Command::new("create", "Create an issue");
+        create.aliases.push("new".to_string());
+        tree.root.subcommands.insert("create".to_string(), create);
+        assert!(add_plugins(&mut tree, &plugins).is_empty());
+
+        let sync = &tree.root.subcommands["sync"];
+        assert_eq!(sync.name, "sync");
+        assert_eq!(sync.description, "Sync issues with a remote tracker");
+        assert_eq!(sync.group.as_deref(), Some(PLUGIN_GROUP));
+        assert_eq!(sync.flags.len(), 2);
+        assert_eq!(sync.flags[1].long, "remote");
+        assert_eq!(sync.usage.as_deref(), Some("bd sync [flags]"));
+        // The built-in command is kept, and its alias isn't shadowed
+        assert_eq!(
+            tree.root.subcommands["create"].description,
+            "Create an issue"
+        );
+        assert!(!tree.root.subcommands.contains_key("new"));
+        assert_eq!(
+            tree.groups,
+            [CommandGroup {
+                name: PLUGIN_GROUP.to_string(),
+                commands: vec!["sync".to_string()],
+            }]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_plugin_diagnostics() {
+        let dir = scratch("diagnostics");
+        let sync = SYNC_HELP.replace("Flags:\n", "Flags:\n  oops, not a flag\n");
+        write_plugin(&dir, "bd-sync", &sync, 0o755);
+        let hostile = "Push things\n\nUsage:\n  bd-push [command]\n\n\
+            Available Commands:\n  push$(id)   Push and run something\n  ???\n";
+        write_plugin(&dir, "bd-push", hostile, 0o755);
+        let plugins = find_plugins("bd", dir.as_os_str());
+
+        let mut tree = CommandTree::new(Command::new("bd", ""));
+        let mut diagnostics = Vec::new();
+        let skipped = add_plugins_with_diagnostics(&mut tree, &plugins, &mut diagnostics);
+
+        assert_eq!(skipped.len(), 1);
+        // Only the plugin that was added reports its lines
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].text, "  oops, not a flag");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_add_plugins_skips_unsafe_plugins() {
+        let dir = scratch("hostile");
+        write_plugin(&dir, "bd-sync", SYNC_HELP, 0o755);
+        let hostile = "Push things\n\n\
+            Usage:\n  bd-push [command]\n\n\
+            Available Commands:\n  push$(id)   Push and run something\n\n\
+            Flags:\n  -h, --help   help for bd-push\n";
+        write_plugin(&dir, "bd-push", hostile, 0o755);
+        let plugins = find_plugins("bd", dir.as_os_str());
+
+        let mut tree = CommandTree::new(Command::new("bd", ""));
+        let skipped = add_plugins(&mut tree, &plugins);
+
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, "push");
+        assert!(
+            matches!(&skipped[0].1, BdCompleteError::UnsafeIdentifier { name, .. } if name == "push$(id)"),
+            "{:?}",
+            skipped[0].1
+        );
+        assert!(!tree.root.subcommands.contains_key("push"));
+        assert!(tree.root.subcommands.contains_key("sync"));
+        assert_eq!(tree.groups[0].commands, ["sync"]);
+        assert!(quote::check_tree(&tree).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
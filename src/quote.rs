@@ -0,0 +1,257 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Quoting for text interpolated into generated scripts.
+//!
+//! Names end up unquoted in bash function names and fish conditions, so
+//! they are validated against a conservative word syntax instead of being
+//! quoted. Bash `case` patterns and `compgen -W` word lists quote them as
+//! well. Free text such as descriptions goes through the per-shell quoting
+//! functions.
+
+use crate::command_tree::{Command, CommandTree, Flag, ValueCompletion};
+use crate::error::BdCompleteError;
+use crate::options::ShellAlias;
+
+/// Whether `s` can be written into a script as a bare word: an ASCII letter,
+/// digit or `_`, followed by letters, digits and `_ . + -`.
+///
+/// This excludes whitespace, quotes, globs, `$`, `;`, `|` and the `:` that
+/// the bash walker uses to join command paths and flags.
+pub fn is_safe_word(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphanumeric() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-'))
+}
+
+/// Check that every command, alias and flag name in the tree is a safe word,
+/// so generators can interpolate them without quoting.
+pub fn check_tree(tree: &CommandTree) -> Result<(), BdCompleteError> {
+    check_word("command", &tree.root.name, "")?;
+    for flag in &tree.global_flags {
+        check_flag(flag, &tree.root.name)?;
+    }
+    check_command(&tree.root, &tree.root.name)
+}
+
+/// Check that the names of shell aliases are safe words too. They are
+/// validated by [`ShellAlias::parse`], but the fields can be set directly.
+pub(crate) fn check_aliases(aliases: &[ShellAlias]) -> Result<(), BdCompleteError> {
+    for alias in aliases {
+        check_word("alias", &alias.name, "")?;
+    }
+    Ok(())
+}
+
+fn check_command(cmd: &Command, path: &str) -> Result<(), BdCompleteError> {
+    for flag in &cmd.flags {
+        check_flag(flag, path)?;
+    }
+    for (name, sub) in &cmd.subcommands {
+        check_word("command", name, path)?;
+        let sub_path = format!("{path} {name}");
+        for alias in &sub.aliases {
+            check_word("alias", alias, &sub_path)?;
+        }
+        check_command(sub, &sub_path)?;
+    }
+    Ok(())
+}
+
+fn check_flag(flag: &Flag, path: &str) -> Result<(), BdCompleteError> {
+    check_word("flag", &flag.long, path)?;
+    // Listed values go into word lists just like names
+    if let Some(ValueCompletion::Values(values)) = &flag.completion {
+        for value in values {
+            check_word("value", value, path)?;
+        }
+    }
+    match flag.short {
+        Some(s) if !s.is_ascii_alphanumeric() => check_word("flag", &s.to_string(), path),
+        _ => Ok(()),
+    }
+}
+
+fn check_word(kind: &'static str, word: &str, path: &str) -> Result<(), BdCompleteError> {
+    if is_safe_word(word) {
+        return Ok(());
+    }
+    Err(BdCompleteError::UnsafeIdentifier {
+        kind,
+        name: word.to_string(),
+        path: path.to_string(),
+    })
+}
+
+/// Quote a word for bash: safe words are left bare, anything else is
+/// single-quoted with embedded quotes written as `'\''`.
+pub fn bash(s: &str) -> String {
+    if is_safe_word(s) {
+        return s.to_string();
+    }
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Quote text for fish as a single-quoted string.
+///
+/// Inside fish single quotes only `\\` and `\'` are escapes and `$` or `(`
+/// are literal. Line breaks and tabs become spaces and other control
+/// characters are dropped, so the result always stays on one line.
+pub fn fish(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('\'');
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str(r"\\"),
+            '\'' => quoted.push_str(r"\'"),
+            '\n' | '\r' | '\t' => quoted.push(' '),
+            c if c.is_control() => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTILE: &[&str] = &[
+        "foo;rm",
+        "$(touch pwned)",
+        "`id`",
+        "a b",
+        "it's",
+        "\"x\"",
+        "*",
+        "a|b",
+        "x)",
+        "dep:add",
+        "line\nbreak",
+        "-rf",
+        "",
+        "naïve",
+    ];
+
+    #[test]
+    fn test_safe_words() {
+        for word in ["bd", "create", "close-eligible", "set_state", "v2", "c++", "x.y"] {
+            assert!(is_safe_word(word), "{word}");
+        }
+        for word in HOSTILE {
+            assert!(!is_safe_word(word), "{word:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_tree_rejects_hostile_names() {
+        for word in HOSTILE {
+            let mut tree = CommandTree::new(Command::new("bd", ""));
+            tree.root
+                .subcommands
+                .insert(word.to_string(), Command::new(*word, ""));
+            let err = check_tree(&tree).unwrap_err();
+            match &err {
+                BdCompleteError::UnsafeIdentifier { kind, name, .. } => {
+                    assert_eq!((*kind, name.as_str()), ("command", *word));
+                }
+                _ => panic!("{err:?}"),
+            }
+
+            let mut tree = CommandTree::new(Command::new("bd", ""));
+            let mut create = Command::new("create", "");
+            create.aliases.push(word.to_string());
+            tree.root.subcommands.insert("create".to_string(), create);
+            assert!(check_tree(&tree).is_err(), "alias {word:?}");
+
+            let mut tree = CommandTree::new(Command::new("bd", ""));
+            tree.global_flags.push(Flag::new(*word, ""));
+            assert!(check_tree(&tree).is_err(), "flag {word:?}");
+
+            tree.global_flags[0].long = "status".to_string();
+            let values = vec!["open".to_string(), word.to_string()];
+            tree.global_flags[0].completion = Some(ValueCompletion::Values(values));
+            assert!(check_tree(&tree).is_err(), "value {word:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_tree_names_the_location() {
+        let mut tree = CommandTree::new(Command::new("bd", ""));
+        let mut dep = Command::new("dep", "");
+        dep.subcommands
+            .insert("a;b".to_string(), Command::new("a;b", ""));
+        tree.root.subcommands.insert("dep".to_string(), dep);
+
+        let err = check_tree(&tree).unwrap_err();
+        assert!(err.to_string().contains("\"a;b\" in `bd dep`"), "{err}");
+    }
+
+    #[test]
+    fn test_check_tree_accepts_descriptions_and_defaults() {
+        let mut tree = CommandTree::new(Command::new("bd", "Tracks $work; `really`"));
+        tree.global_flags.push(Flag {
+            short: Some('d'),
+            default: Some("$HOME/.beads".to_string()),
+            ..Flag::new("db", "Path (default \"$HOME/.beads\")")
+        });
+        assert!(check_tree(&tree).is_ok());
+
+        tree.global_flags[0].short = Some(';');
+        assert!(check_tree(&tree).is_err());
+    }
+
+    #[test]
+    fn test_check_aliases() {
+        let alias = |name: &str| ShellAlias {
+            name: name.to_string(),
+            prefix: vec!["--db".to_string(), "work db".to_string()],
+        };
+        assert!(check_aliases(&[alias("bdw"), alias("b")]).is_ok());
+        for word in HOSTILE {
+            let err = check_aliases(&[alias("bdw"), alias(word)]).unwrap_err();
+            match &err {
+                BdCompleteError::UnsafeIdentifier { kind, name, .. } => {
+                    assert_eq!((*kind, name.as_str()), ("alias", *word));
+                }
+                _ => panic!("{err:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bash_quoting() {
+        assert_eq!(bash("create"), "create");
+        assert_eq!(bash("dep add"), "'dep add'");
+        assert_eq!(bash(""), "''");
+        assert_eq!(bash("it's"), r"'it'\''s'");
+        assert_eq!(bash("$(id)"), "'$(id)'");
+    }
+
+    #[test]
+    fn test_fish_quoting() {
+        assert_eq!(fish("Show issue"), "'Show issue'");
+        assert_eq!(fish("it's"), r"'it\'s'");
+        assert_eq!(fish(r"C:\path\"), r"'C:\\path\\'");
+        assert_eq!(fish("a\nb\tc"), "'a b c'");
+        assert_eq!(fish("$HOME (id)"), "'$HOME (id)'");
+        assert_eq!(fish("bell\x07"), "'bell'");
+        // A trailing backslash can't escape the closing quote
+        assert!(fish("\\").ends_with(r"\\'"));
+    }
+}
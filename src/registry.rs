@@ -0,0 +1,206 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generators by name, for callers that pick an output format at run time
+//! (`--shell bash`, `--format fig-ts`).
+
+use crate::bash::generate_bash_completion;
+use crate::carapace::generate_carapace_spec;
+use crate::command_tree::CommandTree;
+use crate::docs::{generate_html_docs, generate_markdown_docs};
+use crate::error::BdCompleteError;
+use crate::fig::{generate_fig_json, generate_fig_typescript};
+use crate::fish::generate_fish_completion;
+use crate::options::GenerateOptions;
+use std::io::Write;
+
+/// Writes one output file for a tree.
+pub type GenerateFn =
+    fn(&CommandTree, &GenerateOptions, &mut dyn Write) -> Result<(), BdCompleteError>;
+
+/// What a generator produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GeneratorKind {
+    /// A shell completion script (`generate --shell`)
+    Completion,
+    /// A command reference (`docs --format`)
+    Docs,
+    /// A completion spec for another tool (`export --format`)
+    Spec,
+}
+
+impl GeneratorKind {
+    /// How its output is named in messages, e.g. "completion script".
+    pub fn noun(self) -> &'static str {
+        match self {
+            GeneratorKind::Completion => "completion script",
+            GeneratorKind::Docs => "documentation",
+            GeneratorKind::Spec => "completion spec",
+        }
+    }
+}
+
+/// A named output format.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Generator {
+    /// The name it's chosen by, e.g. "bash" or "fig-ts"
+    pub name: &'static str,
+    pub kind: GeneratorKind,
+    pub generate: GenerateFn,
+}
+
+impl Generator {
+    pub fn new(name: &'static str, kind: GeneratorKind, generate: GenerateFn) -> Self {
+        Generator {
+            name,
+            kind,
+            generate,
+        }
+    }
+}
+
+/// The generators available by name. `default()` holds the built-in ones;
+/// others can be registered next to them or in their place.
+#[derive(Debug, Clone)]
+pub struct GeneratorRegistry {
+    generators: Vec<Generator>,
+}
+
+impl Default for GeneratorRegistry {
+    fn default() -> Self {
+        let mut registry = GeneratorRegistry::new();
+        for (name, kind, generate) in [
+            ("bash", GeneratorKind::Completion, generate_bash_completion as GenerateFn),
+            ("fish", GeneratorKind::Completion, generate_fish_completion),
+            ("markdown", GeneratorKind::Docs, generate_markdown_docs),
+            ("html", GeneratorKind::Docs, generate_html_docs),
+            ("fig", GeneratorKind::Spec, generate_fig_json),
+            ("fig-ts", GeneratorKind::Spec, generate_fig_typescript),
+            ("carapace", GeneratorKind::Spec, generate_carapace_spec),
+        ] {
+            registry.register(Generator::new(name, kind, generate));
+        }
+        registry
+    }
+}
+
+impl GeneratorRegistry {
+    /// A registry with no generators.
+    pub fn new() -> Self {
+        GeneratorRegistry {
+            generators: Vec::new(),
+        }
+    }
+
+    /// Add a generator, replacing any of the same name.
+    pub fn register(&mut self, generator: Generator) {
+        match self.generators.iter_mut().find(|g| g.name == generator.name) {
+            Some(existing) => *existing = generator,
+            None => self.generators.push(generator),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Generator> {
+        self.generators.iter().find(|g| g.name == name)
+    }
+
+    /// Names of the generators of one kind, in registration order.
+    pub fn names(&self, kind: GeneratorKind) -> Vec<&'static str> {
+        self.generators
+            .iter()
+            .filter(|g| g.kind == kind)
+            .map(|g| g.name)
+            .collect()
+    }
+
+    /// Run the generator called `name`.
+    pub fn generate(
+        &self,
+        name: &str,
+        tree: &CommandTree,
+        opts: &GenerateOptions,
+        out: &mut dyn Write,
+    ) -> Result<(), BdCompleteError> {
+        let generator = self
+            .get(name)
+            .ok_or_else(|| BdCompleteError::UnknownGenerator(name.to_string()))?;
+        (generator.generate)(tree, opts, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_tree::Command;
+
+    #[test]
+    fn test_builtin_generators() {
+        let registry = GeneratorRegistry::default();
+        assert_eq!(registry.names(GeneratorKind::Completion), ["bash", "fish"]);
+        assert_eq!(registry.names(GeneratorKind::Docs), ["markdown", "html"]);
+        assert_eq!(
+            registry.names(GeneratorKind::Spec),
+            ["fig", "fig-ts", "carapace"]
+        );
+
+        let tree = CommandTree::new(Command::new("bd", "An issue tracker"));
+        let mut buf = Vec::new();
+        registry
+            .generate("fish", &tree, &GenerateOptions::default(), &mut buf)
+            .unwrap();
+        assert!(String::from_utf8(buf).unwrap().contains("complete -c bd -f"));
+
+        let err = registry
+            .generate("zsh", &tree, &GenerateOptions::default(), &mut Vec::new())
+            .unwrap_err();
+        assert!(matches!(err, BdCompleteError::UnknownGenerator(ref name) if name == "zsh"));
+    }
+
+    #[test]
+    fn test_register_replaces_by_name() {
+        fn names_only(
+            tree: &CommandTree,
+            _: &GenerateOptions,
+            out: &mut dyn Write,
+        ) -> Result<(), BdCompleteError> {
+            writeln!(out, "{}", tree.root.name).map_err(BdCompleteError::Write)
+        }
+
+        let mut registry = GeneratorRegistry::default();
+        registry.register(Generator {
+            name: "bash",
+            kind: GeneratorKind::Completion,
+            generate: names_only,
+        });
+        registry.register(Generator {
+            name: "names",
+            kind: GeneratorKind::Spec,
+            generate: names_only,
+        });
+        assert_eq!(registry.names(GeneratorKind::Completion), ["bash", "fish"]);
+        assert_eq!(
+            registry.names(GeneratorKind::Spec),
+            ["fig", "fig-ts", "carapace", "names"]
+        );
+
+        let tree = CommandTree::new(Command::new("bd", ""));
+        let mut buf = Vec::new();
+        registry
+            .generate("bash", &tree, &GenerateOptions::default(), &mut buf)
+            .unwrap();
+        assert_eq!(buf, b"bd\n");
+    }
+}
@@ -0,0 +1,357 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A small TOML reader, enough for the overrides file.
+//!
+//! Tables (`[command."dep add"]`), bare and quoted keys, and values that are
+//! strings, booleans or arrays of them. Numbers, dates, inline tables, arrays
+//! of tables, dotted keys and multi-line strings are rejected.
+
+use crate::error::BdCompleteError;
+
+/// A value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Toml {
+    Str(String),
+    Bool(bool),
+    Array(Vec<Toml>),
+}
+
+/// A `[table]` and its keys in file order. Keys before the first header
+/// belong to a table with an empty path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub path: Vec<String>,
+    /// Line of the header, counting from 1
+    pub line: usize,
+    /// Key, value and the line the key is on
+    pub entries: Vec<(String, Toml, usize)>,
+}
+
+/// Read a document into its tables.
+pub fn parse_toml(text: &str) -> Result<Vec<Table>, BdCompleteError> {
+    let mut reader = Reader {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut tables = vec![Table {
+        path: Vec::new(),
+        line: 1,
+        entries: Vec::new(),
+    }];
+    loop {
+        reader.skip_trivia();
+        match reader.peek() {
+            None => break,
+            Some('[') => {
+                let line = reader.line;
+                reader.bump();
+                let mut path = Vec::new();
+                loop {
+                    reader.skip_blanks();
+                    if reader.peek() == Some('[') {
+                        return Err(reader.error("arrays of tables aren't supported"));
+                    }
+                    path.push(reader.key()?);
+                    reader.skip_blanks();
+                    match reader.bump() {
+                        Some('.') => {}
+                        Some(']') => break,
+                        _ => return Err(reader.error("expected '.' or ']' in table header")),
+                    }
+                }
+                if tables.iter().any(|t| t.path == path) {
+                    return Err(reader.error(&format!("table [{}] defined twice", path.join("."))));
+                }
+                reader.end_of_line()?;
+                tables.push(Table {
+                    path,
+                    line,
+                    entries: Vec::new(),
+                });
+            }
+            Some(_) => {
+                let line = reader.line;
+                let key = reader.key()?;
+                reader.skip_blanks();
+                match reader.bump() {
+                    Some('=') => {}
+                    Some('.') => return Err(reader.error("dotted keys aren't supported")),
+                    _ => return Err(reader.error(&format!("expected '=' after {key:?}"))),
+                }
+                reader.skip_blanks();
+                let value = reader.value()?;
+                reader.end_of_line()?;
+                let table = tables.last_mut().unwrap();
+                if table.entries.iter().any(|(k, _, _)| *k == key) {
+                    return Err(reader.error(&format!("key {key:?} set twice")));
+                }
+                table.entries.push((key, value, line));
+            }
+        }
+    }
+    if tables[0].entries.is_empty() {
+        tables.remove(0);
+    }
+    Ok(tables)
+}
+
+struct Reader {
+    chars: Vec<char>,
+    pos: usize,
+    /// Line of the next character, counting from 1
+    line: usize,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> BdCompleteError {
+        BdCompleteError::InvalidOverrides {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+
+    /// Skip spaces and tabs.
+    fn skip_blanks(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    /// Skip a `#` comment, leaving the line break.
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skip blanks, comments and line breaks.
+    fn skip_trivia(&mut self) {
+        loop {
+            self.skip_blanks();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.bump();
+                }
+                Some('\r') if self.chars.get(self.pos + 1) == Some(&'\n') => {
+                    self.bump();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Finish a header or key: an optional comment, then a line break.
+    fn end_of_line(&mut self) -> Result<(), BdCompleteError> {
+        self.skip_blanks();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\n') => Ok(()),
+            Some('\r') if self.chars.get(self.pos + 1) == Some(&'\n') => Ok(()),
+            Some(c) => Err(self.error(&format!("unexpected {c:?} after value"))),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, BdCompleteError> {
+        if matches!(self.peek(), Some('"' | '\'')) {
+            return self.string();
+        }
+        let mut key = String::new();
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                break;
+            }
+            key.push(c);
+            self.bump();
+        }
+        if key.is_empty() {
+            return Err(self.error("expected a key"));
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<Toml, BdCompleteError> {
+        match self.peek() {
+            Some('"' | '\'') => self.string().map(Toml::Str),
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_trivia();
+                    if self.peek() == Some(']') {
+                        self.bump();
+                        return Ok(Toml::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip_trivia();
+                    match self.bump() {
+                        Some(',') => {}
+                        Some(']') => return Ok(Toml::Array(items)),
+                        _ => return Err(self.error("expected ',' or ']' in array")),
+                    }
+                }
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = self.peek().filter(|c| c.is_ascii_alphanumeric()) {
+                    word.push(c);
+                    self.bump();
+                }
+                match word.as_str() {
+                    "true" => Ok(Toml::Bool(true)),
+                    "false" => Ok(Toml::Bool(false)),
+                    _ => Err(self.error("expected a string, true, false or an array")),
+                }
+            }
+        }
+    }
+
+    /// A basic (`"..."`, with escapes) or literal (`'...'`) string.
+    fn string(&mut self) -> Result<String, BdCompleteError> {
+        let quote = self.bump().unwrap();
+        if self.peek() == Some(quote) && self.chars.get(self.pos + 1) == Some(&quote) {
+            return Err(self.error("multi-line strings aren't supported"));
+        }
+        let mut s = String::new();
+        loop {
+            let c = match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(_) => self.bump().unwrap(),
+            };
+            match c {
+                c if c == quote => return Ok(s),
+                '\\' if quote == '"' => s.push(self.escape()?),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, BdCompleteError> {
+        let digits = match self.bump() {
+            Some('"') => return Ok('"'),
+            Some('\\') => return Ok('\\'),
+            Some('n') => return Ok('\n'),
+            Some('t') => return Ok('\t'),
+            Some('r') => return Ok('\r'),
+            Some('b') => return Ok('\u{8}'),
+            Some('f') => return Ok('\u{c}'),
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return Err(self.error("unknown escape in string")),
+        };
+        let hex: String = (0..digits).filter_map(|_| self.bump()).collect();
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .filter(|_| hex.len() == digits)
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error("invalid unicode escape in string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strs(items: &[&str]) -> Toml {
+        Toml::Array(items.iter().map(|s| Toml::Str(s.to_string())).collect())
+    }
+
+    #[test]
+    fn test_tables_and_values() {
+        let text = r#"
+# Leading comment
+[command."dep add"]   # trailing comment
+hidden = true
+description = "Tab\there \"quoted\" \u00e9"
+path = 'C:\no\escapes'
+
+[ command . create . flag . parent ]
+values = [
+  "epic",  # one per line
+  'task',
+]
+"#;
+        let tables = parse_toml(text).unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].path, ["command", "dep add"]);
+        assert_eq!(tables[0].line, 3);
+        assert_eq!(
+            tables[0].entries,
+            [
+                ("hidden".to_string(), Toml::Bool(true), 4),
+                (
+                    "description".to_string(),
+                    Toml::Str("Tab\there \"quoted\" é".to_string()),
+                    5
+                ),
+                (
+                    "path".to_string(),
+                    Toml::Str(r"C:\no\escapes".to_string()),
+                    6
+                ),
+            ]
+        );
+        assert_eq!(tables[1].path, ["command", "create", "flag", "parent"]);
+        assert_eq!(tables[1].entries[0].1, strs(&["epic", "task"]));
+    }
+
+    #[test]
+    fn test_keys_before_any_table() {
+        let tables = parse_toml("a = []\n\n[b]\n").unwrap();
+        assert_eq!(tables[0].path, Vec::<String>::new());
+        assert_eq!(tables[0].entries[0].1, strs(&[]));
+        assert_eq!(tables[1].path, ["b"]);
+        assert!(parse_toml("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        for (text, message) in [
+            ("[a]\nx = 1\n", "line 2: expected a string"),
+            ("[a]\n[a]\n", "line 2: table [a] defined twice"),
+            ("x = \"a\"\nx = \"b\"\n", "line 2: key \"x\" set twice"),
+            ("a.b = true\n", "line 1: dotted keys"),
+            ("[[a]]\n", "line 1: arrays of tables"),
+            ("x = \"open\n", "line 1: unterminated string"),
+            ("x = true false\n", "line 1: unexpected 'f' after value"),
+            ("x = [\"a\" \"b\"]\n", "line 1: expected ',' or ']'"),
+            ("x = \"\\q\"\n", "line 1: unknown escape"),
+            ("x = \"\"\"doc\"\"\"\n", "line 1: multi-line strings"),
+        ] {
+            let err = parse_toml(text).unwrap_err();
+            assert!(
+                matches!(err, BdCompleteError::InvalidOverrides { .. }),
+                "{err:?}"
+            );
+            assert!(err.to_string().starts_with(message), "{text:?}: {err}");
+        }
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The cache's on-disk form of a parsed command tree and its diagnostics.
+//!
+//! One record per line, fields separated by tabs, with `\\`, `\t`, `\n` and
+//! `\r` escaped. Commands come in depth-first order, each followed by its
+//! own details and then its flags; a flag's details follow its `flag` line.
+//! With tabs shown as `\t`:
+//!
+//! ```text
+//! bd-complete tree 1
+//! command\t0\tbd
+//! name\tbd
+//! description\tA lightweight issue tracker
+//! command\t1\tcreate
+//! name\tcreate
+//! alias\tnew
+//! flag\tpriority
+//! short\tp
+//! type\tstring
+//! global\tdb
+//! group\tWorking With Issues\tcreate
+//! diagnostic\tbd create\tFlags\t12\tskipped\tnot a flag entry\t  -x
+//! ```
+
+use crate::command_tree::{
+    Command, CommandGroup, CommandTree, Flag, PositionalArg, ValueCompletion, ValueKind,
+};
+use crate::parser::{known_reason, Diagnostic, DiagnosticKind};
+
+/// First line of every tree file; bump the number when the format changes.
+const HEADER: &str = "bd-complete tree 1";
+
+/// Write `tree` and the `diagnostics` its build reported.
+pub(crate) fn write_tree(tree: &CommandTree, diagnostics: &[Diagnostic]) -> String {
+    let mut out = String::new();
+    out.push_str(HEADER);
+    out.push('\n');
+    write_command(&tree.root.name, &tree.root, 0, &mut out);
+    for flag in &tree.global_flags {
+        write_flag("global", flag, &mut out);
+    }
+    for group in &tree.groups {
+        let mut fields = vec!["group", group.name.as_str()];
+        fields.extend(group.commands.iter().map(String::as_str));
+        record(&fields, &mut out);
+    }
+    for d in diagnostics {
+        let kind = match d.kind {
+            DiagnosticKind::Skipped => "skipped",
+            DiagnosticKind::Ambiguous => "ambiguous",
+        };
+        let line = d.line.to_string();
+        let fields = [
+            "diagnostic",
+            d.path.as_str(),
+            d.section.as_str(),
+            line.as_str(),
+            kind,
+            d.reason,
+            d.text.as_str(),
+        ];
+        record(&fields, &mut out);
+    }
+    out
+}
+
+/// Read a tree file back; None if it isn't one this version wrote.
+pub(crate) fn read_tree(text: &str) -> Option<(CommandTree, Vec<Diagnostic>)> {
+    let mut lines = text.lines();
+    if lines.next()? != HEADER {
+        return None;
+    }
+
+    // Commands still open, with their depth and subcommand key
+    let mut open: Vec<(usize, String, Command)> = Vec::new();
+    let mut global_flags: Vec<Flag> = Vec::new();
+    let mut groups = Vec::new();
+    let mut diagnostics = Vec::new();
+    // Whether detail records apply to the last flag or the open command
+    let mut in_flag = false;
+    let mut in_global = false;
+
+    for line in lines {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect::<Option<_>>()?;
+        let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+        match fields.as_slice() {
+            ["command", depth, key] => {
+                let depth: usize = depth.parse().ok()?;
+                close_to(&mut open, depth)?;
+                if open.len() != depth {
+                    return None;
+                }
+                open.push((depth, key.to_string(), Command::new(*key, "")));
+                in_flag = false;
+                in_global = false;
+            }
+            ["flag", long] if !in_global => {
+                open.last_mut()?.2.flags.push(Flag::new(*long, ""));
+                in_flag = true;
+            }
+            ["global", long] => {
+                global_flags.push(Flag::new(*long, ""));
+                in_flag = true;
+                in_global = true;
+            }
+            ["group", name, commands @ ..] => {
+                let commands = commands.iter().map(|c| c.to_string()).collect();
+                groups.push(CommandGroup::new(*name, commands));
+            }
+            ["diagnostic", path, section, line, kind, reason, text] => {
+                diagnostics.push(Diagnostic {
+                    path: path.to_string(),
+                    section: section.to_string(),
+                    line: line.parse().ok()?,
+                    text: text.to_string(),
+                    kind: match *kind {
+                        "skipped" => DiagnosticKind::Skipped,
+                        "ambiguous" => DiagnosticKind::Ambiguous,
+                        _ => return None,
+                    },
+                    reason: known_reason(reason)?,
+                });
+            }
+            fields if in_flag => {
+                let flag = match in_global {
+                    true => global_flags.last_mut()?,
+                    false => open.last_mut()?.2.flags.last_mut()?,
+                };
+                read_flag_detail(fields, flag)?;
+            }
+            fields => read_command_detail(fields, &mut open.last_mut()?.2)?,
+        }
+    }
+
+    close_to(&mut open, 1)?;
+    let (_, _, root) = open.pop()?;
+    let mut tree = CommandTree::new(root);
+    tree.global_flags = global_flags;
+    tree.groups = groups;
+    Some((tree, diagnostics))
+}
+
+fn write_command(key: &str, cmd: &Command, depth: usize, out: &mut String) {
+    record(&["command", &depth.to_string(), key], out);
+    record(&["name", &cmd.name], out);
+    if !cmd.description.is_empty() {
+        record(&["description", &cmd.description], out);
+    }
+    if let Some(long) = &cmd.long_description {
+        record(&["long-description", long], out);
+    }
+    for alias in &cmd.aliases {
+        record(&["alias", alias], out);
+    }
+    if let Some(usage) = &cmd.usage {
+        record(&["usage", usage], out);
+    }
+    for arg in &cmd.args {
+        let required = if arg.required { "required" } else { "optional" };
+        let variadic = if arg.variadic { "variadic" } else { "single" };
+        record(&["arg", &arg.name, required, variadic], out);
+    }
+    if let Some(examples) = &cmd.examples {
+        record(&["examples", examples], out);
+    }
+    if let Some(group) = &cmd.group {
+        record(&["in-group", group], out);
+    }
+    if cmd.deprecated {
+        record(&["deprecated"], out);
+    }
+    if cmd.hidden {
+        record(&["hidden"], out);
+    }
+    for flag in &cmd.flags {
+        write_flag("flag", flag, out);
+    }
+    for (key, sub) in cmd.subcommands.iter() {
+        write_command(key, sub, depth + 1, out);
+    }
+}
+
+fn write_flag(kind: &str, flag: &Flag, out: &mut String) {
+    record(&[kind, &flag.long], out);
+    if let Some(short) = flag.short {
+        record(&["short", &short.to_string()], out);
+    }
+    if !flag.description.is_empty() {
+        record(&["description", &flag.description], out);
+    }
+    if let Some(kind) = &flag.value_type {
+        record(&["type", kind.type_name()], out);
+    }
+    if let Some(default) = &flag.default {
+        record(&["default", default], out);
+    }
+    if flag.deprecated {
+        record(&["deprecated"], out);
+    }
+    if flag.hidden {
+        record(&["hidden"], out);
+    }
+    let Some(completion) = &flag.completion else {
+        return;
+    };
+    let mut fields = vec!["complete"];
+    match completion {
+        ValueCompletion::Nothing => fields.push("nothing"),
+        ValueCompletion::Bool => fields.push("bool"),
+        ValueCompletion::File => fields.push("file"),
+        ValueCompletion::Directory => fields.push("dir"),
+        ValueCompletion::Duration => fields.push("duration"),
+        ValueCompletion::KeyValue => fields.push("key-value"),
+        ValueCompletion::IssueId => fields.push("issue-id"),
+        ValueCompletion::Command(command) => fields.extend(["command", command]),
+        ValueCompletion::Values(values) => {
+            fields.push("values");
+            fields.extend(values.iter().map(String::as_str));
+        }
+    }
+    record(&fields, out);
+}
+
+fn read_command_detail(fields: &[&str], cmd: &mut Command) -> Option<()> {
+    match fields {
+        ["name", name] => cmd.name = name.to_string(),
+        ["description", text] => cmd.description = text.to_string(),
+        ["long-description", text] => cmd.long_description = Some(text.to_string()),
+        ["alias", alias] => cmd.aliases.push(alias.to_string()),
+        ["usage", usage] => cmd.usage = Some(usage.to_string()),
+        ["arg", name, required, variadic] => cmd.args.push(PositionalArg {
+            name: name.to_string(),
+            required: *required == "required",
+            variadic: *variadic == "variadic",
+        }),
+        ["examples", text] => cmd.examples = Some(text.to_string()),
+        ["in-group", group] => cmd.group = Some(group.to_string()),
+        ["deprecated"] => cmd.deprecated = true,
+        ["hidden"] => cmd.hidden = true,
+        _ => return None,
+    }
+    Some(())
+}
+
+fn read_flag_detail(fields: &[&str], flag: &mut Flag) -> Option<()> {
+    match fields {
+        ["short", short] => {
+            let mut chars = short.chars();
+            flag.short = Some(chars.next()?);
+            if chars.next().is_some() {
+                return None;
+            }
+        }
+        ["description", text] => flag.description = text.to_string(),
+        ["type", name] => flag.value_type = Some(ValueKind::from_type_name(name)),
+        ["default", default] => flag.default = Some(default.to_string()),
+        ["deprecated"] => flag.deprecated = true,
+        ["hidden"] => flag.hidden = true,
+        ["complete", kind, rest @ ..] => {
+            flag.completion = Some(match (*kind, rest) {
+                ("nothing", []) => ValueCompletion::Nothing,
+                ("bool", []) => ValueCompletion::Bool,
+                ("file", []) => ValueCompletion::File,
+                ("dir", []) => ValueCompletion::Directory,
+                ("duration", []) => ValueCompletion::Duration,
+                ("key-value", []) => ValueCompletion::KeyValue,
+                ("issue-id", []) => ValueCompletion::IssueId,
+                ("command", [command]) => ValueCompletion::Command(command.to_string()),
+                ("values", values) => {
+                    ValueCompletion::Values(values.iter().map(|v| v.to_string()).collect())
+                }
+                _ => return None,
+            })
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Attach open commands deeper than `depth` to their parents.
+fn close_to(open: &mut Vec<(usize, String, Command)>, depth: usize) -> Option<()> {
+    while open.last().is_some_and(|(d, _, _)| *d >= depth) && open.len() > 1 {
+        let (_, key, cmd) = open.pop()?;
+        open.last_mut()?.2.subcommands.insert(key, cmd);
+    }
+    Some(())
+}
+
+fn record(fields: &[&str], out: &mut String) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push('\t');
+        }
+        for c in field.chars() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                '\t' => out.push_str("\\t"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                c => out.push(c),
+            }
+        }
+    }
+    out.push('\n');
+}
+
+fn unescape(field: &str) -> Option<String> {
+    let mut text = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        text.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{build_command_tree_with_diagnostics, HelpSource};
+    use std::path::Path;
+
+    fn sample_tree() -> CommandTree {
+        let mut root = Command::new("bd", "A lightweight issue tracker");
+        root.long_description = Some("Issues\tand\ndependencies \\ more".to_string());
+        root.usage = Some("bd [command]".to_string());
+
+        let mut create = Command::new("create", "Create a new issue");
+        create.aliases = vec!["new".to_string()];
+        create.group = Some("Working With Issues".to_string());
+        create.args = vec![PositionalArg {
+            name: "title".to_string(),
+            required: false,
+            variadic: true,
+        }];
+        create.examples = Some("  bd create \"Fix it\"\n\n  bd new x".to_string());
+        create.flags.push(Flag {
+            short: Some('p'),
+            value_type: Some(ValueKind::Custom("priority".to_string())),
+            default: Some("2".to_string()),
+            ..Flag::new("priority", "Priority (0-4)")
+        });
+        create.flags.push(Flag {
+            value_type: Some(ValueKind::Strings),
+            completion: Some(ValueCompletion::Values(vec![
+                "a b".to_string(),
+                "".to_string(),
+            ])),
+            deprecated: true,
+            hidden: true,
+            ..Flag::new("labels", "")
+        });
+
+        let mut dep = Command::new("dep", "Manage dependencies");
+        let mut add = Command::new("add", "Add a dependency");
+        add.deprecated = true;
+        dep.subcommands.insert("add".to_string(), add);
+        let mut help = Command::new("help", "Help about any command");
+        help.hidden = true;
+
+        root.subcommands.insert("create".to_string(), create);
+        root.subcommands.insert("dep".to_string(), dep);
+        root.subcommands.insert("help".to_string(), help);
+
+        let mut tree = CommandTree::new(root);
+        tree.global_flags = vec![Flag {
+            value_type: Some(ValueKind::String),
+            completion: Some(ValueCompletion::Command("ls *.db".to_string())),
+            ..Flag::new("db", "Database path")
+        }];
+        tree.root.flags = tree.global_flags.clone();
+        tree.groups = vec![CommandGroup::new(
+            "Working With Issues",
+            vec!["create".to_string()],
+        )];
+        tree
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tree = sample_tree();
+        let diagnostics = vec![Diagnostic {
+            path: "bd create".to_string(),
+            section: "Flags".to_string(),
+            line: 12,
+            text: "  -x\tstray".to_string(),
+            kind: DiagnosticKind::Skipped,
+            reason: known_reason("not a flag entry").unwrap(),
+        }];
+
+        let text = write_tree(&tree, &diagnostics);
+        assert!(text.starts_with("bd-complete tree 1\ncommand\t0\tbd\n"));
+        assert_eq!(read_tree(&text), Some((tree, diagnostics)));
+    }
+
+    #[test]
+    fn test_round_trip_of_the_fixture_tree() {
+        let source = HelpSource::Dir {
+            dir: Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/help"),
+            root: "bd".to_string(),
+        };
+        let mut diagnostics = Vec::new();
+        let tree = build_command_tree_with_diagnostics(&source, &mut diagnostics).unwrap();
+
+        let text = write_tree(&tree, &diagnostics);
+        assert_eq!(read_tree(&text), Some((tree, diagnostics)));
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let text = write_tree(&sample_tree(), &[]);
+        assert!(read_tree("").is_none());
+        assert!(read_tree(&text.replace("tree 1", "tree 0")).is_none());
+        assert!(read_tree(&text.replace("command\t1\tdep", "command\t3\tdep")).is_none());
+        assert!(read_tree(&format!("{text}frob\n")).is_none());
+        let unknown_reason = "diagnostic\tbd\tFlags\t1\tskipped\tnew reason\tx\n";
+        assert!(read_tree(&format!("{text}{unknown_reason}")).is_none());
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A small block-style YAML emitter, enough for carapace specs.
+//!
+//! Scalars are written plain when YAML would read them back as the same
+//! string, and double-quoted otherwise (`true`, `2`, `a: b`, `#x`, ...).
+
+use std::io::{self, Write};
+
+/// A YAML node. Maps keep insertion order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Yaml {
+    Str(String),
+    Bool(bool),
+    List(Vec<Yaml>),
+    Map(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    pub fn str(s: &str) -> Yaml {
+        Yaml::Str(s.to_string())
+    }
+
+    /// Whether this node is written inline after `key: ` rather than on the
+    /// following lines.
+    fn is_inline(&self) -> bool {
+        match self {
+            Yaml::Str(_) | Yaml::Bool(_) => true,
+            Yaml::List(items) => items.is_empty(),
+            Yaml::Map(entries) => entries.is_empty(),
+        }
+    }
+}
+
+/// Write a document whose top level is `node`.
+pub fn write_yaml(node: &Yaml, out: &mut dyn Write) -> io::Result<()> {
+    match node {
+        _ if node.is_inline() => writeln!(out, "{}", inline(node)),
+        _ => write_block(node, 0, out),
+    }
+}
+
+fn write_block(node: &Yaml, indent: usize, out: &mut dyn Write) -> io::Result<()> {
+    let pad = "  ".repeat(indent);
+    match node {
+        Yaml::Str(_) | Yaml::Bool(_) => writeln!(out, "{pad}{}", inline(node)),
+        Yaml::Map(entries) => {
+            for (key, value) in entries {
+                write_entry(&pad, key, value, indent, out)?;
+            }
+            Ok(())
+        }
+        Yaml::List(items) => {
+            for item in items {
+                match item {
+                    // "- key: value" with the rest of the map aligned under it
+                    Yaml::Map(entries) if !entries.is_empty() => {
+                        let (first_key, first_value) = &entries[0];
+                        write_entry(&format!("{pad}- "), first_key, first_value, indent + 1, out)?;
+                        for (key, value) in &entries[1..] {
+                            write_entry(&format!("{pad}  "), key, value, indent + 1, out)?;
+                        }
+                    }
+                    Yaml::List(inner) if !inner.is_empty() => {
+                        writeln!(out, "{pad}-")?;
+                        write_block(item, indent + 1, out)?;
+                    }
+                    _ => writeln!(out, "{pad}- {}", inline(item))?,
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Write `key: value`, where `prefix` is the indentation (and possibly the
+/// list marker) and `indent` the level of the key itself.
+fn write_entry(
+    prefix: &str,
+    key: &str,
+    value: &Yaml,
+    indent: usize,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    if value.is_inline() {
+        writeln!(out, "{prefix}{}: {}", scalar(key), inline(value))
+    } else {
+        writeln!(out, "{prefix}{}:", scalar(key))?;
+        write_block(value, indent + 1, out)
+    }
+}
+
+fn inline(node: &Yaml) -> String {
+    match node {
+        Yaml::Str(s) => scalar(s),
+        Yaml::Bool(b) => b.to_string(),
+        Yaml::List(_) => "[]".to_string(),
+        Yaml::Map(_) => "{}".to_string(),
+    }
+}
+
+/// A scalar, plain if that reads back as the same string.
+fn scalar(s: &str) -> String {
+    if is_plain_safe(s) {
+        s.to_string()
+    } else {
+        double_quoted(s)
+    }
+}
+
+fn is_plain_safe(s: &str) -> bool {
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    if s.trim() != s || s.chars().any(|c| c.is_control()) {
+        return false;
+    }
+    // Indicators can't start a plain scalar, except "-x", "?x" and ":x"
+    let second = s.chars().nth(1);
+    match first {
+        '-' | '?' | ':' if second.is_none_or(char::is_whitespace) || s.starts_with("---") => {
+            return false
+        }
+        ',' | '[' | ']' | '{' | '}' | '#' | '&' | '*' | '!' | '|' | '>' | '\'' | '"' | '%'
+        | '@' | '`' => return false,
+        _ => {}
+    }
+    if s.contains(": ") || s.contains(" #") || s.ends_with(':') {
+        return false;
+    }
+    // Anything a resolver might read as a bool, null, number or date
+    let lower = s.to_ascii_lowercase();
+    let reserved = [
+        "true", "false", "yes", "no", "on", "off", "y", "n", "null", "~", ".inf", "-.inf", ".nan",
+    ];
+    if reserved.contains(&lower.as_str()) {
+        return false;
+    }
+    !(first.is_ascii_digit()
+        || ((first == '-' || first == '+' || first == '.')
+            && second.is_some_and(|c| c.is_ascii_digit())))
+}
+
+fn double_quoted(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(node: &Yaml) -> String {
+        let mut buf = Vec::new();
+        write_yaml(node, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn map(entries: &[(&str, Yaml)]) -> Yaml {
+        Yaml::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_plain_and_quoted_scalars() {
+        for plain in [
+            "bd",
+            "Create a new issue",
+            "-p, --priority=",
+            "--json",
+            "$files",
+            "a:b",
+            "x-1",
+            "say \"hi\"\\",
+        ] {
+            assert_eq!(scalar(plain), plain);
+        }
+        let quoted = [
+            ("", r#""""#),
+            ("true", r#""true""#),
+            ("No", r#""No""#),
+            ("2", r#""2""#),
+            ("30s", r#""30s""#),
+            ("-1", r#""-1""#),
+            ("key: value", r#""key: value""#),
+            ("# comment", r##""# comment""##),
+            ("value #x", r##""value #x""##),
+            ("- item", r#""- item""#),
+            ("[x]", r#""[x]""#),
+            ("'q'", r#""'q'""#),
+            ("\"hi\" \\", r#""\"hi\" \\""#),
+            ("line\nbreak", r#""line\nbreak""#),
+            (" padded", r#"" padded""#),
+        ];
+        for (input, expected) in quoted {
+            assert_eq!(scalar(input), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_nested_block_style() {
+        let doc = map(&[
+            ("name", Yaml::str("bd")),
+            ("flags", map(&[("-q, --quiet", Yaml::str("Quiet"))])),
+            (
+                "commands",
+                Yaml::List(vec![
+                    map(&[
+                        ("name", Yaml::str("create")),
+                        ("aliases", Yaml::List(vec![Yaml::str("new")])),
+                    ]),
+                    map(&[("name", Yaml::str("list")), ("flags", Yaml::Map(vec![]))]),
+                ]),
+            ),
+        ]);
+        assert_eq!(
+            render(&doc),
+            "name: bd\n\
+             flags:\n  -q, --quiet: Quiet\n\
+             commands:\n  - name: create\n    aliases:\n      - new\n  - name: list\n    flags: {}\n"
+        );
+    }
+
+    #[test]
+    fn test_lists_of_lists_and_empty_documents() {
+        let doc = Yaml::List(vec![Yaml::List(vec![Yaml::str("a"), Yaml::str("true")])]);
+        assert_eq!(render(&doc), "-\n  - a\n  - \"true\"\n");
+        assert_eq!(render(&Yaml::Map(vec![])), "{}\n");
+        assert_eq!(render(&Yaml::str("x: y")), "\"x: y\"\n");
+        assert_eq!(render(&Yaml::Bool(true)), "true\n");
+    }
+}
@@ -0,0 +1,465 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! End-to-end completion tests.
+//!
+//! A stub `bd` prints the captured help in `tests/fixtures/help`, bd-complete
+//! generates a script from it, and a real shell is asked what TAB returns.
+//! Tests skip when the shell isn't installed.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Stand-in for bd's `_init_completion` (from bash-completion), which needs
+/// an interactive completion context. It fills in the same four variables.
+/// `$FUNC` is the completion function to call, `_bd` by default.
+const BASH_DRIVER: &str = r#"
+_init_completion() {
+    words=("${COMP_WORDS[@]}")
+    cword=$COMP_CWORD
+    cur="${words[cword]}"
+    prev="${words[cword-1]}"
+}
+compopt() { :; }
+source "$SCRIPT"
+COMP_WORDS=("$@")
+COMP_CWORD=$(( $# - 1 ))
+COMP_LINE="$*"
+COMP_POINT=${#COMP_LINE}
+COMPREPLY=()
+"${FUNC:-_bd}"
+printf '%s\n' "${COMPREPLY[@]}"
+"#;
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/help")
+}
+
+/// A scratch directory unique to one test.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bd-complete-e2e-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write a stub `bd` that prints `<fixtures>/bd_<sub>_<sub>.txt` for `bd <sub> <sub> --help`.
+fn write_stub_bd(dir: &Path) -> PathBuf {
+    let script = format!(
+        r#"#!/bin/sh
+name=bd
+for arg in "$@"; do
+    [ "$arg" = "--help" ] || name="${{name}}_$arg"
+done
+if [ -f "{fixtures}/$name.txt" ]; then
+    cat "{fixtures}/$name.txt"
+else
+    echo "Error: unknown command for \"bd\"" >&2
+    exit 1
+fi
+"#,
+        fixtures = fixtures_dir().display()
+    );
+    let path = dir.join("bd");
+    std::fs::write(&path, script).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    path
+}
+
+fn has_shell(shell: &str) -> bool {
+    Command::new(shell)
+        .arg("--version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Generate a completion script for `shell` from the stub bd and save it in `dir`.
+fn generate(shell: &str, dir: &Path) -> PathBuf {
+    generate_with(shell, dir, &[])
+}
+
+/// Like `generate`, passing `args` to bd-complete as well.
+fn generate_with(shell: &str, dir: &Path, args: &[&Path]) -> PathBuf {
+    let stub = write_stub_bd(dir);
+    let script = dir.join(format!("bd.{shell}"));
+    let status = Command::new(env!("CARGO_BIN_EXE_bd-complete"))
+        .args(["generate", "--shell", shell, "--bd"])
+        .arg(&stub)
+        .arg("--output")
+        .arg(&script)
+        .arg("--no-plugins")
+        .args(args)
+        .env("XDG_CACHE_HOME", dir.join("cache"))
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .status()
+        .unwrap();
+    assert!(status.success(), "bd-complete generate --shell {shell} failed");
+    script
+}
+
+/// Generate a split bash completion from the stub bd into `dir/split`,
+/// returning the loader.
+fn generate_split(dir: &Path) -> PathBuf {
+    let stub = write_stub_bd(dir);
+    let split = dir.join("split");
+    let status = Command::new(env!("CARGO_BIN_EXE_bd-complete"))
+        .args(["generate", "--shell", "bash", "--bd"])
+        .arg(&stub)
+        .arg("--split-dir")
+        .arg(&split)
+        .arg("--no-plugins")
+        .env("XDG_CACHE_HOME", dir.join("cache"))
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .status()
+        .unwrap();
+    assert!(status.success(), "bd-complete generate --split-dir failed");
+    split.join("bd")
+}
+
+/// Run the bash completion function for a command line given as words;
+/// the last word is the one being completed.
+fn bash_complete(script: &Path, words: &[&str]) -> Vec<String> {
+    bash_complete_with(script, "_bd", words)
+}
+
+/// Like `bash_complete`, calling the completion function `func`.
+fn bash_complete_with(script: &Path, func: &str, words: &[&str]) -> Vec<String> {
+    let output = Command::new("bash")
+        .arg("-c")
+        .arg(BASH_DRIVER)
+        .arg("bash")
+        .args(words)
+        .env("SCRIPT", script)
+        .env("FUNC", func)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "bash failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut candidates: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+/// Ask fish for the candidates of a command line (e.g. "bd epic "),
+/// dropping the descriptions.
+fn fish_complete(script: &Path, line: &str) -> Vec<String> {
+    let output = Command::new("fish")
+        .arg("--no-config")
+        .arg("-c")
+        .arg(format!("source {}; complete -C '{line}'", script.display()))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "fish failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut candidates: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split('\t').next().unwrap_or("").to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_bash_top_level_commands() {
+    if !has_shell("bash") {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("bash-top");
+    let script = generate("bash", &dir);
+
+    let candidates = bash_complete(&script, &["bd", ""]);
+    assert!(candidates.contains(&"create".to_string()));
+    assert!(candidates.contains(&"epic".to_string()));
+    assert!(candidates.contains(&"ready".to_string()));
+    // cobra's helpers are left out by default
+    assert!(!candidates.contains(&"help".to_string()));
+    assert!(!candidates.contains(&"completion".to_string()));
+
+    assert_eq!(bash_complete(&script, &["bd", "re"]), strings(&["ready", "reopen"]));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_bash_nested_subcommands() {
+    if !has_shell("bash") {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("bash-nested");
+    let script = generate("bash", &dir);
+
+    assert_eq!(
+        bash_complete(&script, &["bd", "epic", ""]),
+        strings(&["close-eligible", "status"])
+    );
+    // Global flags and their values don't throw off the walk
+    assert_eq!(
+        bash_complete(&script, &["bd", "--db", "x.db", "dep", ""]),
+        strings(&["add", "cycles", "remove", "tree"])
+    );
+    // Once positionals start, subcommands are no longer offered
+    assert!(bash_complete(&script, &["bd", "dep", "add", "bd-1", ""]).is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_bash_flags_and_aliases() {
+    if !has_shell("bash") {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("bash-flags");
+    let script = generate("bash", &dir);
+
+    assert_eq!(
+        bash_complete(&script, &["bd", "create", "--pri"]),
+        strings(&["--priority"])
+    );
+    // "new" is an alias of create
+    assert_eq!(
+        bash_complete(&script, &["bd", "new", "--pa"]),
+        strings(&["--parent"])
+    );
+    // Deprecated flags only complete once typed in full
+    assert!(!bash_complete(&script, &["bd", "list", "--"]).contains(&"--assigned".to_string()));
+    assert_eq!(
+        bash_complete(&script, &["bd", "list", "--assigned"]),
+        strings(&["--assigned"])
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_bash_flag_values() {
+    if !has_shell("bash") {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("bash-values");
+    let script = generate("bash", &dir);
+
+    // Duration flags offer units after a number
+    assert_eq!(
+        bash_complete(&script, &["bd", "--lock-timeout", "5"]),
+        strings(&["5h", "5m", "5ms", "5s"])
+    );
+    // key=value flags close the key
+    assert_eq!(
+        bash_complete(&script, &["bd", "update", "--set-metadata", "team"]),
+        strings(&["team="])
+    );
+    // Numbers get nothing
+    assert!(bash_complete(&script, &["bd", "list", "--limit", ""]).is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Overrides for `test_*_overrides`, written to `dir/overrides.toml`.
+fn write_overrides(dir: &Path) -> PathBuf {
+    let path = dir.join("overrides.toml");
+    std::fs::write(
+        &path,
+        r#"
+[command.create.flag.type]
+values = ["bug", "feature", "task", "epic", "chore"]
+
+[command.create.flag.parent]
+command = "printf 'bd-1\nbd-2\n'"
+
+[command.create]
+aliases = ["add"]
+
+[command.ready]
+hidden = true
+"#,
+    )
+    .unwrap();
+    path
+}
+
+#[test]
+fn test_bash_overrides() {
+    if !has_shell("bash") {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("bash-overrides");
+    let overrides = write_overrides(&dir);
+    let script = generate_with("bash", &dir, &[Path::new("--overrides"), &overrides]);
+
+    assert_eq!(
+        bash_complete(&script, &["bd", "create", "--type", "e"]),
+        strings(&["epic"])
+    );
+    assert_eq!(
+        bash_complete(&script, &["bd", "add", "--parent", ""]),
+        strings(&["bd-1", "bd-2"])
+    );
+    assert_eq!(bash_complete(&script, &["bd", "re"]), strings(&["reopen"]));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_bash_aliases() {
+    if !has_shell("bash") {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("bash-aliases");
+    let aliases = ["b", "bdc=create", "bdw=--db work.db"].map(|a| ["--alias", a]);
+    let args: Vec<&Path> = aliases.iter().flatten().map(Path::new).collect();
+    let script = generate_with("bash", &dir, &args);
+    let text = std::fs::read_to_string(&script).unwrap();
+    assert!(text.contains("complete -F _bd b\n"));
+
+    assert_eq!(
+        bash_complete_with(&script, "__bd_alias_bdc", &["bdc", "--pa"]),
+        strings(&["--parent"])
+    );
+    // The injected --db value isn't mistaken for a command
+    assert_eq!(
+        bash_complete_with(&script, "__bd_alias_bdw", &["bdw", "dep", ""]),
+        strings(&["add", "cycles", "remove", "tree"])
+    );
+    assert_eq!(
+        bash_complete_with(&script, "__bd_alias_bdw", &["bdw", "re"]),
+        strings(&["ready", "reopen"])
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_bash_split_matches_monolithic() {
+    if !has_shell("bash") {
+        eprintln!("bash not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("bash-split");
+    let script = generate("bash", &dir);
+    let loader = generate_split(&dir);
+
+    for words in [
+        &["bd", ""][..],
+        &["bd", "re"],
+        &["bd", "--"],
+        &["bd", "--db", "x.db", "dep", ""],
+        &["bd", "epic", ""],
+        &["bd", "dep", "add", "bd-1", ""],
+        &["bd", "new", "--pa"],
+        &["bd", "list", "--assigned"],
+        &["bd", "--lock-timeout", "5"],
+        &["bd", "update", "--set-metadata", "team"],
+    ] {
+        assert_eq!(
+            bash_complete(&loader, words),
+            bash_complete(&script, words),
+            "{words:?}"
+        );
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_fish_completions() {
+    if !has_shell("fish") {
+        eprintln!("fish not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("fish");
+    let script = generate("fish", &dir);
+
+    let top = fish_complete(&script, "bd ");
+    assert!(top.contains(&"create".to_string()));
+    assert!(top.contains(&"epic".to_string()));
+
+    let epic = fish_complete(&script, "bd epic ");
+    assert!(epic.contains(&"close-eligible".to_string()));
+    assert!(epic.contains(&"status".to_string()));
+    assert!(!epic.contains(&"create".to_string()));
+
+    // Subcommands stop once a positional argument has been given
+    assert!(fish_complete(&script, "bd dep ").contains(&"add".to_string()));
+    assert!(!fish_complete(&script, "bd dep add bd-1 ").contains(&"add".to_string()));
+
+    assert!(fish_complete(&script, "bd create --pri").contains(&"--priority".to_string()));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_fish_overrides() {
+    if !has_shell("fish") {
+        eprintln!("fish not available, skipping");
+        return;
+    }
+    let dir = scratch_dir("fish-overrides");
+    let overrides = write_overrides(&dir);
+    let script = generate_with("fish", &dir, &[Path::new("--overrides"), &overrides]);
+
+    assert_eq!(fish_complete(&script, "bd create --type e"), strings(&["epic"]));
+    assert_eq!(
+        fish_complete(&script, "bd create --parent "),
+        strings(&["bd-1", "bd-2"])
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_exit_codes() {
+    let dir = scratch_dir("exit-codes");
+    let run = |bd: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_bd-complete"))
+            .args(["generate", "--shell", "bash", "--no-cache", "--no-plugins", "--bd"])
+            .arg(bd)
+            .env("XDG_CONFIG_HOME", dir.join("config"))
+            .output()
+            .unwrap()
+    };
+
+    // bd missing
+    let missing = run(&dir.join("no-such-bd"));
+    assert_eq!(missing.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("can't run"));
+
+    // bd present, but what it prints isn't help
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let junk = dir.join("junk");
+        std::fs::write(&junk, "#!/bin/sh\necho 'bd 1.0'\n").unwrap();
+        std::fs::set_permissions(&junk, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let unparseable = run(&junk);
+        assert_eq!(unparseable.status.code(), Some(6));
+        assert!(String::from_utf8_lossy(&unparseable.stderr).contains("line 1"));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
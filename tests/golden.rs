@@ -0,0 +1,215 @@
+// Copyright 2006 JT Perry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Golden-file snapshots of every generator's full output.
+//!
+//! Each test renders the command tree captured in `tests/fixtures/help` and
+//! compares it byte-for-byte with `tests/golden/<name>`. Output in
+//! `--order alphabetical` is pinned under `tests/golden/alphabetical/`.
+//! `bd_close.txt` is colored the way `tput` would color it, so the colored
+//! capture must render exactly like the plain ones.
+//! After an intended change, regenerate the files and review the diff:
+//!
+//! ```text
+//! BD_COMPLETE_BLESS=1 cargo test --test golden
+//! ```
+
+use std::path::Path;
+use std::process::Command;
+
+/// Run bd-complete against the captured help fixtures and return stdout.
+/// Strict mode keeps the fixtures free of lines the parser skips.
+fn render(args: &[&str]) -> String {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_bd-complete"))
+        .args(args)
+        .arg("--help-dir")
+        .arg(root.join("tests/fixtures/help"))
+        .arg("--no-overrides")
+        .arg("--strict")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "bd-complete {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Run `bd-complete man` with `args` against the fixtures into a scratch
+/// directory and return the contents of one page.
+fn render_man_page(dir: &Path, args: &[&str], page: &str) -> String {
+    if !dir.exists() {
+        let mut man = vec!["man", "--output-dir", dir.to_str().unwrap()];
+        man.extend_from_slice(args);
+        render(&man);
+    }
+    std::fs::read_to_string(dir.join(page)).unwrap()
+}
+
+/// Compare `actual` with the golden file, or overwrite it when blessing.
+fn assert_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name);
+
+    if std::env::var_os("BD_COMPLETE_BLESS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "missing golden file {}: {e}\nRun with BD_COMPLETE_BLESS=1 to create it.",
+            path.display()
+        )
+    });
+    if expected != actual {
+        let line = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(e, a)| e != a)
+            .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+        panic!(
+            "{} is out of date (first difference at line {}).\n\
+             expected: {:?}\n  actual: {:?}\n\
+             Run with BD_COMPLETE_BLESS=1 to update it, then review the diff.",
+            path.display(),
+            line + 1,
+            expected.lines().nth(line).unwrap_or("<end of file>"),
+            actual.lines().nth(line).unwrap_or("<end of file>"),
+        );
+    }
+}
+
+#[test]
+fn test_bash_golden() {
+    assert_golden("bd.bash", &render(&["generate", "--shell", "bash"]));
+}
+
+#[test]
+fn test_fish_golden() {
+    assert_golden("bd.fish", &render(&["generate", "--shell", "fish"]));
+}
+
+#[test]
+fn test_bash_alphabetical_golden() {
+    assert_golden(
+        "alphabetical/bd.bash",
+        &render(&["generate", "--shell", "bash", "--order", "alphabetical"]),
+    );
+}
+
+#[test]
+fn test_fish_alphabetical_golden() {
+    assert_golden(
+        "alphabetical/bd.fish",
+        &render(&["generate", "--shell", "fish", "--order", "alphabetical"]),
+    );
+}
+
+#[test]
+fn test_markdown_docs_golden() {
+    assert_golden("bd.md", &render(&["docs", "--format", "markdown"]));
+}
+
+#[test]
+fn test_markdown_docs_alphabetical_golden() {
+    assert_golden(
+        "alphabetical/bd.md",
+        &render(&["docs", "--format", "markdown", "--order", "alphabetical"]),
+    );
+}
+
+#[test]
+fn test_html_docs_golden() {
+    assert_golden("bd.html", &render(&["docs", "--format", "html"]));
+}
+
+#[test]
+fn test_html_docs_alphabetical_golden() {
+    assert_golden(
+        "alphabetical/bd.html",
+        &render(&["docs", "--format", "html", "--order", "alphabetical"]),
+    );
+}
+
+#[test]
+fn test_fig_json_golden() {
+    assert_golden("bd.fig.json", &render(&["export", "--format", "fig"]));
+}
+
+#[test]
+fn test_fig_json_alphabetical_golden() {
+    assert_golden(
+        "alphabetical/bd.fig.json",
+        &render(&["export", "--format", "fig", "--order", "alphabetical"]),
+    );
+}
+
+#[test]
+fn test_fig_typescript_golden() {
+    assert_golden(
+        "bd.fig.ts",
+        &render(&["export", "--format", "fig-ts", "--issue-ids"]),
+    );
+}
+
+#[test]
+fn test_fig_typescript_alphabetical_golden() {
+    assert_golden(
+        "alphabetical/bd.fig.ts",
+        &render(&["export", "--format", "fig-ts", "--issue-ids", "--order", "alphabetical"]),
+    );
+}
+
+#[test]
+fn test_carapace_golden() {
+    assert_golden(
+        "bd.carapace.yaml",
+        &render(&["export", "--format", "carapace"]),
+    );
+}
+
+#[test]
+fn test_carapace_alphabetical_golden() {
+    assert_golden(
+        "alphabetical/bd.carapace.yaml",
+        &render(&["export", "--format", "carapace", "--order", "alphabetical"]),
+    );
+}
+
+#[test]
+fn test_man_pages_golden() {
+    let dir = std::env::temp_dir().join(format!("bd-complete-golden-man-{}", std::process::id()));
+    for page in ["bd.1", "bd-create.1", "bd-dep.1", "bd-dep-add.1"] {
+        assert_golden(&format!("man/{page}"), &render_man_page(&dir, &[], page));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_man_pages_alphabetical_golden() {
+    let dir = std::env::temp_dir().join(format!(
+        "bd-complete-golden-man-alphabetical-{}",
+        std::process::id()
+    ));
+    let args = ["--order", "alphabetical"];
+    for page in ["bd.1", "bd-create.1", "bd-dep.1", "bd-dep-add.1"] {
+        let actual = render_man_page(&dir, &args, page);
+        assert_golden(&format!("alphabetical/man/{page}"), &actual);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,1282 @@
+#!/usr/bin/env bash
+# Copyright 2006 JT Perry
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+# Bash completion for bd
+# Generated by bd-complete
+
+_bd() {
+    local cur prev words cword
+    _init_completion || return
+
+    # Walk the command tree to find the active subcommand
+    local cmd_path=""
+    local positional=0
+    local i=1
+    while [[ $i -lt $cword ]]; do
+        case "${words[$i]}" in
+            --*=*)
+                ;;
+            -*)
+                case "$cmd_path:${words[$i]}" in
+                    *:--actor|*:--db|*:--lock-timeout|':--actor'|':--db'|':--lock-timeout'|'close:--reason'|'close:-r'|'count:--assignee'|'count:-a'|'count:--status'|'count:-s'|'create:--acceptance'|'create:--assignee'|'create:-a'|'create:--deps'|'create:--description'|'create:-d'|'create:--design'|'create:--due'|'create:--estimate'|'create:-e'|'create:--file'|'create:-f'|'create:--id'|'create:--labels'|'create:-l'|'create:--parent'|'create:--priority'|'create:-p'|'create:--title'|'create:--type'|'create:-t'|'delete:--from-file'|'dep add:--type'|'dep add:-t'|'dep tree:--max-depth'|'dep tree:-d'|'init:--branch'|'init:--prefix'|'init:-p'|'list:--assigned'|'list:--assignee'|'list:-a'|'list:--format'|'list:--label'|'list:-l'|'list:--limit'|'list:-n'|'list:--priority'|'list:-p'|'list:--sort'|'list:--status'|'list:-s'|'list:--type'|'list:-t'|'ready:--assignee'|'ready:-a'|'ready:--limit'|'ready:-n'|'ready:--priority'|'ready:-p'|'ready:--sort'|'ready:-s'|'reopen:--reason'|'reopen:-r'|'update:--acceptance'|'update:--add-label'|'update:--assignee'|'update:-a'|'update:--description'|'update:-d'|'update:--notes'|'update:--priority'|'update:-p'|'update:--remove-label'|'update:--set-metadata'|'update:--status'|'update:-s'|'update:--title')
+                        ((i++))
+                        ;;
+                esac
+                ;;
+            *)
+                if [[ $positional -eq 0 ]]; then
+                    case "$cmd_path" in
+                        '')
+                            case "${words[$i]}" in
+                                blocked)
+                                    cmd_path=blocked
+                                    ;;
+                                close)
+                                    cmd_path=close
+                                    ;;
+                                config)
+                                    cmd_path=config
+                                    ;;
+                                count)
+                                    cmd_path=count
+                                    ;;
+                                create|new)
+                                    cmd_path=create
+                                    ;;
+                                delete)
+                                    cmd_path=delete
+                                    ;;
+                                dep)
+                                    cmd_path=dep
+                                    ;;
+                                epic)
+                                    cmd_path=epic
+                                    ;;
+                                init)
+                                    cmd_path=init
+                                    ;;
+                                label)
+                                    cmd_path=label
+                                    ;;
+                                list)
+                                    cmd_path=list
+                                    ;;
+                                ready)
+                                    cmd_path=ready
+                                    ;;
+                                reopen)
+                                    cmd_path=reopen
+                                    ;;
+                                show)
+                                    cmd_path=show
+                                    ;;
+                                stats|status)
+                                    cmd_path=stats
+                                    ;;
+                                update)
+                                    cmd_path=update
+                                    ;;
+                                version)
+                                    cmd_path=version
+                                    ;;
+                                *)
+                                    positional=1
+                                    ;;
+                            esac
+                            ;;
+                        config)
+                            case "${words[$i]}" in
+                                get)
+                                    cmd_path='config get'
+                                    ;;
+                                list)
+                                    cmd_path='config list'
+                                    ;;
+                                set)
+                                    cmd_path='config set'
+                                    ;;
+                                unset)
+                                    cmd_path='config unset'
+                                    ;;
+                                *)
+                                    positional=1
+                                    ;;
+                            esac
+                            ;;
+                        dep)
+                            case "${words[$i]}" in
+                                add)
+                                    cmd_path='dep add'
+                                    ;;
+                                cycles)
+                                    cmd_path='dep cycles'
+                                    ;;
+                                remove|rm)
+                                    cmd_path='dep remove'
+                                    ;;
+                                tree)
+                                    cmd_path='dep tree'
+                                    ;;
+                                *)
+                                    positional=1
+                                    ;;
+                            esac
+                            ;;
+                        epic)
+                            case "${words[$i]}" in
+                                close-eligible)
+                                    cmd_path='epic close-eligible'
+                                    ;;
+                                status)
+                                    cmd_path='epic status'
+                                    ;;
+                                *)
+                                    positional=1
+                                    ;;
+                            esac
+                            ;;
+                        label)
+                            case "${words[$i]}" in
+                                add)
+                                    cmd_path='label add'
+                                    ;;
+                                list)
+                                    cmd_path='label list'
+                                    ;;
+                                list-all)
+                                    cmd_path='label list-all'
+                                    ;;
+                                remove)
+                                    cmd_path='label remove'
+                                    ;;
+                                *)
+                                    positional=1
+                                    ;;
+                            esac
+                            ;;
+                        *)
+                            positional=1
+                            ;;
+                    esac
+                fi
+                ;;
+        esac
+        ((i++))
+    done
+
+    case "$cmd_path" in
+        blocked)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        close)
+            case "$prev" in
+                --reason|-r)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--force --help --reason --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        config)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            if [[ $positional -eq 0 ]]; then
+                mapfile -t COMPREPLY < <(compgen -W 'get list set unset' -- "$cur")
+            fi
+            ;;
+        'config get')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'config list')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'config set')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'config unset')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        count)
+            case "$prev" in
+                --assignee|-a)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --status|-s)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--assignee --by-status --by-type --help --status --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        create)
+            case "$prev" in
+                --acceptance)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --assignee|-a)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --deps)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --description|-d)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --design)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --due)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --estimate|-e)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --file|-f)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --id)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --labels|-l)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --parent)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --priority|-p)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --title)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --type|-t)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--acceptance --assignee --deps --description --design --due --estimate --file --help --id --labels --parent --priority --title --type --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        delete)
+            case "$prev" in
+                --from-file)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--cascade --dry-run --force --from-file --help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        dep)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            if [[ $positional -eq 0 ]]; then
+                mapfile -t COMPREPLY < <(compgen -W 'add cycles remove tree' -- "$cur")
+            fi
+            ;;
+        'dep add')
+            case "$prev" in
+                --type|-t)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --type --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'dep cycles')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'dep remove')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'dep tree')
+            case "$prev" in
+                --max-depth|-d)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --max-depth --reverse --show-all-paths --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        epic)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            if [[ $positional -eq 0 ]]; then
+                mapfile -t COMPREPLY < <(compgen -W 'close-eligible status' -- "$cur")
+            fi
+            ;;
+        'epic close-eligible')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--dry-run --help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'epic status')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--eligible-only --help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        init)
+            case "$prev" in
+                --branch)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --prefix|-p)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--branch --help --prefix --skip-merge-driver --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        label)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            if [[ $positional -eq 0 ]]; then
+                mapfile -t COMPREPLY < <(compgen -W 'add list list-all remove' -- "$cur")
+            fi
+            ;;
+        'label add')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'label list')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'label list-all')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        'label remove')
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        list)
+            case "$prev" in
+                --assigned)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --assignee|-a)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --format)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --label|-l)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --limit|-n)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --priority|-p)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --sort)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --status|-s)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --type|-t)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--all --assignee --format --help --label --limit --priority --sort --status --type --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --assigned|--sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        ready)
+            case "$prev" in
+                --assignee|-a)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --limit|-n)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --priority|-p)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --sort|-s)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--assignee --help --limit --priority --sort --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        reopen)
+            case "$prev" in
+                --reason|-r)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --reason --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        show)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --thread --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        stats)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        update)
+            case "$prev" in
+                --acceptance)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --add-label)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --assignee|-a)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --description|-d)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --notes)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --priority|-p)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --remove-label)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --set-metadata)
+                    COMPREPLY=()
+                    if [[ -n "$cur" && "$cur" != *=* ]]; then
+                        COMPREPLY=("$cur=")
+                        compopt -o nospace
+                    fi
+                    return
+                    ;;
+                --status|-s)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --title)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--acceptance --add-label --assignee --description --help --notes --priority --remove-label --set-metadata --status --title --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        version)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--help --actor --db --json --lock-timeout --no-daemon --quiet --verbose' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            ;;
+        *)
+            case "$prev" in
+                --actor)
+                    COMPREPLY=()
+                    return
+                    ;;
+                --db)
+                    mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+                    return
+                    ;;
+                --lock-timeout)
+                    COMPREPLY=()
+                    if [[ "$cur" =~ ^[0-9]+$ ]]; then
+                        COMPREPLY=("${cur}ms" "${cur}s" "${cur}m" "${cur}h")
+                    fi
+                    return
+                    ;;
+            esac
+            if [[ "$cur" == -* ]]; then
+                mapfile -t COMPREPLY < <(compgen -W '--actor --db --help --json --lock-timeout --no-daemon --quiet --verbose --version' -- "$cur")
+                case "$cur" in
+                    --sandbox)
+                        COMPREPLY+=("$cur")
+                        ;;
+                esac
+                return
+            fi
+            if [[ $positional -eq 0 ]]; then
+                mapfile -t COMPREPLY < <(compgen -W 'blocked close config count create delete dep epic init label list ready reopen show stats update version' -- "$cur")
+            fi
+            ;;
+    esac
+}
+
+complete -F _bd bd
@@ -0,0 +1,247 @@
+# Copyright 2006 JT Perry
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+# Fish completion for bd
+# Generated by bd-complete
+
+complete -c bd -f
+
+# Whether the last word that isn't a flag or a flag's value is one of
+# the given commands, i.e. no positional argument follows it
+function __bd_at_command
+    set -l words (commandline -opc)
+    set -e words[1]
+    set -l last
+    set -l skip 0
+    for word in $words
+        if test $skip -eq 1
+            set skip 0
+        else if contains -- $word --actor --db --lock-timeout --acceptance --add-label --assignee -a --description -d --notes --priority -p --remove-label --set-metadata --status -s --title --reason -r --limit -n --sort --assigned --format --label -l --type -t --branch --prefix --max-depth --from-file --deps --design --due --estimate -e --file -f --id --labels --parent
+            set skip 1
+        else if not string match -q -- '-*' $word
+            set last $word
+        end
+    end
+    contains -- "$last" $argv
+end
+
+# Global flags
+complete -c bd -l actor -r -d 'Actor name for audit trail (default: $BD_ACTOR or $USER)'
+complete -c bd -l db -r -F -d 'Database path (default: auto-discover .beads/*.db)'
+complete -c bd -l json -d 'Output in JSON format'
+complete -c bd -l lock-timeout -r -a '(string match -r -- "^[0-9]+\$" (commandline -ct)){ms,s,m,h}' -d 'SQLite busy timeout (default 30s)'
+complete -c bd -l no-daemon -d 'Force direct storage mode, bypass daemon if running'
+complete -c bd -l quiet -s q -d 'Suppress non-essential output (errors only)'
+complete -c bd -n 'contains -- (commandline -ct) --sandbox' -l sandbox -d 'Deprecated: use --no-daemon'
+complete -c bd -l verbose -s v -d 'Enable verbose/debug output'
+
+# Root flags
+complete -c bd -n '__fish_use_subcommand' -l help -s h -d 'help for bd'
+complete -c bd -n '__fish_use_subcommand' -l version -d 'version for bd'
+
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a blocked -d 'Show blocked issues'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a close -d 'Close one or more issues'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a config -d 'Manage configuration settings'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a count -d 'Count issues matching filters'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a create -d 'Create a new issue (or multiple issues from markdown file)'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a new -d 'Create a new issue (or multiple issues from markdown file)'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a delete -d 'Delete one or more issues and clean up references'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a dep -d 'Manage dependencies'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a epic -d 'Epic management commands'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a init -d 'Initialize bd in the current directory'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a label -d 'Manage issue labels'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a list -d 'List issues'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a ready -d 'Show ready work (no blockers, open or in-progress)'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a reopen -d 'Reopen one or more closed issues'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a show -d 'Show issue details'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a stats -d 'Show statistics'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a status -d 'Show statistics'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a update -d 'Update one or more issues'
+complete -c bd -n 'not __fish_seen_subcommand_from blocked close config count create new delete dep epic init label list ready reopen show stats status update version' -a version -d 'Print version information'
+
+# Flags for 'blocked'
+complete -c bd -n '__fish_seen_subcommand_from blocked' -l help -s h -d 'help for blocked'
+
+# Flags for 'close'
+complete -c bd -n '__fish_seen_subcommand_from close' -l force -d 'Force close pinned issues'
+complete -c bd -n '__fish_seen_subcommand_from close' -l help -s h -d 'help for close'
+complete -c bd -n '__fish_seen_subcommand_from close' -l reason -s r -r -d 'Reason for closing (default "Closed")'
+
+# Flags for 'config'
+complete -c bd -n '__fish_seen_subcommand_from config' -l help -s h -d 'help for config'
+
+# Subcommands of 'config'
+complete -c bd -n '__fish_seen_subcommand_from config; and __bd_at_command config' -a get -d 'Get a configuration value'
+complete -c bd -n '__fish_seen_subcommand_from config; and __bd_at_command config' -a list -d 'List all configuration'
+complete -c bd -n '__fish_seen_subcommand_from config; and __bd_at_command config' -a set -d 'Set a configuration value'
+complete -c bd -n '__fish_seen_subcommand_from config; and __bd_at_command config' -a unset -d 'Delete a configuration value'
+
+# Flags for 'get'
+complete -c bd -n '__fish_seen_subcommand_from get' -l help -s h -d 'help for get'
+
+# Flags for 'list'
+complete -c bd -n '__fish_seen_subcommand_from list' -l help -s h -d 'help for list'
+
+# Flags for 'set'
+complete -c bd -n '__fish_seen_subcommand_from set' -l help -s h -d 'help for set'
+
+# Flags for 'unset'
+complete -c bd -n '__fish_seen_subcommand_from unset' -l help -s h -d 'help for unset'
+
+# Flags for 'count'
+complete -c bd -n '__fish_seen_subcommand_from count' -l assignee -s a -r -d 'Filter by assignee'
+complete -c bd -n '__fish_seen_subcommand_from count' -l by-status -d 'Group count by status'
+complete -c bd -n '__fish_seen_subcommand_from count' -l by-type -d 'Group count by issue type'
+complete -c bd -n '__fish_seen_subcommand_from count' -l help -s h -d 'help for count'
+complete -c bd -n '__fish_seen_subcommand_from count' -l status -s s -r -d 'Filter by status (open, in_progress, blocked, closed)'
+
+# Flags for 'create'
+complete -c bd -n '__fish_seen_subcommand_from create' -l acceptance -r -d 'Acceptance criteria'
+complete -c bd -n '__fish_seen_subcommand_from create' -l assignee -s a -r -d 'Assignee'
+complete -c bd -n '__fish_seen_subcommand_from create' -l deps -r -d 'Dependencies in format \'type:id\' or \'id\' (e.g., \'discovered-from:bd-20,blocks:bd-15\' or \'bd-20\')'
+complete -c bd -n '__fish_seen_subcommand_from create' -l description -s d -r -d 'Issue description'
+complete -c bd -n '__fish_seen_subcommand_from create' -l design -r -d 'Design notes'
+complete -c bd -n '__fish_seen_subcommand_from create' -l due -r -d 'Due date, absolute (2026-01-31) or relative (+3d); parsed in the local timezone'
+complete -c bd -n '__fish_seen_subcommand_from create' -l estimate -s e -r -d 'Time estimate in minutes'
+complete -c bd -n '__fish_seen_subcommand_from create' -l file -s f -r -F -d 'Create multiple issues from markdown file'
+complete -c bd -n '__fish_seen_subcommand_from create' -l help -s h -d 'help for create'
+complete -c bd -n '__fish_seen_subcommand_from create' -l id -r -d 'Explicit issue ID (e.g., \'bd-42\' for partitioning)'
+complete -c bd -n '__fish_seen_subcommand_from create' -l labels -s l -r -d 'Labels (comma-separated)'
+complete -c bd -n '__fish_seen_subcommand_from create' -l parent -r -d 'Parent issue ID for hierarchical child (e.g., \'bd-a3f8e9\')'
+complete -c bd -n '__fish_seen_subcommand_from create' -l priority -s p -r -d 'Priority (0-4 or P0-P4, 0=highest) (default "2")'
+complete -c bd -n '__fish_seen_subcommand_from create' -l title -r -d 'Issue title (alternative to positional argument)'
+complete -c bd -n '__fish_seen_subcommand_from create' -l type -s t -r -d 'Issue type (bug|feature|task|epic|chore) (default "task")'
+
+# Flags for 'delete'
+complete -c bd -n '__fish_seen_subcommand_from delete' -l cascade -d 'Recursively delete all dependent issues'
+complete -c bd -n '__fish_seen_subcommand_from delete' -l dry-run -d 'Preview what would be deleted without making changes'
+complete -c bd -n '__fish_seen_subcommand_from delete' -l force -d 'Actually delete (without this flag, shows preview)'
+complete -c bd -n '__fish_seen_subcommand_from delete' -l from-file -r -F -d 'Read issue IDs from file (one per line)'
+complete -c bd -n '__fish_seen_subcommand_from delete' -l help -s h -d 'help for delete'
+
+# Flags for 'dep'
+complete -c bd -n '__fish_seen_subcommand_from dep' -l help -s h -d 'help for dep'
+
+# Subcommands of 'dep'
+complete -c bd -n '__fish_seen_subcommand_from dep; and __bd_at_command dep' -a add -d 'Add a dependency'
+complete -c bd -n '__fish_seen_subcommand_from dep; and __bd_at_command dep' -a cycles -d 'Detect dependency cycles'
+complete -c bd -n '__fish_seen_subcommand_from dep; and __bd_at_command dep' -a remove -d 'Remove a dependency'
+complete -c bd -n '__fish_seen_subcommand_from dep; and __bd_at_command dep' -a rm -d 'Remove a dependency'
+complete -c bd -n '__fish_seen_subcommand_from dep; and __bd_at_command dep' -a tree -d 'Show dependency tree'
+
+# Flags for 'add'
+complete -c bd -n '__fish_seen_subcommand_from add' -l help -s h -d 'help for add'
+complete -c bd -n '__fish_seen_subcommand_from add' -l type -s t -r -d 'Dependency type (blocks|related|parent-child|discovered-from) (default "blocks")'
+
+# Flags for 'cycles'
+complete -c bd -n '__fish_seen_subcommand_from cycles' -l help -s h -d 'help for cycles'
+
+# Flags for 'remove'
+complete -c bd -n '__fish_seen_subcommand_from remove' -l help -s h -d 'help for remove'
+
+# Flags for 'tree'
+complete -c bd -n '__fish_seen_subcommand_from tree' -l help -s h -d 'help for tree'
+complete -c bd -n '__fish_seen_subcommand_from tree' -l max-depth -s d -r -d 'Maximum tree depth to display (safety limit) (default 50)'
+complete -c bd -n '__fish_seen_subcommand_from tree' -l reverse -d 'Show dependent tree (what was discovered from this) instead of dependency tree'
+complete -c bd -n '__fish_seen_subcommand_from tree' -l show-all-paths -d 'Show all paths to nodes (no deduplication for diamond dependencies)'
+
+# Flags for 'epic'
+complete -c bd -n '__fish_seen_subcommand_from epic' -l help -s h -d 'help for epic'
+
+# Subcommands of 'epic'
+complete -c bd -n '__fish_seen_subcommand_from epic; and __bd_at_command epic' -a close-eligible -d 'Close epics where all children are complete'
+complete -c bd -n '__fish_seen_subcommand_from epic; and __bd_at_command epic' -a status -d 'Show epic completion status'
+
+# Flags for 'close-eligible'
+complete -c bd -n '__fish_seen_subcommand_from close-eligible' -l dry-run -d 'Preview what would be closed without making changes'
+complete -c bd -n '__fish_seen_subcommand_from close-eligible' -l help -s h -d 'help for close-eligible'
+
+# Flags for 'status'
+complete -c bd -n '__fish_seen_subcommand_from status' -l eligible-only -d 'Show only epics eligible for closure'
+complete -c bd -n '__fish_seen_subcommand_from status' -l help -s h -d 'help for status'
+
+# Flags for 'init'
+complete -c bd -n '__fish_seen_subcommand_from init' -l branch -r -d 'Git branch for beads commits (default: current branch)'
+complete -c bd -n '__fish_seen_subcommand_from init' -l help -s h -d 'help for init'
+complete -c bd -n '__fish_seen_subcommand_from init' -l prefix -s p -r -d 'Issue prefix (default: current directory name)'
+complete -c bd -n '__fish_seen_subcommand_from init' -l skip-merge-driver -d 'Skip git merge driver setup'
+
+# Flags for 'label'
+complete -c bd -n '__fish_seen_subcommand_from label' -l help -s h -d 'help for label'
+
+# Subcommands of 'label'
+complete -c bd -n '__fish_seen_subcommand_from label; and __bd_at_command label' -a add -d 'Add a label to one or more issues'
+complete -c bd -n '__fish_seen_subcommand_from label; and __bd_at_command label' -a list -d 'List labels for an issue'
+complete -c bd -n '__fish_seen_subcommand_from label; and __bd_at_command label' -a list-all -d 'List all unique labels in the database'
+complete -c bd -n '__fish_seen_subcommand_from label; and __bd_at_command label' -a remove -d 'Remove a label from one or more issues'
+
+# Flags for 'add'
+complete -c bd -n '__fish_seen_subcommand_from add' -l help -s h -d 'help for add'
+
+# Flags for 'list'
+complete -c bd -n '__fish_seen_subcommand_from list' -l help -s h -d 'help for list'
+
+# Flags for 'list-all'
+complete -c bd -n '__fish_seen_subcommand_from list-all' -l help -s h -d 'help for list-all'
+
+# Flags for 'remove'
+complete -c bd -n '__fish_seen_subcommand_from remove' -l help -s h -d 'help for remove'
+
+# Flags for 'list'
+complete -c bd -n '__fish_seen_subcommand_from list' -l all -d 'Show all issues (default excludes closed)'
+complete -c bd -n '__fish_seen_subcommand_from list; and contains -- (commandline -ct) --assigned' -l assigned -r -d 'Deprecated: use --assignee'
+complete -c bd -n '__fish_seen_subcommand_from list' -l assignee -s a -r -d 'Filter by assignee'
+complete -c bd -n '__fish_seen_subcommand_from list' -l format -r -d 'Output format: \'digraph\' (for golang.org/x/tools/cmd/digraph), \'dot\' (Graphviz), or Go template'
+complete -c bd -n '__fish_seen_subcommand_from list' -l help -s h -d 'help for list'
+complete -c bd -n '__fish_seen_subcommand_from list' -l label -s l -r -d 'Filter by labels (AND: must have ALL)'
+complete -c bd -n '__fish_seen_subcommand_from list' -l limit -s n -r -d 'Limit results'
+complete -c bd -n '__fish_seen_subcommand_from list' -l priority -s p -r -d 'Filter by priority (0-4: 0=critical, 1=high, 2=medium, 3=low, 4=backlog)'
+complete -c bd -n '__fish_seen_subcommand_from list' -l sort -r -d 'Sort by field: priority, created, updated, closed, status, id, title (default "priority")'
+complete -c bd -n '__fish_seen_subcommand_from list' -l status -s s -r -d 'Filter by status (open, in_progress, blocked, closed)'
+complete -c bd -n '__fish_seen_subcommand_from list' -l type -s t -r -d 'Filter by type (bug, feature, task, epic, chore)'
+
+# Flags for 'ready'
+complete -c bd -n '__fish_seen_subcommand_from ready' -l assignee -s a -r -d 'Filter by assignee'
+complete -c bd -n '__fish_seen_subcommand_from ready' -l help -s h -d 'help for ready'
+complete -c bd -n '__fish_seen_subcommand_from ready' -l limit -s n -r -d 'Maximum issues to show (default 10)'
+complete -c bd -n '__fish_seen_subcommand_from ready' -l priority -s p -r -d 'Filter by priority'
+complete -c bd -n '__fish_seen_subcommand_from ready' -l sort -s s -r -d 'Sort policy: hybrid (default), priority, oldest (default "hybrid")'
+
+# Flags for 'reopen'
+complete -c bd -n '__fish_seen_subcommand_from reopen' -l help -s h -d 'help for reopen'
+complete -c bd -n '__fish_seen_subcommand_from reopen' -l reason -s r -r -d 'Reason for reopening'
+
+# Flags for 'show'
+complete -c bd -n '__fish_seen_subcommand_from show' -l help -s h -d 'help for show'
+complete -c bd -n '__fish_seen_subcommand_from show' -l thread -d 'Show full conversation thread'
+
+# Flags for 'stats'
+complete -c bd -n '__fish_seen_subcommand_from stats' -l help -s h -d 'help for stats'
+
+# Flags for 'update'
+complete -c bd -n '__fish_seen_subcommand_from update' -l acceptance -r -d 'Acceptance criteria'
+complete -c bd -n '__fish_seen_subcommand_from update' -l add-label -r -d 'Add labels (repeatable)'
+complete -c bd -n '__fish_seen_subcommand_from update' -l assignee -s a -r -d 'Assignee'
+complete -c bd -n '__fish_seen_subcommand_from update' -l description -s d -r -d 'Issue description'
+complete -c bd -n '__fish_seen_subcommand_from update' -l help -s h -d 'help for update'
+complete -c bd -n '__fish_seen_subcommand_from update' -l notes -r -d 'Additional notes'
+complete -c bd -n '__fish_seen_subcommand_from update' -l priority -s p -r -d 'Priority (0-4 or P0-P4)'
+complete -c bd -n '__fish_seen_subcommand_from update' -l remove-label -r -d 'Remove labels (repeatable)'
+complete -c bd -n '__fish_seen_subcommand_from update' -l set-metadata -r -a '(string match -v -- "*=*" (commandline -ct))=' -d 'Set metadata key=value pairs (default [])'
+complete -c bd -n '__fish_seen_subcommand_from update' -l status -s s -r -d 'New status'
+complete -c bd -n '__fish_seen_subcommand_from update' -l title -r -d 'New title'
+
+# Flags for 'version'
+complete -c bd -n '__fish_seen_subcommand_from version' -l help -s h -d 'help for version'
+
@@ -0,0 +1,906 @@
+// Fig completion spec for bd
+// Generated by bd-complete
+
+const issueIds: Fig.Generator = {
+  script: ["bd", "list", "--json"],
+  postProcess: (output) => {
+    try {
+      return JSON.parse(output).map((issue: { id: string; title: string }) => ({
+        name: issue.id,
+        description: issue.title,
+      }));
+    } catch {
+      return [];
+    }
+  },
+};
+
+const completionSpec: Fig.Spec = {
+  name: "bd",
+  description: "Issues chained together like beads. A lightweight issue tracker with first-class dependency support.",
+  subcommands: [
+    {
+      name: "close",
+      description: "Close one or more issues",
+      options: [
+        {
+          name: "--force",
+          description: "Force close pinned issues",
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for close",
+        },
+        {
+          name: ["-r", "--reason"],
+          description: "Reason for closing (default \"Closed\")",
+          args: {
+            name: "string",
+            default: "Closed",
+          },
+        },
+      ],
+      args: {
+        name: "id",
+        isOptional: true,
+        isVariadic: true,
+        generators: issueIds,
+      },
+    },
+    {
+      name: ["create", "new"],
+      description: "Create a new issue (or multiple issues from markdown file)",
+      options: [
+        {
+          name: "--acceptance",
+          description: "Acceptance criteria",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-a", "--assignee"],
+          description: "Assignee",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--deps",
+          description: "Dependencies in format 'type:id' or 'id' (e.g., 'discovered-from:bd-20,blocks:bd-15' or 'bd-20')",
+          args: {
+            name: "strings",
+          },
+        },
+        {
+          name: ["-d", "--description"],
+          description: "Issue description",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--design",
+          description: "Design notes",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--due",
+          description: "Due date, absolute (2026-01-31) or relative (+3d); parsed in the local timezone",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-e", "--estimate"],
+          description: "Time estimate in minutes",
+          args: {
+            name: "int",
+          },
+        },
+        {
+          name: ["-f", "--file"],
+          description: "Create multiple issues from markdown file",
+          args: {
+            name: "string",
+            template: "filepaths",
+          },
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for create",
+        },
+        {
+          name: "--id",
+          description: "Explicit issue ID (e.g., 'bd-42' for partitioning)",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-l", "--labels"],
+          description: "Labels (comma-separated)",
+          args: {
+            name: "strings",
+          },
+        },
+        {
+          name: "--parent",
+          description: "Parent issue ID for hierarchical child (e.g., 'bd-a3f8e9')",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-p", "--priority"],
+          description: "Priority (0-4 or P0-P4, 0=highest) (default \"2\")",
+          args: {
+            name: "string",
+            default: "2",
+          },
+        },
+        {
+          name: "--title",
+          description: "Issue title (alternative to positional argument)",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-t", "--type"],
+          description: "Issue type (bug|feature|task|epic|chore) (default \"task\")",
+          args: {
+            name: "string",
+            default: "task",
+          },
+        },
+      ],
+      args: {
+        name: "title",
+        isOptional: true,
+      },
+    },
+    {
+      name: "delete",
+      description: "Delete one or more issues and clean up references",
+      options: [
+        {
+          name: "--cascade",
+          description: "Recursively delete all dependent issues",
+        },
+        {
+          name: "--dry-run",
+          description: "Preview what would be deleted without making changes",
+        },
+        {
+          name: "--force",
+          description: "Actually delete (without this flag, shows preview)",
+        },
+        {
+          name: "--from-file",
+          description: "Read issue IDs from file (one per line)",
+          args: {
+            name: "string",
+            template: "filepaths",
+          },
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for delete",
+        },
+      ],
+      args: [
+        {
+          name: "issue-id",
+          generators: issueIds,
+        },
+        {
+          name: "issue-id",
+          isOptional: true,
+          isVariadic: true,
+          generators: issueIds,
+        },
+      ],
+    },
+    {
+      name: "dep",
+      description: "Manage dependencies",
+      subcommands: [
+        {
+          name: "add",
+          description: "Add a dependency",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for add",
+            },
+            {
+              name: ["-t", "--type"],
+              description: "Dependency type (blocks|related|parent-child|discovered-from) (default \"blocks\")",
+              args: {
+                name: "string",
+                default: "blocks",
+              },
+            },
+          ],
+          args: [
+            {
+              name: "issue-id",
+              isOptional: true,
+              generators: issueIds,
+            },
+            {
+              name: "depends-on-id",
+              isOptional: true,
+              generators: issueIds,
+            },
+          ],
+        },
+        {
+          name: "cycles",
+          description: "Detect dependency cycles",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for cycles",
+            },
+          ],
+        },
+        {
+          name: ["remove", "rm"],
+          description: "Remove a dependency",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for remove",
+            },
+          ],
+          args: [
+            {
+              name: "issue-id",
+              isOptional: true,
+              generators: issueIds,
+            },
+            {
+              name: "depends-on-id",
+              isOptional: true,
+              generators: issueIds,
+            },
+          ],
+        },
+        {
+          name: "tree",
+          description: "Show dependency tree",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for tree",
+            },
+            {
+              name: ["-d", "--max-depth"],
+              description: "Maximum tree depth to display (safety limit) (default 50)",
+              args: {
+                name: "int",
+                default: "50",
+              },
+            },
+            {
+              name: "--reverse",
+              description: "Show dependent tree (what was discovered from this) instead of dependency tree",
+            },
+            {
+              name: "--show-all-paths",
+              description: "Show all paths to nodes (no deduplication for diamond dependencies)",
+            },
+          ],
+          args: {
+            name: "issue-id",
+            isOptional: true,
+            generators: issueIds,
+          },
+        },
+      ],
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for dep",
+        },
+      ],
+    },
+    {
+      name: "epic",
+      description: "Epic management commands",
+      subcommands: [
+        {
+          name: "close-eligible",
+          description: "Close epics where all children are complete",
+          options: [
+            {
+              name: "--dry-run",
+              description: "Preview what would be closed without making changes",
+            },
+            {
+              name: ["-h", "--help"],
+              description: "help for close-eligible",
+            },
+          ],
+        },
+        {
+          name: "status",
+          description: "Show epic completion status",
+          options: [
+            {
+              name: "--eligible-only",
+              description: "Show only epics eligible for closure",
+            },
+            {
+              name: ["-h", "--help"],
+              description: "help for status",
+            },
+          ],
+        },
+      ],
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for epic",
+        },
+      ],
+    },
+    {
+      name: "label",
+      description: "Manage issue labels",
+      subcommands: [
+        {
+          name: "add",
+          description: "Add a label to one or more issues",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for add",
+            },
+          ],
+          args: [
+            {
+              name: "issue-id",
+              isOptional: true,
+              isVariadic: true,
+              generators: issueIds,
+            },
+            {
+              name: "label",
+              isOptional: true,
+            },
+          ],
+        },
+        {
+          name: "list",
+          description: "List labels for an issue",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for list",
+            },
+          ],
+          args: {
+            name: "issue-id",
+            isOptional: true,
+            generators: issueIds,
+          },
+        },
+        {
+          name: "list-all",
+          description: "List all unique labels in the database",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for list-all",
+            },
+          ],
+        },
+        {
+          name: "remove",
+          description: "Remove a label from one or more issues",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for remove",
+            },
+          ],
+          args: [
+            {
+              name: "issue-id",
+              isOptional: true,
+              isVariadic: true,
+              generators: issueIds,
+            },
+            {
+              name: "label",
+              isOptional: true,
+            },
+          ],
+        },
+      ],
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for label",
+        },
+      ],
+    },
+    {
+      name: "list",
+      description: "List issues",
+      options: [
+        {
+          name: "--all",
+          description: "Show all issues (default excludes closed)",
+        },
+        {
+          name: "--assigned",
+          description: "Deprecated: use --assignee",
+          deprecated: true,
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-a", "--assignee"],
+          description: "Filter by assignee",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--format",
+          description: "Output format: 'digraph' (for golang.org/x/tools/cmd/digraph), 'dot' (Graphviz), or Go template",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for list",
+        },
+        {
+          name: ["-l", "--label"],
+          description: "Filter by labels (AND: must have ALL)",
+          args: {
+            name: "strings",
+          },
+        },
+        {
+          name: ["-n", "--limit"],
+          description: "Limit results",
+          args: {
+            name: "int",
+          },
+        },
+        {
+          name: ["-p", "--priority"],
+          description: "Filter by priority (0-4: 0=critical, 1=high, 2=medium, 3=low, 4=backlog)",
+          args: {
+            name: "int",
+          },
+        },
+        {
+          name: "--sort",
+          description: "Sort by field: priority, created, updated, closed, status, id, title (default \"priority\")",
+          args: {
+            name: "string",
+            default: "priority",
+          },
+        },
+        {
+          name: ["-s", "--status"],
+          description: "Filter by status (open, in_progress, blocked, closed)",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-t", "--type"],
+          description: "Filter by type (bug, feature, task, epic, chore)",
+          args: {
+            name: "string",
+          },
+        },
+      ],
+    },
+    {
+      name: "reopen",
+      description: "Reopen one or more closed issues",
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for reopen",
+        },
+        {
+          name: ["-r", "--reason"],
+          description: "Reason for reopening",
+          args: {
+            name: "string",
+          },
+        },
+      ],
+      args: {
+        name: "id",
+        isOptional: true,
+        isVariadic: true,
+        generators: issueIds,
+      },
+    },
+    {
+      name: "show",
+      description: "Show issue details",
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for show",
+        },
+        {
+          name: "--thread",
+          description: "Show full conversation thread",
+        },
+      ],
+      args: {
+        name: "id",
+        isOptional: true,
+        isVariadic: true,
+        generators: issueIds,
+      },
+    },
+    {
+      name: "update",
+      description: "Update one or more issues",
+      options: [
+        {
+          name: "--acceptance",
+          description: "Acceptance criteria",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--add-label",
+          description: "Add labels (repeatable)",
+          args: {
+            name: "strings",
+          },
+        },
+        {
+          name: ["-a", "--assignee"],
+          description: "Assignee",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-d", "--description"],
+          description: "Issue description",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for update",
+        },
+        {
+          name: "--notes",
+          description: "Additional notes",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-p", "--priority"],
+          description: "Priority (0-4 or P0-P4)",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--remove-label",
+          description: "Remove labels (repeatable)",
+          args: {
+            name: "strings",
+          },
+        },
+        {
+          name: "--set-metadata",
+          description: "Set metadata key=value pairs (default [])",
+          args: {
+            name: "stringToString",
+            default: "[]",
+          },
+        },
+        {
+          name: ["-s", "--status"],
+          description: "New status",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--title",
+          description: "New title",
+          args: {
+            name: "string",
+          },
+        },
+      ],
+      args: {
+        name: "id",
+        isOptional: true,
+        isVariadic: true,
+        generators: issueIds,
+      },
+    },
+    {
+      name: "blocked",
+      description: "Show blocked issues",
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for blocked",
+        },
+      ],
+    },
+    {
+      name: "count",
+      description: "Count issues matching filters",
+      options: [
+        {
+          name: ["-a", "--assignee"],
+          description: "Filter by assignee",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: "--by-status",
+          description: "Group count by status",
+        },
+        {
+          name: "--by-type",
+          description: "Group count by issue type",
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for count",
+        },
+        {
+          name: ["-s", "--status"],
+          description: "Filter by status (open, in_progress, blocked, closed)",
+          args: {
+            name: "string",
+          },
+        },
+      ],
+    },
+    {
+      name: "ready",
+      description: "Show ready work (no blockers, open or in-progress)",
+      options: [
+        {
+          name: ["-a", "--assignee"],
+          description: "Filter by assignee",
+          args: {
+            name: "string",
+          },
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for ready",
+        },
+        {
+          name: ["-n", "--limit"],
+          description: "Maximum issues to show (default 10)",
+          args: {
+            name: "int",
+            default: "10",
+          },
+        },
+        {
+          name: ["-p", "--priority"],
+          description: "Filter by priority",
+          args: {
+            name: "int",
+          },
+        },
+        {
+          name: ["-s", "--sort"],
+          description: "Sort policy: hybrid (default), priority, oldest (default \"hybrid\")",
+          args: {
+            name: "string",
+          },
+        },
+      ],
+    },
+    {
+      name: ["stats", "status"],
+      description: "Show statistics",
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for stats",
+        },
+      ],
+    },
+    {
+      name: "config",
+      description: "Manage configuration settings",
+      subcommands: [
+        {
+          name: "get",
+          description: "Get a configuration value",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for get",
+            },
+          ],
+          args: {
+            name: "key",
+          },
+        },
+        {
+          name: "list",
+          description: "List all configuration",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for list",
+            },
+          ],
+        },
+        {
+          name: "set",
+          description: "Set a configuration value",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for set",
+            },
+          ],
+          args: [
+            {
+              name: "key",
+            },
+            {
+              name: "value",
+            },
+          ],
+        },
+        {
+          name: "unset",
+          description: "Delete a configuration value",
+          options: [
+            {
+              name: ["-h", "--help"],
+              description: "help for unset",
+            },
+          ],
+          args: {
+            name: "key",
+          },
+        },
+      ],
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for config",
+        },
+      ],
+    },
+    {
+      name: "init",
+      description: "Initialize bd in the current directory",
+      options: [
+        {
+          name: "--branch",
+          description: "Git branch for beads commits (default: current branch)",
+          args: {
+            name: "string",
+            default: "current branch",
+          },
+        },
+        {
+          name: ["-h", "--help"],
+          description: "help for init",
+        },
+        {
+          name: ["-p", "--prefix"],
+          description: "Issue prefix (default: current directory name)",
+          args: {
+            name: "string",
+            default: "current directory name",
+          },
+        },
+        {
+          name: "--skip-merge-driver",
+          description: "Skip git merge driver setup",
+        },
+      ],
+    },
+    {
+      name: "version",
+      description: "Print version information",
+      options: [
+        {
+          name: ["-h", "--help"],
+          description: "help for version",
+        },
+      ],
+    },
+  ],
+  options: [
+    {
+      name: "--actor",
+      description: "Actor name for audit trail (default: $BD_ACTOR or $USER)",
+      isPersistent: true,
+      args: {
+        name: "string",
+        default: "$BD_ACTOR or $USER",
+      },
+    },
+    {
+      name: "--db",
+      description: "Database path (default: auto-discover .beads/*.db)",
+      isPersistent: true,
+      args: {
+        name: "string",
+        default: "auto-discover .beads/*.db",
+        template: "filepaths",
+      },
+    },
+    {
+      name: "--json",
+      description: "Output in JSON format",
+      isPersistent: true,
+    },
+    {
+      name: "--lock-timeout",
+      description: "SQLite busy timeout (default 30s)",
+      isPersistent: true,
+      args: {
+        name: "duration",
+        default: "30s",
+      },
+    },
+    {
+      name: "--no-daemon",
+      description: "Force direct storage mode, bypass daemon if running",
+      isPersistent: true,
+    },
+    {
+      name: ["-q", "--quiet"],
+      description: "Suppress non-essential output (errors only)",
+      isPersistent: true,
+    },
+    {
+      name: "--sandbox",
+      description: "Deprecated: use --no-daemon",
+      isPersistent: true,
+      deprecated: true,
+    },
+    {
+      name: ["-v", "--verbose"],
+      description: "Enable verbose/debug output",
+      isPersistent: true,
+    },
+    {
+      name: ["-h", "--help"],
+      description: "help for bd",
+    },
+    {
+      name: "--version",
+      description: "version for bd",
+    },
+  ],
+};
+
+export default completionSpec;